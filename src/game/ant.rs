@@ -0,0 +1,244 @@
+use std::fmt;
+use ::game::board;
+
+// Direction an ant is facing, the y axis grows downwards
+#[derive(Copy,Clone,PartialEq,Debug)]
+pub enum Heading {
+    Up,
+    Right,
+    Down,
+    Left
+}
+
+impl Heading {
+    fn turn(self, turn: Turn) -> Heading {
+        let headings = [Heading::Up, Heading::Right, Heading::Down, Heading::Left];
+        let quarters = match turn {
+            Turn::None => 0,
+            Turn::Right => 1,
+            Turn::Back => 2,
+            Turn::Left => 3,
+        };
+        headings[(self as usize + quarters) % 4]
+    }
+
+    fn delta(self) -> (isize, isize) {
+        match self {
+            Heading::Up => (0, -1),
+            Heading::Right => (1, 0),
+            Heading::Down => (0, 1),
+            Heading::Left => (-1, 0),
+        }
+    }
+
+    // Character shown for an ant facing this way
+    pub fn to_char(self) -> char {
+        match self {
+            Heading::Up => '^',
+            Heading::Right => '>',
+            Heading::Down => 'v',
+            Heading::Left => '<',
+        }
+    }
+}
+
+#[derive(Copy,Clone,PartialEq,Debug)]
+enum Turn {
+    None,
+    Right,
+    Back,
+    Left
+}
+
+// Turn made by an ant on each colour, the cell then takes the next colour.
+// Langton's ant is "RL"
+#[derive(Clone,PartialEq,Debug)]
+pub struct AntRule {
+    turns: Vec<Turn>
+}
+
+impl AntRule {
+    pub fn colours(&self) -> usize {
+        self.turns.len()
+    }
+}
+
+// Parses a turmite rule made of L (left), R (right), N (no turn) and U (u-turn), one per colour
+pub fn parse_ant_rule(input: &str) -> Result<AntRule, &str> {
+    let turns: Option<Vec<Turn>> = input.chars().map(|c| match c.to_ascii_uppercase() {
+        'L' => Some(Turn::Left),
+        'R' => Some(Turn::Right),
+        'N' => Some(Turn::None),
+        'U' => Some(Turn::Back),
+        _ => None,
+    }).collect();
+
+    match turns {
+        Some(ref turns) if turns.len() < 2 => Err("Invalid ant rule: at least two colours are needed"),
+        Some(ref turns) if turns.len() > 255 => Err("Invalid ant rule: at most 255 colours are supported"),
+        Some(turns) => Ok(AntRule { turns }),
+        None => Err("Invalid ant rule: expected a string of L, R, N or U"),
+    }
+}
+
+impl fmt::Display for AntRule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for turn in &self.turns {
+            write!(f, "{}", match *turn {
+                Turn::None => 'N',
+                Turn::Right => 'R',
+                Turn::Back => 'U',
+                Turn::Left => 'L',
+            })?;
+        }
+        Ok(())
+    }
+}
+
+// Colour of a cell as seen by the ants: dead cells are 0, alive cells 1
+// and cells with dying state d are d + 1
+pub fn colour(board: &board::Board, x: usize, y: usize) -> u8 {
+    if board.get(x, y).ok().unwrap() {
+        1
+    } else {
+        match board.dying(x, y).ok().unwrap() {
+            0 => 0,
+            d => d + 1,
+        }
+    }
+}
+
+fn set_colour(board: &mut board::Board, x: usize, y: usize, colour: u8) {
+    board.set(x, y, colour == 1).ok();
+    // the dying states are only allocated once a third colour is painted
+    let dying = colour.saturating_sub(1);
+    if dying != board.dying(x, y).ok().unwrap() {
+        board.set_dying(x, y, dying).ok();
+    }
+}
+
+#[derive(Clone,PartialEq,Debug)]
+pub struct Ant {
+    pub x: usize,
+    pub y: usize,
+    pub heading: Heading,
+    pub rule: AntRule
+}
+
+impl Ant {
+    // New ants face up
+    pub fn new(x: usize, y: usize, rule: AntRule) -> Ant {
+        Ant { x, y, heading: Heading::Up, rule }
+    }
+
+    // Turns according to the colour of the cell, paints it with the next colour and moves
+    // forward. Returns false when the ant walks off a bounded edge
+    pub fn step(&mut self, board: &mut board::Board) -> bool {
+        // cells painted by another rule with more colours are taken modulo the colours
+        let colours = self.rule.colours();
+        let current = colour(board, self.x, self.y) as usize % colours;
+        self.heading = self.heading.turn(self.rule.turns[current]);
+        set_colour(board, self.x, self.y, ((current + 1) % colours) as u8);
+
+        let (dx, dy) = self.heading.delta();
+        match board.neighbour(self.x, self.y, dx, dy) {
+            Some((x, y)) => {
+                self.x = x;
+                self.y = y;
+                true
+            },
+            None => false,
+        }
+    }
+}
+
+// Character shown for a cell when there are ants on the board: the heading of the
+// last ant on it, or its colour
+pub fn cell_char(board: &board::Board, ants: &[Ant], x: usize, y: usize) -> char {
+    match ants.iter().rev().find(|ant| ant.x == x && ant.y == y) {
+        Some(ant) => ant.heading.to_char(),
+        None => match colour(board, x, y) {
+            0 => '_',
+            1 => 'X',
+            c => ::std::char::from_digit(c as u32, 36).unwrap_or('#'),
+        },
+    }
+}
+
+pub fn render(board: &board::Board, ants: &[Ant]) -> String {
+    (0..board.height).fold("".to_string(), |acc, y| {
+        let line: String = (0..board.width).map(|x| cell_char(board, ants, x, y)).collect();
+        acc + &line + "\n"
+    })
+}
+
+#[test]
+fn ant_parse_rule() {
+    assert_eq!(parse_ant_rule("RL").ok().unwrap().colours(), 2);
+    assert_eq!(parse_ant_rule("rl").ok().unwrap().to_string(), "RL");
+    assert_eq!(parse_ant_rule("LLRR").ok().unwrap().to_string(), "LLRR");
+    assert_eq!(parse_ant_rule("RLNU").ok().unwrap().colours(), 4);
+    assert_eq!(parse_ant_rule("R").err(), Some("Invalid ant rule: at least two colours are needed"));
+    assert_eq!(parse_ant_rule("RLX").err(), Some("Invalid ant rule: expected a string of L, R, N or U"));
+    assert!(parse_ant_rule(&"RL".repeat(128)).is_err());
+}
+
+#[test]
+fn ant_heading_turn() {
+    assert_eq!(Heading::Up.turn(Turn::Right), Heading::Right);
+    assert_eq!(Heading::Up.turn(Turn::Left), Heading::Left);
+    assert_eq!(Heading::Left.turn(Turn::Right), Heading::Up);
+    assert_eq!(Heading::Down.turn(Turn::Back), Heading::Up);
+    assert_eq!(Heading::Right.turn(Turn::None), Heading::Right);
+}
+
+#[test]
+fn ant_langton_steps() {
+    let mut board = board::Board::new(10, 10);
+    let mut ant = Ant::new(5, 5, parse_ant_rule("RL").ok().unwrap());
+
+    // the first four steps turn right on white cells around a square
+    let expected = [(6, 5, Heading::Right), (6, 6, Heading::Down), (5, 6, Heading::Left), (5, 5, Heading::Up)];
+    for &(x, y, heading) in expected.iter() {
+        assert!(ant.step(&mut board));
+        assert_eq!((ant.x, ant.y, ant.heading), (x, y, heading));
+    }
+    assert_eq!(board.population(), 4);
+
+    // back on a black cell it turns left and clears it
+    assert!(ant.step(&mut board));
+    assert_eq!((ant.x, ant.y, ant.heading), (4, 5, Heading::Left));
+    assert_eq!(board.population(), 3);
+    assert_eq!(render(&board, &[ant]).lines().nth(5), Some("____<_X___"));
+}
+
+#[test]
+fn ant_colours() {
+    let mut board = board::Board::new(3, 3);
+    let rule = parse_ant_rule("NNNN").ok().unwrap();
+
+    // without turning, every ant paints the cell it leaves with the next colour
+    for c in 1..4 {
+        let mut ant = Ant::new(1, 1, rule.clone());
+        assert!(ant.step(&mut board));
+        assert_eq!(colour(&board, 1, 1), c);
+    }
+    assert_eq!(cell_char(&board, &[], 1, 1), '3');
+    let mut ant = Ant::new(1, 1, rule);
+    ant.step(&mut board);
+    assert_eq!(colour(&board, 1, 1), 0);
+}
+
+#[test]
+fn ant_edges() {
+    let rule = parse_ant_rule("NL").ok().unwrap();
+
+    let mut board = board::Board::new(3, 3);
+    let mut ant = Ant::new(0, 0, rule.clone());
+    assert!(!ant.step(&mut board));
+
+    let mut board = board::Board::with_topology(3, 3, board::Topology::Torus);
+    let mut ant = Ant::new(0, 0, rule);
+    assert!(ant.step(&mut board));
+    assert_eq!((ant.x, ant.y), (0, 2));
+}
@@ -1,24 +1,212 @@
 use std::fmt;
+use std::thread;
+use ::game::ltl;
+use ::game::period;
+use ::game::random;
+use ::game::rule;
 
+// How the edges of the board are connected when counting neighbours
+#[derive(Copy,Clone,PartialEq,Debug)]
+pub enum Topology {
+    Bounded,    // cells outside the board are always dead
+    Torus,      // wraps around both axes
+    Cylinder    // wraps around the horizontal axis only
+}
+
+impl Topology {
+    fn wraps_x(&self) -> bool {
+        *self != Topology::Bounded
+    }
+
+    fn wraps_y(&self) -> bool {
+        *self == Topology::Torus
+    }
+}
+
+pub fn parse_topology(input: &str) -> Result<Topology, &str> {
+    match input {
+        "bounded" | "b" => Ok(Topology::Bounded),
+        "torus" | "t" => Ok(Topology::Torus),
+        "cylinder" | "c" => Ok(Topology::Cylinder),
+        _ => Err("Invalid topology: expected bounded, torus or cylinder"),
+    }
+}
+
+impl fmt::Display for Topology {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Topology::Bounded => write!(f, "bounded"),
+            Topology::Torus => write!(f, "torus"),
+            Topology::Cylinder => write!(f, "cylinder"),
+        }
+    }
+}
+
+// How the cells are kept in memory
+#[derive(Copy,Clone,PartialEq,Debug)]
+pub enum Storage {
+    Cells,      // one bool per cell
+    Packed      // 64 cells per word, stepped with bitwise operations
+}
+
+pub fn parse_storage(input: &str) -> Result<Storage, &str> {
+    match input {
+        "cells" => Ok(Storage::Cells),
+        "packed" => Ok(Storage::Packed),
+        _ => Err("Invalid storage: expected cells or packed"),
+    }
+}
+
+impl fmt::Display for Storage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Storage::Cells => write!(f, "cells"),
+            Storage::Packed => write!(f, "packed"),
+        }
+    }
+}
+
+// Cells are kept either in board or in words depending on the storage, the other one is empty.
+// Packed rows start at a new word, cell x of row y is bit x % 64 of word y * row_words + x / 64
+// and the bits past the width in the last word of a row are always 0
 #[derive(Clone)]
 pub struct Board {
     pub width: usize,
     pub height: usize,
-    board: Vec<Vec<bool>>
+    pub topology: Topology,
+    pub storage: Storage,
+    board: Vec<Vec<bool>>,
+    words: Vec<u64>,
+    row_words: usize,
+    // tiles changed by the last generation or edited since then, row_words tiles per row of tiles
+    active: Vec<bool>,
+    // cells evaluated to compute the last generation
+    evaluated: usize,
+    // rule that computed the last generation, the active tiles only tell which cells can change with it
+    rule: Option<rule::Rule>,
+    // dying state of every cell (row-major) for Generations rules, empty while no cell is dying
+    dying: Vec<u8>
+}
+
+// Cells are grouped in tiles of one packed word by TILE_HEIGHT rows. Only the tiles that changed
+// in the last generation and their neighbours are evaluated when stepping
+const TILE_WIDTH: usize = 64;
+const TILE_HEIGHT: usize = 16;
+
+// Moves a coordinate by delta, wrapping around if needed.
+// Returns None when the coordinate falls outside a non wrapping edge
+fn shift(value: usize, delta: isize, size: usize, wraps: bool) -> Option<usize> {
+    let moved = value.wrapping_add(delta as usize);
+    if moved < size {
+        Some(moved)
+    } else if wraps {
+        Some((value as isize + delta).rem_euclid(size as isize) as usize)
+    } else {
+        None
+    }
+}
+
+// Adds a one bit value to each of the 64 counters kept bit-sliced in count, count[0] being the
+// lowest bit of every counter
+fn add_bits(count: &mut [u64; 4], value: u64) {
+    let mut carry = value;
+    for bits in count.iter_mut() {
+        let next_carry = *bits & carry;
+        *bits ^= carry;
+        carry = next_carry;
+    }
+}
+
+// Calls f with the index and the contents of every band, each one on its own thread
+// when there are several, and adds up the results
+fn run_bands<T: Send, F: Fn(usize, T) -> usize + Sync>(bands: Vec<T>, f: F) -> usize {
+    if bands.len() == 1 {
+        return bands.into_iter().map(|band| f(0, band)).sum();
+    }
+
+    let f = &f;
+    thread::scope(|scope| {
+        let handles: Vec<_> = bands.into_iter()
+            .enumerate()
+            .map(|(i, band)| scope.spawn(move || f(i, band)))
+            .collect();
+        handles.into_iter().map(|handle| handle.join().unwrap()).sum()
+    })
+}
+
+// Bits set where the bit-sliced counters are equal to n
+fn count_equals(count: &[u64; 4], n: u8) -> u64 {
+    count.iter().enumerate().fold(!0, |acc, (i, &bits)| {
+        acc & if n & (1 << i) != 0 { bits } else { !bits }
+    })
 }
 
 impl Board {
     pub fn new(width: usize, height: usize) -> Board {
+        Board::with_topology(width, height, Topology::Bounded)
+    }
+
+    pub fn with_topology(width: usize, height: usize, topology: Topology) -> Board {
+        Board::with_storage(width, height, topology, Storage::Cells)
+    }
+
+    pub fn with_storage(width: usize, height: usize, topology: Topology, storage: Storage) -> Board {
+        let row_words = width.div_ceil(64);
         Board {
-            width: width,
-            height: height,
-            board: vec![vec![false; width]; height]
+            width,
+            height,
+            topology,
+            storage,
+            board: if storage == Storage::Cells { vec![vec![false; width]; height] } else { Vec::new() },
+            words: if storage == Storage::Packed { vec![0; row_words * height] } else { Vec::new() },
+            row_words,
+            active: vec![true; row_words * height.div_ceil(TILE_HEIGHT)],
+            evaluated: 0,
+            rule: None,
+            dying: Vec::new()
         }
     }
 
+    pub fn evaluated(&self) -> usize {
+        self.evaluated
+    }
+
+    fn tile(&self, x: usize, y: usize) -> usize {
+        (y / TILE_HEIGHT) * self.row_words + x / TILE_WIDTH
+    }
+
+    // Checks if a tile or any of its neighbours is active, so its cells may change
+    fn is_awake(&self, tile_x: usize, tile_y: usize) -> bool {
+        let tiles_y = self.height.div_ceil(TILE_HEIGHT);
+        for dx in -1..2 {
+            for dy in -1..2 {
+                let i = shift(tile_x, dx, self.row_words, self.topology.wraps_x());
+                let j = shift(tile_y, dy, tiles_y, self.topology.wraps_y());
+                if let (Some(i), Some(j)) = (i, j) {
+                    if self.active[j * self.row_words + i] {
+                        return true;
+                    }
+                }
+            }
+        }
+        false
+    }
+
+    // Word and bit of a packed cell
+    fn bit(&self, x: usize, y: usize) -> (usize, u64) {
+        (y * self.row_words + x / 64, 1 << (x % 64))
+    }
+
     pub fn set(&mut self, width: usize, height: usize, value: bool) -> Result<(), &str> {
         if width < self.width && height < self.height {
-            self.board[height][width] = value;
+            let tile = self.tile(width, height);
+            self.active[tile] = true;
+            if self.storage == Storage::Packed {
+                let (word, bit) = self.bit(width, height);
+                if value { self.words[word] |= bit; } else { self.words[word] &= !bit; }
+            } else {
+                self.board[height][width] = value;
+            }
             Ok(())
         } else {
             Err("Invalid indexes")
@@ -27,7 +215,12 @@ impl Board {
 
     pub fn get(&self, width: usize, height: usize) -> Result<bool, &str> {
         if width < self.width && height < self.height {
-            Ok(self.board[height][width])
+            if self.storage == Storage::Packed {
+                let (word, bit) = self.bit(width, height);
+                Ok(self.words[word] & bit != 0)
+            } else {
+                Ok(self.board[height][width])
+            }
         } else {
             Err("Invalid indexes")
         }
@@ -35,31 +228,507 @@ impl Board {
 
     pub fn toggle(&mut self, width: usize, height: usize) -> Result<(), &str> {
         if width < self.width && height < self.height {
-            self.board[height][width] = !self.board[height][width];
+            let tile = self.tile(width, height);
+            self.active[tile] = true;
+            if self.storage == Storage::Packed {
+                let (word, bit) = self.bit(width, height);
+                self.words[word] ^= bit;
+            } else {
+                self.board[height][width] = !self.board[height][width];
+            }
+            Ok(())
+        } else {
+            Err("Invalid indexes")
+        }
+    }
+
+    // Dying state of a cell, 0 if it is alive or dead
+    pub fn dying(&self, width: usize, height: usize) -> Result<u8, &str> {
+        if width < self.width && height < self.height {
+            Ok(self.dying.get(height * self.width + width).cloned().unwrap_or(0))
+        } else {
+            Err("Invalid indexes")
+        }
+    }
+
+    pub fn set_dying(&mut self, width: usize, height: usize, dying: u8) -> Result<(), &str> {
+        if width < self.width && height < self.height {
+            if self.dying.is_empty() {
+                self.dying = vec![0; self.width * self.height];
+            }
+            let tile = self.tile(width, height);
+            self.active[tile] = true;
+            self.dying[height * self.width + width] = dying;
             Ok(())
         } else {
             Err("Invalid indexes")
         }
     }
 
+    // Character shown for a cell with the given rule
+    pub fn cell_char(&self, width: usize, height: usize, rule: &rule::Rule) -> Result<char, &str> {
+        Ok(rule::cell_char(rule, self.get(width, height)?, self.dying(width, height)?))
+    }
+
+    // Cell at the given offset from another one, None past an edge that does not wrap
+    pub fn neighbour(&self, width: usize, height: usize, dx: isize, dy: isize) -> Option<(usize, usize)> {
+        let x = shift(width, dx, self.width, self.topology.wraps_x())?;
+        let y = shift(height, dy, self.height, self.topology.wraps_y())?;
+        Some((x, y))
+    }
+
+    // Rows of the board as shown with the given rule, Display uses Life. Hexagonal rules show the
+    // cells spaced and every row shifted half a cell to the left of the previous one, so the six
+    // neighbours of a cell are the ones around it
+    pub fn render(&self, rule: &rule::Rule) -> String {
+        let hexagonal = rule.neighbourhood() == rule::Neighbourhood::Hexagonal;
+        (0..self.height).fold("".to_string(), |acc, y| {
+            let cells = (0..self.width).map(|x| self.cell_char(x, y, rule).ok().unwrap());
+            let line: String = if hexagonal {
+                let indent = " ".repeat(self.height - 1 - y);
+                indent + &cells.map(|c| c.to_string()).collect::<Vec<String>>().join(" ")
+            } else {
+                cells.collect()
+            };
+            acc + &line + "\n"
+        })
+    }
+
+    pub fn population(&self) -> usize {
+        if self.storage == Storage::Packed {
+            self.words.iter().map(|word| word.count_ones() as usize).sum()
+        } else {
+            self.board.iter().map(|row| row.iter().filter(|&&cell| cell).count()).sum()
+        }
+    }
+
+    // Cells x to x + 63 of row y as the bits of a word, the ones past the width are 0
+    fn row_bits(&self, x: usize, y: usize) -> u64 {
+        if self.storage == Storage::Packed {
+            let row = &self.words[y * self.row_words..(y + 1) * self.row_words];
+            let (i, shift) = (x / 64, x % 64);
+            let high = if shift > 0 && i + 1 < row.len() { row[i + 1] << (64 - shift) } else { 0 };
+            row[i] >> shift | high
+        } else {
+            self.board[y][x..].iter().take(64).enumerate().filter(|&(_, &cell)| cell).fold(0, |bits, (i, _)| bits | 1 << i)
+        }
+    }
+
+    // Signature of the alive cells for the period detection, None when there are none. The rows
+    // are read 64 cells at a time, without listing the cells
+    pub fn signature(&self) -> Option<period::Signature> {
+        let (mut x_min, mut population, mut rows) = (self.width, 0, None);
+        for y in 0..self.height {
+            let (first, count) = if self.storage == Storage::Packed {
+                let row = &self.words[y * self.row_words..(y + 1) * self.row_words];
+                let first = row.iter().position(|&word| word != 0).map(|i| i * 64 + row[i].trailing_zeros() as usize);
+                (first, row.iter().map(|word| word.count_ones() as usize).sum())
+            } else {
+                (self.board[y].iter().position(|&cell| cell), self.board[y].iter().filter(|&&cell| cell).count())
+            };
+            if let Some(x) = first {
+                x_min = x_min.min(x);
+                population += count;
+                rows = Some((rows.map_or(y, |(y_min, _)| y_min), y));
+            }
+        }
+        let (y_min, y_max) = rows?;
+
+        let mut hash: u64 = 0;
+        for y in y_min..=y_max {
+            for chunk in 0..(self.width - x_min).div_ceil(64) {
+                hash = hash.wrapping_add(period::chunk_hash(y - y_min, chunk, self.row_bits(x_min + chunk * 64, y)));
+            }
+        }
+        Some(period::Signature { hash, x: x_min as i64, y: y_min as i64, population })
+    }
+
+    // Adds the alive cells to the list, row by row
+    pub fn cells_into(&self, cells: &mut Vec<(i64, i64)>) {
+        for y in 0..self.height {
+            for chunk in 0..self.width.div_ceil(64) {
+                let mut bits = self.row_bits(chunk * 64, y);
+                while bits != 0 {
+                    cells.push(((chunk * 64) as i64 + bits.trailing_zeros() as i64, y as i64));
+                    bits &= bits - 1;
+                }
+            }
+        }
+    }
+
+    // Flips the cells that are alive in the other board, which must have the same shape
+    pub fn xor(&mut self, other: &Board) {
+        if self.storage == Storage::Packed {
+            for (word, &other) in self.words.iter_mut().zip(other.words.iter()) {
+                *word ^= other;
+            }
+        } else {
+            for (row, other) in self.board.iter_mut().zip(other.board.iter()) {
+                for (cell, &other) in row.iter_mut().zip(other.iter()) {
+                    *cell ^= other;
+                }
+            }
+        }
+        self.active.iter_mut().for_each(|tile| *tile = true);
+    }
+
+    // Cells with a different value in the other board, which must have the same size
+    pub fn diff(&self, other: &Board) -> Vec<(usize, usize)> {
+        let mut cells = Vec::new();
+        self.diff_into(other, &mut cells);
+        cells
+    }
+
+    // Adds the cells of diff to the list, so that its memory can be reused
+    pub fn diff_into(&self, other: &Board, cells: &mut Vec<(usize, usize)>) {
+        if self.storage == Storage::Packed && other.storage == Storage::Packed {
+            for (i, (word, other_word)) in self.words.iter().zip(other.words.iter()).enumerate() {
+                let mut changed = word ^ other_word;
+                while changed != 0 {
+                    let bit = changed.trailing_zeros() as usize;
+                    cells.push(((i % self.row_words) * 64 + bit, i / self.row_words));
+                    changed &= changed - 1;
+                }
+            }
+            return;
+        }
+
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if self.get(x, y) != other.get(x, y) {
+                    cells.push((x, y));
+                }
+            }
+        }
+    }
+
+    // Cells with a different dying state in the other board, which must have the same size,
+    // with both states xor'ed so the same value turns each state into the other one
+    pub fn dying_diff(&self, other: &Board) -> Vec<(usize, usize, u8)> {
+        let mut cells = Vec::new();
+        self.dying_diff_into(other, &mut cells);
+        cells
+    }
+
+    pub fn dying_diff_into(&self, other: &Board, cells: &mut Vec<(usize, usize, u8)>) {
+        if self.dying.is_empty() && other.dying.is_empty() {
+            return;
+        }
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let flipped = self.dying(x, y).ok().unwrap() ^ other.dying(x, y).ok().unwrap();
+                if flipped != 0 {
+                    cells.push((x, y, flipped));
+                }
+            }
+        }
+    }
+
+    // Advances the board one generation. Stochastic rules make the same choices on every call,
+    // step_into takes a different key for each generation
+    pub fn step(&mut self, rule: &rule::Rule) {
+        self.step_parallel(rule, 1);
+    }
+
+    pub fn step_parallel(&mut self, rule: &rule::Rule, threads: usize) {
+        let mut next = self.clone();
+        self.step_into(rule, threads, 0, &mut next);
+        *self = next;
+    }
+
+    // Checks if the other board has the same size, topology and storage, so it can hold the next generation
+    pub fn same_shape(&self, other: &Board) -> bool {
+        self.width == other.width && self.height == other.height &&
+            self.topology == other.topology && self.storage == other.storage
+    }
+
+    // Writes the next generation in the other board, which must have the same shape, reusing its memory.
+    // The rows are split in bands of whole tiles, one per thread. Every row only reads the previous
+    // generation, so the result does not depend on the number of threads. The cells of the tiles
+    // that are not awake can not change, so they are copied without evaluating them.
+    // The random choices of stochastic rules depend on the key and on the position of each cell
+    pub fn step_into(&self, rule: &rule::Rule, threads: usize, key: u64, next: &mut Board) {
+        let tiles_y = self.height.div_ceil(TILE_HEIGHT);
+        let band_tiles = tiles_y.div_ceil(threads.max(1)).max(1) * self.row_words;
+        let rows = band_tiles / self.row_words * TILE_HEIGHT;
+        let awake: Vec<bool> = (0..self.active.len())
+            .map(|tile| self.rule != Some(*rule) || rule.chances().is_some() || self.is_awake(tile % self.row_words, tile / self.row_words))
+            .collect();
+        next.rule = Some(*rule);
+
+        next.evaluated = if let Some(range) = rule.range() {
+            self.step_range(rule, &range, next)
+        } else if self.storage == Storage::Packed {
+            let births: Vec<u8> = (0..13).filter(|&n| rule.born(n)).collect();
+            let survivals: Vec<u8> = (0..13).filter(|&n| rule.survives(n)).collect();
+            let bands = next.words.chunks_mut(rows * self.row_words).zip(next.active.chunks_mut(band_tiles)).collect();
+
+            run_bands(bands, |band, (words, active)| {
+                let mut evaluated = 0;
+                active.iter_mut().for_each(|tile| *tile = false);
+                for (i, row) in words.chunks_mut(self.row_words).enumerate() {
+                    let y = band * rows + i;
+                    let tiles = (y / TILE_HEIGHT) * self.row_words;
+                    evaluated += self.step_packed_row(y, rule, &births, &survivals, &awake[tiles..tiles + self.row_words], row);
+
+                    for (x, (word, previous)) in row.iter().zip(self.row(y, 0).unwrap()).enumerate() {
+                        if word != previous {
+                            active[tiles - band * band_tiles + x] = true;
+                        }
+                    }
+                }
+                evaluated
+            })
+        } else {
+            let bands = next.board.chunks_mut(rows).zip(next.active.chunks_mut(band_tiles)).collect();
+
+            run_bands(bands, |band, (cells, active)| {
+                let mut evaluated = 0;
+                active.iter_mut().for_each(|tile| *tile = false);
+                for (i, row) in cells.iter_mut().enumerate() {
+                    let y = band * rows + i;
+                    for (x, cell) in row.iter_mut().enumerate() {
+                        let tile = self.tile(x, y);
+                        let is_alive = self.board[y][x];
+                        *cell = if awake[tile] {
+                            evaluated += 1;
+                            self.next_cell(rule, x, y, is_alive)
+                        } else {
+                            is_alive
+                        };
+                        if *cell != is_alive {
+                            active[tile - band * band_tiles] = true;
+                        }
+                    }
+                }
+                evaluated
+            })
+        };
+
+        if let Some(chances) = rule.chances() {
+            self.step_chances(&chances, key, next);
+        }
+        self.step_dying(rule, next);
+    }
+
+    // Undoes the births and survivals of the next generation that are not drawn with the
+    // probabilities of a stochastic rule. Any cell can change, so every tile is left active
+    fn step_chances(&self, chances: &rule::Chances, key: u64, next: &mut Board) {
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if next.get(x, y).ok().unwrap() && !self.drawn(chances, key, x, y) {
+                    next.set(x, y, false).ok();
+                }
+            }
+        }
+        next.active.iter_mut().for_each(|tile| *tile = true);
+    }
+
+    // Checks if the birth or survival of a cell happens with the probabilities of a stochastic rule
+    fn drawn(&self, chances: &rule::Chances, key: u64, x: usize, y: usize) -> bool {
+        let probability = if self.get(x, y).ok().unwrap() { chances.survival } else { chances.birth };
+        random::chance(key, (y * self.width + x) as u64, probability)
+    }
+
+    // Next state and dying state of a single cell, for the schedules that update the cells one
+    // at a time. Larger than Life rules are not supported, they count all the cells at once
+    pub fn next_state(&self, rule: &rule::Rule, x: usize, y: usize, key: u64) -> (bool, u8) {
+        let is_alive = self.get(x, y).ok().unwrap();
+        let alive = self.next_cell(rule, x, y, is_alive) && match rule.chances() {
+            Some(chances) => self.drawn(&chances, key, x, y),
+            None => true,
+        };
+        (alive, rule.next_dying(self.dying(x, y).ok().unwrap(), is_alive, alive))
+    }
+
+    // Sets the state and dying state of a cell, only writing the ones that change
+    pub fn set_state(&mut self, x: usize, y: usize, alive: bool, dying: u8) {
+        if self.get(x, y).ok().unwrap() != alive {
+            self.set(x, y, alive).ok();
+        }
+        if self.dying(x, y).ok().unwrap() != dying {
+            self.set_dying(x, y, dying).ok();
+        }
+    }
+
+    // Computes the next generation of a Larger than Life rule. The neighbours of all the cells are
+    // counted at once, so every cell is evaluated and every tile left active
+    fn step_range(&self, rule: &rule::Rule, range: &ltl::Range, next: &mut Board) -> usize {
+        let counts = ltl::counts(self, range);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let is_alive = self.get(x, y).ok().unwrap();
+                let count = counts[y * self.width + x];
+                let alive = if is_alive {
+                    range.survives(count)
+                } else {
+                    range.born(count) && rule.can_be_born(self.dying(x, y).ok().unwrap())
+                };
+                next.set(x, y, alive).ok();
+            }
+        }
+        next.active.iter_mut().for_each(|tile| *tile = true);
+        self.width * self.height
+    }
+
+    // Moves the dying cells to their next state once the alive cells of the next generation are known
+    fn step_dying(&self, rule: &rule::Rule, next: &mut Board) {
+        if self.dying.is_empty() && rule.states() <= 2 {
+            next.dying.clear();
+            return;
+        }
+
+        next.dying.resize(self.width * self.height, 0);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let dying = self.dying(x, y).ok().unwrap();
+                let is_alive = self.get(x, y).ok().unwrap();
+                let next_dying = rule.next_dying(dying, is_alive, next.get(x, y).ok().unwrap());
+                next.dying[y * self.width + x] = next_dying;
+                if next_dying != dying {
+                    let tile = self.tile(x, y);
+                    next.active[tile] = true;
+                }
+            }
+        }
+    }
+
+    // Row of words of a packed board, None for the rows outside a non wrapping edge
+    fn row(&self, y: usize, delta: isize) -> Option<&[u64]> {
+        shift(y, delta, self.height, self.topology.wraps_y())
+            .map(|y| &self.words[y * self.row_words..(y + 1) * self.row_words])
+    }
+
+    // Words with the left and right neighbours of the cells of word i of a row
+    fn shifted(&self, row: &[u64], i: usize) -> (u64, u64) {
+        let wraps = self.topology.wraps_x();
+        let word = row[i];
+
+        let previous = if i > 0 {
+            row[i - 1]
+        } else if wraps {
+            // the last cell of the row, moved to the highest bit
+            (row[self.row_words - 1] >> ((self.width - 1) % 64)) << 63
+        } else {
+            0
+        };
+
+        let mut extended = word;
+        let mut next = if i + 1 < self.row_words { row[i + 1] } else { 0 };
+        if i + 1 == self.row_words && wraps {
+            // the first cell of the row goes right after the last one
+            let used = self.width - i * 64;
+            if used < 64 { extended |= (row[0] & 1) << used; } else { next = row[0] & 1; }
+        }
+
+        ((word << 1) | (previous >> 63), (extended >> 1) | (next << 63))
+    }
+
+    // Computes the next generation of the awake words of row y of a packed board, returns the number
+    // of cells evaluated. The 8 neighbours of 64 cells are counted at once with bit-sliced adders,
+    // then the rule is applied to every possible count
+    fn step_packed_row(&self, y: usize, rule: &rule::Rule, births: &[u8], survivals: &[u8], awake: &[bool], next: &mut [u64]) -> usize {
+        let mut evaluated = 0;
+        let last_mask = if self.width.is_multiple_of(64) { !0 } else { (1 << (self.width % 64)) - 1 };
+        let above = self.row(y, -1);
+        let centre = self.row(y, 0).unwrap();
+        let below = self.row(y, 1);
+        // left, centre and right words added for the rows above, at and below y
+        let neighbourhood = rule.neighbourhood();
+        let offsets = neighbourhood.offsets(0, 0);
+        let included: Vec<[bool; 3]> = (-1..2)
+            .map(|dy| [offsets.contains(&(-1, dy)), offsets.contains(&(0, dy)), offsets.contains(&(1, dy))])
+            .collect();
+
+        for (i, word) in next.iter_mut().enumerate() {
+            if !awake[i] {
+                *word = centre[i];
+                continue;
+            }
+            evaluated += if i + 1 == self.row_words { self.width - i * TILE_WIDTH } else { TILE_WIDTH };
+
+            if neighbourhood == rule::Neighbourhood::Triangular || rule.isotropic().is_some() {
+                // the neighbours depend on the orientation of every cell, or their arrangement matters
+                *word = 0;
+                for bit in 0..TILE_WIDTH.min(self.width - i * TILE_WIDTH) {
+                    if self.next_cell(rule, i * TILE_WIDTH + bit, y, centre[i] & (1 << bit) != 0) {
+                        *word |= 1 << bit;
+                    }
+                }
+                continue;
+            }
+
+            let mut count = [0; 4];
+            for (row, included) in [above, Some(centre), below].iter().zip(included.iter()) {
+                if let Some(row) = *row {
+                    let (left, right) = self.shifted(row, i);
+                    for (&word, &included) in [left, row[i], right].iter().zip(included.iter()) {
+                        if included {
+                            add_bits(&mut count, word);
+                        }
+                    }
+                }
+            }
+
+            let alive = centre[i];
+            *word = 0;
+            for &n in births {
+                *word |= !alive & count_equals(&count, n);
+            }
+            if !self.dying.is_empty() || !rule.can_be_born(0) {
+                // some dead cells can not be born, dying ones in Generations or empty ones in Wireworld
+                for bit in 0..TILE_WIDTH.min(self.width - i * TILE_WIDTH) {
+                    let dying = self.dying.get(y * self.width + i * TILE_WIDTH + bit).cloned().unwrap_or(0);
+                    if !rule.can_be_born(dying) {
+                        *word &= !(1 << bit);
+                    }
+                }
+            }
+            for &n in survivals {
+                *word |= alive & count_equals(&count, n);
+            }
+            if i + 1 == self.row_words {
+                *word &= last_mask;
+            }
+        }
+        evaluated
+    }
+
+    // Next state of a single cell, dead cells that can not be born stay dead
+    fn next_cell(&self, rule: &rule::Rule, x: usize, y: usize, is_alive: bool) -> bool {
+        let next = match rule.isotropic() {
+            Some(isotropic) => isotropic.next(is_alive, self.configuration(x, y)),
+            None => rule.next(is_alive, self.neighbours_in(x, y, rule.neighbourhood())),
+        };
+        next && (is_alive || rule.can_be_born(self.dying(x, y).ok().unwrap()))
+    }
+
+    // Arrangement of the alive Moore neighbours of a cell, bit i is set if the neighbour at
+    // offset i of the Moore neighbourhood is alive
+    pub fn configuration(&self, x: usize, y: usize) -> u8 {
+        rule::Neighbourhood::Moore.offsets(x, y).iter().enumerate().fold(0, |acc, (i, &(dx, dy))| {
+            match self.neighbour(x, y, dx, dy) {
+                Some((i_x, i_y)) if self.get(i_x, i_y).ok().unwrap() => acc | 1 << i,
+                _ => acc,
+            }
+        })
+    }
+
     pub fn neighbours(&self, x: usize, y: usize) -> u8 {
+        self.neighbours_in(x, y, rule::Neighbourhood::Moore)
+    }
 
+    // Alive neighbours of a cell in the given neighbourhood. Triangular neighbourhoods only
+    // wrap consistently around even sizes, the orientation of the cells alternates
+    pub fn neighbours_in(&self, x: usize, y: usize, neighbourhood: rule::Neighbourhood) -> u8 {
         let mut count: u8 = 0;
-        let x_min;
-        let x_max;
-        let y_min;
-        let y_max;
-        if x > 0 { x_min = x - 1; } else { x_min = 0; }
-        if x < self.width - 1 { x_max = x + 1; } else { x_max = self.width - 1; }
-        if y > 0 { y_min = y - 1; } else { y_min = 0; }
-        if y < self.height - 1 { y_max = y + 1; } else { y_max = self.height -1; }
-
-        for i in x_min..x_max + 1 {
-            for j in y_min..y_max + 1 {
-                if !(i == x && j == y) {
-                    if self.get(i, j).ok().unwrap() {
-                        count += 1;
-                    }
+
+        for &(dx, dy) in neighbourhood.offsets(x, y) {
+            let i = shift(x, dx, self.width, self.topology.wraps_x());
+            let j = shift(y, dy, self.height, self.topology.wraps_y());
+            if let (Some(i), Some(j)) = (i, j) {
+                if self.get(i, j).ok().unwrap() {
+                    count += 1;
                 }
             }
         }
@@ -70,11 +739,7 @@ impl Board {
 
 impl fmt::Display for Board {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let display = self.board.iter().fold("".to_string(), |acc, x| {
-            let line = x.iter().fold("".to_string(), |acc, x| acc + if *x { "X" } else { "_" });
-            acc + &line + "\n"
-        });
-        write!(f, "{}", display)
+        write!(f, "{}", self.render(&rule::Rule::default()))
     }
 }
 
@@ -87,6 +752,7 @@ fn board_constructor() {
 
     assert_eq!(b.width, W);
     assert_eq!(b.height, H);
+    assert_eq!(b.topology, Topology::Bounded);
 
     assert_eq!(b.board.len(), H);
     for i in 0..H {
@@ -95,7 +761,7 @@ fn board_constructor() {
 
     for i in 0..H {
         for j in 0..W {
-            assert_eq!(b.board[i][j], false);
+            assert!(!b.board[i][j]);
         }
     }
 }
@@ -123,22 +789,22 @@ fn board_set() {
     let mut b = Board::new(W, H);
 
     assert!(b.set(0, 0, true).is_ok());
-    assert_eq!(b.board[0][0], true);
+    assert!(b.board[0][0]);
     assert!(b.set(0, 0, false).is_ok());
-    assert_eq!(b.board[0][0], false);
+    assert!(!b.board[0][0]);
 
     // Invert width and height when accessing directly to the vector
     assert!(b.set(W - 1, 0, true).is_ok());
-    assert_eq!(b.board[0][W - 1], true);
+    assert!(b.board[0][W - 1]);
 
     assert!(b.set(0, H - 1, true).is_ok());
-    assert_eq!(b.board[H - 1][0], true);
+    assert!(b.board[H - 1][0]);
 
     assert!(b.set(W - 1, H - 1, true).is_ok());
-    assert_eq!(b.board[H - 1][W - 1], true);
+    assert!(b.board[H - 1][W - 1]);
 
     assert!(b.set(4, 1, true).is_ok());
-    assert_eq!(b.board[1][4], true);
+    assert!(b.board[1][4]);
 
     assert!(b.set(W, 0, true).is_err());
     assert!(b.set(0, H, true).is_err());
@@ -265,3 +931,702 @@ fn board_neighbours() {
     assert!(b.set(2, 3, true).is_ok());
     assert_eq!(b.neighbours(1, 2), 8);
 }
+
+#[test]
+fn board_topology() {
+    let b = Board::with_topology(5, 4, Topology::Torus);
+    assert_eq!(b.topology, Topology::Torus);
+
+    assert_eq!(parse_topology("bounded").ok(), Some(Topology::Bounded));
+    assert_eq!(parse_topology("b").ok(), Some(Topology::Bounded));
+    assert_eq!(parse_topology("torus").ok(), Some(Topology::Torus));
+    assert_eq!(parse_topology("t").ok(), Some(Topology::Torus));
+    assert_eq!(parse_topology("cylinder").ok(), Some(Topology::Cylinder));
+    assert_eq!(parse_topology("c").ok(), Some(Topology::Cylinder));
+    assert_eq!(parse_topology("sphere").err(), Some("Invalid topology: expected bounded, torus or cylinder"));
+
+    assert_eq!(Topology::Bounded.to_string(), "bounded");
+    assert_eq!(Topology::Torus.to_string(), "torus");
+    assert_eq!(Topology::Cylinder.to_string(), "cylinder");
+}
+
+#[test]
+fn board_neighbours_torus() {
+    const W: usize = 6;
+    const H: usize = 4;
+
+    let mut b = Board::with_topology(W, H, Topology::Torus);
+
+    assert!(b.set(0, 0, true).is_ok());
+    assert_eq!(b.neighbours(0, 0), 0);
+    assert_eq!(b.neighbours(1, 1), 1);
+    assert_eq!(b.neighbours(W - 1, 0), 1);
+    assert_eq!(b.neighbours(0, H - 1), 1);
+    assert_eq!(b.neighbours(W - 1, H - 1), 1);
+    assert_eq!(b.neighbours(W - 1, 1), 1);
+    assert_eq!(b.neighbours(2, 0), 0);
+    assert_eq!(b.neighbours(0, 2), 0);
+
+    assert!(b.set(W - 1, H - 1, true).is_ok());
+    assert!(b.set(W - 1, 0, true).is_ok());
+    assert!(b.set(0, H - 1, true).is_ok());
+    assert_eq!(b.neighbours(0, 0), 3);
+    assert_eq!(b.neighbours(W - 1, H - 1), 3);
+}
+
+#[test]
+fn board_neighbours_cylinder() {
+    const W: usize = 6;
+    const H: usize = 4;
+
+    let mut b = Board::with_topology(W, H, Topology::Cylinder);
+
+    assert!(b.set(0, 0, true).is_ok());
+    assert_eq!(b.neighbours(W - 1, 0), 1);
+    assert_eq!(b.neighbours(W - 1, 1), 1);
+    assert_eq!(b.neighbours(0, H - 1), 0);
+    assert_eq!(b.neighbours(W - 1, H - 1), 0);
+}
+
+#[test]
+fn board_population() {
+    let mut b = Board::new(4, 3);
+    assert_eq!(b.population(), 0);
+
+    assert!(b.set(1, 2, true).is_ok());
+    assert!(b.set(3, 0, true).is_ok());
+    assert_eq!(b.population(), 2);
+}
+
+#[test]
+fn board_diff() {
+    let mut b = Board::new(4, 3);
+    let mut other = b.clone();
+    assert!(b.diff(&other).is_empty());
+
+    assert!(b.set(1, 2, true).is_ok());
+    assert!(b.set(3, 0, true).is_ok());
+    assert!(other.set(3, 0, true).is_ok());
+    assert!(other.set(0, 0, true).is_ok());
+    assert_eq!(b.diff(&other), vec![(0, 0), (1, 2)]);
+    assert_eq!(other.diff(&b), vec![(0, 0), (1, 2)]);
+}
+
+#[test]
+fn board_storage() {
+    assert_eq!(parse_storage("cells").ok(), Some(Storage::Cells));
+    assert_eq!(parse_storage("packed").ok(), Some(Storage::Packed));
+    assert_eq!(parse_storage("bits").err(), Some("Invalid storage: expected cells or packed"));
+
+    assert_eq!(Storage::Cells.to_string(), "cells");
+    assert_eq!(Storage::Packed.to_string(), "packed");
+
+    let mut b = Board::with_storage(70, 3, Topology::Bounded, Storage::Packed);
+    assert_eq!(b.storage, Storage::Packed);
+    assert!(b.board.is_empty());
+    assert_eq!(b.words.len(), 6);
+
+    assert!(b.set(0, 0, true).is_ok());
+    assert!(b.set(69, 2, true).is_ok());
+    assert!(b.toggle(64, 1).is_ok());
+    assert!(b.toggle(0, 0).is_ok());
+    assert_eq!(b.get(0, 0).ok(), Some(false));
+    assert_eq!(b.get(64, 1).ok(), Some(true));
+    assert_eq!(b.get(69, 2).ok(), Some(true));
+    assert_eq!(b.get(70, 2).err(), Some("Invalid indexes"));
+    assert_eq!(b.population(), 2);
+    assert_eq!(b.neighbours(65, 2), 1);
+    assert_eq!(b.neighbours(68, 1), 1);
+
+    let mut other = Board::with_storage(70, 3, Topology::Bounded, Storage::Packed);
+    assert!(other.set(5, 0, true).is_ok());
+    assert!(other.set(64, 1, true).is_ok());
+    assert_eq!(b.diff(&other), vec![(5, 0), (69, 2)]);
+    let mut cells = Board::new(70, 3);
+    assert!(cells.set(64, 1, true).is_ok());
+    assert_eq!(b.diff(&cells), vec![(69, 2)]);
+
+    let b = Board::with_storage(3, 2, Topology::Bounded, Storage::Packed);
+    assert_eq!(b.to_string(), "___\n___\n");
+}
+
+// Board filled with pseudo-random cells, about a third of them alive, shared by the tests of
+// the steppers
+#[cfg(test)]
+pub fn random_board(width: usize, height: usize, topology: Topology, storage: Storage, seed: u64) -> Board {
+    let mut b = Board::with_storage(width, height, topology, storage);
+    let mut state = seed | 1;
+    for y in 0..height {
+        for x in 0..width {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            b.set(x, y, state.is_multiple_of(3)).ok();
+        }
+    }
+    b
+}
+
+#[test]
+fn board_step_packed() {
+    let rules = [rule::Rule::conway(), rule::Rule::new(&[3, 6], &[2, 3]), rule::Rule::new(&[1], &[0, 8]), rule::Rule::new(&[0, 2, 4, 6, 8], &[1, 3, 5, 7])];
+    let topologies = [Topology::Bounded, Topology::Torus, Topology::Cylinder];
+
+    for &(width, height) in &[(1, 1), (3, 5), (63, 4), (64, 3), (65, 6), (130, 7)] {
+        for &topology in topologies.iter() {
+            for (seed, rule) in rules.iter().enumerate() {
+                let mut cells = random_board(width, height, topology, Storage::Cells, seed as u64 + 7);
+                let mut packed = random_board(width, height, topology, Storage::Packed, seed as u64 + 7);
+
+                for _ in 0..8 {
+                    cells.step(rule);
+                    packed.step(rule);
+                    assert_eq!(packed.to_string(), cells.to_string());
+                    assert_eq!(packed.population(), cells.population());
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn board_step_parallel() {
+    for &storage in &[Storage::Cells, Storage::Packed] {
+        for &topology in &[Topology::Bounded, Topology::Torus] {
+            let sequential = random_board(100, 37, topology, storage, 3);
+
+            // more threads than rows gives bands of a single row
+            for &threads in &[2, 3, 8, 50] {
+                let mut parallel = random_board(100, 37, topology, storage, 3);
+                let mut expected = sequential.clone();
+                for _ in 0..5 {
+                    expected.step(&rule::Rule::conway());
+                    parallel.step_parallel(&rule::Rule::conway(), threads);
+                    assert_eq!(parallel.to_string(), expected.to_string());
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn board_step_into() {
+    let b = random_board(70, 9, Topology::Torus, Storage::Packed, 5);
+    let mut next = Board::with_storage(70, 9, Topology::Torus, Storage::Packed);
+    assert!(next.same_shape(&b));
+    assert!(!next.same_shape(&Board::with_storage(70, 9, Topology::Torus, Storage::Cells)));
+    assert!(!next.same_shape(&Board::with_storage(70, 9, Topology::Bounded, Storage::Packed)));
+    assert!(!next.same_shape(&Board::with_storage(70, 8, Topology::Torus, Storage::Packed)));
+
+    // the previous contents of the other board do not matter
+    assert!(next.set(3, 3, true).is_ok());
+    b.step_into(&rule::Rule::conway(), 2, 0, &mut next);
+
+    let mut expected = b.clone();
+    expected.step(&rule::Rule::conway());
+    assert_eq!(next.to_string(), expected.to_string());
+}
+
+// Next generation computed visiting every cell, without tiles nor packed words
+#[cfg(test)]
+fn full_scan(b: &Board, rule: &rule::Rule) -> Board {
+    let mut next = Board::with_topology(b.width, b.height, b.topology);
+    for x in 0..b.width {
+        for y in 0..b.height {
+            next.set(x, y, rule.next(b.get(x, y).ok().unwrap(), b.neighbours_in(x, y, rule.neighbourhood()))).ok();
+        }
+    }
+    next
+}
+
+#[test]
+fn board_step_active_tiles() {
+    let rules = [rule::Rule::conway(), rule::Rule::new(&[3, 6], &[2, 3])];
+
+    for &storage in &[Storage::Cells, Storage::Packed] {
+        for &topology in &[Topology::Bounded, Topology::Torus, Topology::Cylinder] {
+            // random cells in a corner, so they spread over quiet tiles and across the edges
+            let soup = random_board(20, 20, topology, storage, 11);
+            let mut b = Board::with_storage(150, 50, topology, storage);
+            for x in 0..20 {
+                for y in 0..20 {
+                    b.set(x + 120, y + 25, soup.get(x, y).ok().unwrap()).ok();
+                }
+            }
+
+            for generation in 0..60 {
+                // the rule changes in the middle of the run
+                let rule = &rules[generation / 30];
+                let expected = full_scan(&b, rule);
+                b.step_parallel(rule, 1 + generation % 3);
+                assert_eq!(b.to_string(), expected.to_string());
+            }
+        }
+    }
+}
+
+#[test]
+fn board_evaluated() {
+    for &storage in &[Storage::Cells, Storage::Packed] {
+        let mut b = Board::with_storage(256, 64, Topology::Bounded, storage);
+        assert_eq!(b.evaluated(), 0);
+
+        // the first generation evaluates every cell
+        b.step(&rule::Rule::conway());
+        assert_eq!(b.evaluated(), 256 * 64);
+        b.step(&rule::Rule::conway());
+        assert_eq!(b.evaluated(), 0);
+
+        // a blinker wakes its tile and the neighbour tiles
+        for &(x, y) in &[(69, 20), (70, 20), (71, 20)] {
+            assert!(b.set(x, y, true).is_ok());
+        }
+        b.step(&rule::Rule::conway());
+        assert_eq!(b.evaluated(), 3 * TILE_WIDTH * 3 * TILE_HEIGHT);
+        b.step(&rule::Rule::conway());
+        assert_eq!(b.evaluated(), 3 * TILE_WIDTH * 3 * TILE_HEIGHT);
+        assert_eq!(b.population(), 3);
+
+        // a block is still, so its tiles go quiet after a generation
+        let mut b = Board::with_storage(256, 64, Topology::Bounded, storage);
+        b.step(&rule::Rule::conway());
+        for &(x, y) in &[(10, 10), (11, 10), (10, 11), (11, 11)] {
+            assert!(b.toggle(x, y).is_ok());
+        }
+        b.step(&rule::Rule::conway());
+        assert_eq!(b.evaluated(), 2 * TILE_WIDTH * 2 * TILE_HEIGHT);
+        b.step(&rule::Rule::conway());
+        assert_eq!(b.evaluated(), 0);
+        assert_eq!(b.population(), 4);
+
+        // another rule wakes every tile
+        b.step(&rule::Rule::new(&[3, 6], &[2, 3]));
+        assert_eq!(b.evaluated(), 256 * 64);
+    }
+}
+
+#[test]
+fn board_generations() {
+    let brain = rule::parse_rule("B2/S/C3").ok().unwrap();
+
+    for &storage in &[Storage::Cells, Storage::Packed] {
+        let mut b = Board::with_storage(4, 3, Topology::Bounded, storage);
+        assert!(b.set(1, 1, true).is_ok());
+        assert!(b.set(2, 1, true).is_ok());
+
+        b.step(&brain);
+        let expected = "_XX_\n\
+                        _11_\n\
+                        _XX_\n";
+        assert_eq!(b.to_string(), expected);
+        assert_eq!(b.population(), 4);
+        assert_eq!(b.dying(1, 1).ok(), Some(1));
+        assert_eq!(b.dying(0, 0).ok(), Some(0));
+        assert_eq!(b.cell_char(1, 0, &brain).ok(), Some('X'));
+        assert_eq!(b.cell_char(1, 1, &brain).ok(), Some('1'));
+        assert_eq!(b.dying(4, 0).err(), Some("Invalid indexes"));
+
+        b.step(&brain);
+        let expected = "_11_\n\
+                        X__X\n\
+                        _11_\n";
+        assert_eq!(b.to_string(), expected);
+
+        // dying cells are not born even with 2 alive neighbours
+        let mut b = Board::with_storage(3, 3, Topology::Bounded, storage);
+        assert!(b.set(0, 1, true).is_ok());
+        assert!(b.set(2, 1, true).is_ok());
+        assert!(b.set_dying(1, 1, 1).is_ok());
+        b.step(&brain);
+        assert_eq!(b.get(1, 1).ok(), Some(false));
+        assert_eq!(b.dying(1, 1).ok(), Some(0));
+        assert_eq!(b.get(1, 0).ok(), Some(true));
+
+        // more dying states use more characters
+        let mut b = Board::with_storage(12, 1, Topology::Bounded, storage);
+        for x in 0..12 {
+            assert!(b.set_dying(x, 0, x as u8).is_ok());
+        }
+        assert_eq!(b.to_string(), "_123456789ab\n");
+        assert!(b.set_dying(11, 0, 200).is_ok());
+        assert_eq!(b.cell_char(11, 0, &brain).ok(), Some('#'));
+
+        // the dying cells die with a two states rule
+        b.step(&rule::Rule::conway());
+        assert_eq!(b.to_string(), "____________\n");
+    }
+}
+
+#[test]
+fn board_generations_packed() {
+    // Star Wars, then Brian's Brain, then Life
+    let rules = [rule::parse_rule("345/2/4").ok().unwrap(), rule::parse_rule("B2/S/C3").ok().unwrap(), rule::Rule::conway()];
+
+    for &topology in &[Topology::Bounded, Topology::Torus] {
+        let mut cells = random_board(90, 40, topology, Storage::Cells, 9);
+        let mut packed = random_board(90, 40, topology, Storage::Packed, 9);
+        let mut previous = cells.clone();
+
+        for generation in 0..45 {
+            let rule = &rules[generation / 15];
+            cells.step(rule);
+            packed.step_parallel(rule, 3);
+            assert_eq!(packed.to_string(), cells.to_string());
+
+            // every dying state comes from an alive cell or from the previous dying state
+            for x in 0..cells.width {
+                for y in 0..cells.height {
+                    let dying = cells.dying(x, y).ok().unwrap();
+                    let was_alive = previous.get(x, y).ok().unwrap();
+                    let was_dying = previous.dying(x, y).ok().unwrap();
+                    assert_eq!(dying, rule.next_dying(was_dying, was_alive, cells.get(x, y).ok().unwrap()));
+                }
+            }
+            previous = cells.clone();
+        }
+    }
+}
+
+#[test]
+fn board_dying_diff() {
+    let mut b = Board::new(3, 2);
+    let mut other = b.clone();
+    assert!(b.dying_diff(&other).is_empty());
+
+    assert!(b.set_dying(1, 1, 3).is_ok());
+    assert!(other.set_dying(1, 1, 1).is_ok());
+    assert!(other.set_dying(0, 0, 2).is_ok());
+    assert_eq!(b.dying_diff(&other), vec![(0, 0, 2), (1, 1, 2)]);
+}
+
+#[test]
+fn board_wireworld() {
+    let wireworld = rule::Rule::wireworld();
+
+    for &storage in &[Storage::Cells, Storage::Packed] {
+        // a wire splitting in two, and a conductor crossed by three heads
+        let mut b = Board::with_storage(7, 5, Topology::Bounded, storage);
+        for &(x, y) in &[(0, 2), (1, 2), (2, 2), (3, 1), (3, 3), (4, 0), (4, 4), (6, 0), (6, 1), (6, 2), (6, 3)] {
+            assert!(b.set_dying(x, y, rule::CONDUCTOR).is_ok());
+        }
+        assert!(b.set_dying(0, 2, rule::TAIL).is_ok());
+        assert!(b.set_dying(1, 2, 0).is_ok());
+        assert!(b.set(1, 2, true).is_ok());
+        for &y in &[0, 1, 2] {
+            assert!(b.set_dying(5, y, 0).is_ok());
+            assert!(b.set(5, y, true).is_ok());
+        }
+        let expected = "____#H#\n\
+                        ___#_H#\n\
+                        tH#__H#\n\
+                        ___#__#\n\
+                        ____#__\n";
+        assert_eq!(b.render(&wireworld), expected);
+
+        b.step(&wireworld);
+        let expected = "____HtH\n\
+                        ___#_t#\n\
+                        #tH__tH\n\
+                        ___#__H\n\
+                        ____#__\n";
+        assert_eq!(b.render(&wireworld), expected);
+
+        b.step(&wireworld);
+        let expected = "____t#t\n\
+                        ___H_#H\n\
+                        ##t__#t\n\
+                        ___H__t\n\
+                        ____#__\n";
+        assert_eq!(b.render(&wireworld), expected);
+    }
+}
+
+// Copy of the original neighbour count: scans the bounded neighbourhood of a single cell
+#[cfg(test)]
+fn baseline_neighbours(board: &[Vec<bool>], x: usize, y: usize) -> u8 {
+    let (width, height) = (board[0].len(), board.len());
+    let get = |i: usize, j: usize| -> Result<bool, &str> {
+        if i < width && j < height { Ok(board[j][i]) } else { Err("Invalid indexes") }
+    };
+    let mut count = 0;
+    for i in x.saturating_sub(1)..(x + 2).min(width) {
+        for j in y.saturating_sub(1)..(y + 2).min(height) {
+            if !(i == x && j == y) && get(i, j).ok().unwrap() {
+                count += 1;
+            }
+        }
+    }
+    count
+}
+
+// Copy of the original stepper: Conway's rule applied cell by cell on a clone of the board
+#[cfg(test)]
+fn baseline_step(board: &mut [Vec<bool>]) {
+    let cloned = board.to_vec();
+    for (j, row) in board.iter_mut().enumerate() {
+        for (i, cell) in row.iter_mut().enumerate() {
+            match baseline_neighbours(&cloned, i, j) {
+                2 => {},
+                3 => *cell = true,
+                _ => *cell = false,
+            }
+        }
+    }
+}
+
+// Run with: cargo test --release board_step_benchmark -- --ignored --nocapture
+#[test]
+#[ignore]
+fn board_step_benchmark() {
+    use std::time::Instant;
+
+    const SIZE: usize = 4096;
+    const GENERATIONS: usize = 10;
+
+    let mut b = random_board(SIZE, SIZE, Topology::Bounded, Storage::Packed, 42);
+    let mut baseline: Vec<Vec<bool>> = (0..SIZE).map(|y| (0..SIZE).map(|x| b.get(x, y).ok().unwrap()).collect()).collect();
+
+    let start = Instant::now();
+    for _ in 0..GENERATIONS {
+        baseline_step(&mut baseline);
+    }
+    let baseline_elapsed = start.elapsed();
+    println!("Baseline board {}x{}: {} generations in {:?}", SIZE, SIZE, GENERATIONS, baseline_elapsed);
+
+    let start = Instant::now();
+    for _ in 0..GENERATIONS {
+        b.step(&rule::Rule::conway());
+    }
+    let elapsed = start.elapsed();
+    println!("{} board {}x{}: {} generations in {:?}", Storage::Packed, SIZE, SIZE, GENERATIONS, elapsed);
+
+    let population = baseline.iter().map(|row| row.iter().filter(|&&cell| cell).count()).sum::<usize>();
+    assert_eq!(b.population(), population);
+    println!("Speed-up: {:.1}x", baseline_elapsed.as_secs_f64() / elapsed.as_secs_f64());
+}
+
+#[test]
+fn board_signature() {
+    assert_eq!(Board::new(70, 5).signature(), None);
+
+    // the same pattern in both storages and in another place, across the words of the rows
+    let pattern = random_board(100, 6, Topology::Bounded, Storage::Cells, 5);
+    let mut signatures = Vec::new();
+    for &storage in &[Storage::Cells, Storage::Packed] {
+        for &(dx, dy) in &[(0, 0), (37, 4), (90, 1)] {
+            let mut b = Board::with_storage(200, 12, Topology::Bounded, storage);
+            for y in 0..6 {
+                for x in 0..100 {
+                    b.set(x + dx, y + dy, pattern.get(x, y).ok().unwrap()).ok();
+                }
+            }
+            let mut cells = Vec::new();
+            b.cells_into(&mut cells);
+            assert_eq!(cells.len(), pattern.population());
+            assert!(cells.iter().all(|&(x, y)| b.get(x as usize, y as usize).ok().unwrap()));
+            assert!(cells.windows(2).all(|pair| (pair[0].1, pair[0].0) < (pair[1].1, pair[1].0)));
+            signatures.push(b.signature().unwrap());
+        }
+    }
+    assert!(signatures.iter().all(|s| s.hash == signatures[0].hash && s.population == pattern.population()));
+    assert_eq!(signatures[4].x - signatures[3].x, 37);
+    assert_eq!(signatures[4].y - signatures[3].y, 4);
+
+    // the hash is the one of the list of cells
+    let mut d = period::PeriodDetector::new();
+    let mut b = Board::with_storage(130, 3, Topology::Torus, Storage::Packed);
+    for &(x, y) in &[(0, 1), (63, 0), (64, 2), (129, 1)] {
+        b.set(x, y, true).ok();
+    }
+    d.record(0, b.signature(), |cells| b.cells_into(cells));
+    let mut cells = Vec::new();
+    b.cells_into(&mut cells);
+    assert_eq!(d.record_cells(1, cells.into_iter().rev()), Some(period::Period::StillLife));
+}
+
+#[test]
+fn board_neighbourhoods() {
+    let mut b = Board::new(5, 5);
+    for &(x, y) in [(1, 1), (2, 1), (3, 1), (1, 2), (3, 2), (1, 3), (2, 3), (3, 3)].iter() {
+        b.set(x, y, true).ok();
+    }
+    assert_eq!(b.neighbours(2, 2), 8);
+    assert_eq!(b.neighbours_in(2, 2, rule::Neighbourhood::VonNeumann), 4);
+    assert_eq!(b.neighbours_in(2, 2, rule::Neighbourhood::Hexagonal), 6);
+    // the up triangle at (2, 2) touches 3 cells above and 5 below, the down one at (3, 2) 5 above and 3 below
+    assert_eq!(b.neighbours_in(2, 2, rule::Neighbourhood::Triangular), 8);
+    assert_eq!(b.neighbours_in(3, 2, rule::Neighbourhood::Triangular), 6);
+    assert_eq!(b.neighbours_in(2, 1, rule::Neighbourhood::Triangular), 4);
+
+    let mut b = Board::with_topology(6, 6, Topology::Torus);
+    b.set(0, 0, true).ok();
+    assert_eq!(b.neighbours_in(2, 0, rule::Neighbourhood::Triangular), 1);
+    assert_eq!(b.neighbours_in(4, 0, rule::Neighbourhood::Triangular), 1);
+    assert_eq!(b.neighbours_in(5, 5, rule::Neighbourhood::Hexagonal), 1);
+    assert_eq!(b.neighbours_in(1, 5, rule::Neighbourhood::Hexagonal), 0);
+}
+
+#[test]
+fn board_step_neighbourhoods() {
+    let seed = |width: usize, height: usize, x: usize, y: usize| {
+        let mut b = Board::new(width, height);
+        b.set(x, y, true).ok();
+        b
+    };
+
+    let rule = rule::parse_rule("B1/SV").ok().unwrap();
+    let mut b = seed(5, 5, 2, 2);
+    b.step(&rule);
+    assert_eq!(b.render(&rule), "_____\n__X__\n_X_X_\n__X__\n_____\n");
+
+    // the rows of hexagonal boards are shifted, so the cell is surrounded by its neighbours
+    let rule = rule::parse_rule("B1/SH").ok().unwrap();
+    let mut b = seed(3, 3, 1, 1);
+    assert_eq!(b.render(&rule), "  _ _ _\n _ X _\n_ _ _\n");
+    b.step(&rule);
+    assert_eq!(b.render(&rule), "  X X _\n X _ X\n_ X X\n");
+
+    let rule = rule::parse_rule("B1/SL").ok().unwrap();
+    let mut b = seed(5, 4, 2, 2);
+    b.step(&rule);
+    assert_eq!(b.render(&rule), "_____\n_XXX_\nXX_XX\nXXXXX\n");
+}
+
+#[test]
+fn board_step_neighbourhoods_storages() {
+    let rules = ["B2/S34H", "B13/S012V", "B45/S34L", "B4a/S3456bcL"];
+
+    for rule in rules.iter() {
+        let rule = rule::parse_rule(rule).ok().unwrap();
+        for &topology in &[Topology::Bounded, Topology::Torus, Topology::Cylinder] {
+            for &storage in &[Storage::Cells, Storage::Packed] {
+                let mut b = random_board(130, 34, topology, storage, 5);
+                for _ in 0..6 {
+                    let expected = full_scan(&b, &rule);
+                    b.step_parallel(&rule, 3);
+                    assert!(b.diff(&expected).is_empty());
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn board_step_larger_than_life() {
+    // R1 without the middle cell is an outer-totalistic rule
+    let ranges = [("R1,C0,M0,S2..3,B3..3,NM", "B3/S23"), ("R1,C0,M0,S1..2,B1..1,NN", "B1/S12V")];
+    for &(range, totalistic) in ranges.iter() {
+        let range = rule::parse_rule(range).ok().unwrap();
+        let totalistic = rule::parse_rule(totalistic).ok().unwrap();
+        for &storage in &[Storage::Cells, Storage::Packed] {
+            let mut b = random_board(70, 40, Topology::Torus, storage, 3);
+            let mut expected = b.clone();
+            for _ in 0..5 {
+                b.step(&range);
+                expected.step(&totalistic);
+                assert!(b.diff(&expected).is_empty());
+            }
+        }
+    }
+
+    // with Bosco's rule a solid square is overcrowded inside but not at its corners,
+    // and cells are born a few cells away from its sides
+    let bosco = rule::parse_rule("R5,C0,M1,S34..58,B34..45,NM").ok().unwrap();
+    let mut b = Board::new(20, 20);
+    for x in 5..15 {
+        for y in 5..15 {
+            b.set(x, y, true).ok();
+        }
+    }
+    b.step(&bosco);
+    assert_eq!(b.get(9, 9), Ok(false));
+    assert_eq!(b.get(5, 5), Ok(true));
+    assert_eq!(b.get(4, 9), Ok(false));
+    assert_eq!(b.get(3, 9), Ok(true));
+    assert_eq!(b.evaluated(), 400);
+}
+
+#[test]
+fn board_xor() {
+    for &storage in &[Storage::Cells, Storage::Packed] {
+        let a = random_board(70, 5, Topology::Bounded, storage, 3);
+        let b = random_board(70, 5, Topology::Bounded, storage, 4);
+        let mut c = a.clone();
+        c.xor(&b);
+        assert_eq!(c.diff(&a).len(), b.population());
+        c.xor(&b);
+        assert!(c.diff(&a).is_empty());
+    }
+}
+
+#[test]
+fn board_step_chances() {
+    let life = rule::Rule::conway();
+    let certain = life.with_chances(rule::Chances { birth: 1.0, survival: 1.0 });
+    let mut expected = random_board(70, 40, Topology::Torus, Storage::Cells, 3);
+    let mut b = expected.clone();
+    expected.step(&life);
+    let mut next = b.clone();
+    b.step_into(&certain, 1, 5, &mut next);
+    assert!(next.diff(&expected).is_empty());
+
+    // without births only the survivors are left, without survivals only the births
+    let before = random_board(70, 40, Topology::Torus, Storage::Packed, 3);
+    next = before.clone();
+    for &(birth, survival) in &[(0.0, 1.0), (1.0, 0.0)] {
+        before.step_into(&life.with_chances(rule::Chances { birth, survival }), 1, 5, &mut next);
+        for y in 0..40 {
+            for x in 0..70 {
+                let was_alive = before.get(x, y).ok().unwrap();
+                let alive = expected.get(x, y).ok().unwrap() && if was_alive { survival > 0.0 } else { birth > 0.0 };
+                assert_eq!(next.get(x, y), Ok(alive));
+            }
+        }
+    }
+
+    // the choices depend on the key, not on the storage or the threads
+    let half = life.with_chances(rule::Chances { birth: 0.5, survival: 0.5 });
+    let mut results = Vec::new();
+    for &(storage, threads, key) in &[(Storage::Cells, 1, 7), (Storage::Packed, 3, 7), (Storage::Packed, 1, 8)] {
+        b = random_board(70, 40, Topology::Torus, storage, 3);
+        next = b.clone();
+        b.step_into(&half, threads, key, &mut next);
+        results.push(next.to_string());
+    }
+    assert_eq!(results[0], results[1]);
+    assert_ne!(results[0], results[2]);
+}
+
+#[test]
+fn board_step_isotropic() {
+    // listing every letter of every count gives back the totalistic rule
+    let isotropic = rule::parse_rule("B3cekainyqjr/S2ceaikn3cekainyqjr").ok().unwrap();
+    let totalistic = rule::parse_rule("B3/S23").ok().unwrap();
+    for &storage in &[Storage::Cells, Storage::Packed] {
+        for &topology in &[Topology::Bounded, Topology::Torus] {
+            let mut b = random_board(70, 40, topology, storage, 5);
+            let mut expected = b.clone();
+            for _ in 0..5 {
+                b.step(&isotropic);
+                expected.step(&totalistic);
+                assert!(b.diff(&expected).is_empty());
+            }
+        }
+    }
+
+    // both storages agree on a rule that depends on the arrangement
+    let rule = rule::parse_rule("B2-a3/S12-k").ok().unwrap();
+    let mut cells = random_board(70, 40, Topology::Torus, Storage::Cells, 9);
+    let mut packed = random_board(70, 40, Topology::Torus, Storage::Packed, 9);
+    for _ in 0..5 {
+        cells.step(&rule);
+        packed.step(&rule);
+        assert_eq!(cells.to_string(), packed.to_string());
+    }
+
+    let mut b = Board::new(3, 3);
+    b.set(0, 0, true).ok();
+    b.set(1, 0, true).ok();
+    b.set(2, 2, true).ok();
+    assert_eq!(b.configuration(1, 1), 0x83);
+}
@@ -1,25 +1,227 @@
 use std::fmt;
+use ::game::ant;
+use ::game::board;
+use ::game::rule;
+use ::game::schedule;
 
-#[derive(Copy,Clone)]
+#[derive(Clone)]
 pub enum Command {
-    New { width: usize, height: usize},
+    New { width: usize, height: usize, topology: board::Topology, storage: board::Storage },
     Toggle { x: usize, y: usize},
-    Play,
+    Wire { x: usize, y: usize },
+    Electron { x: usize, y: usize },
+    Ant { x: usize, y: usize, rule: ant::AntRule },
+    ClearAnts,
+    Wolfram { rule: u8, width: usize, height: usize },
+    Play { generations: Option<usize> },
     Step,
+    Rule { rule: rule::Rule },
+    Universe,
+    Jump { generations: usize },
+    Load { path: String },
+    Save { path: String },
+    Speed { delay: u64 },
+    Threads { threads: usize },
+    Seed { seed: u64 },
+    Schedule { schedule: schedule::Schedule },
+    Reversible { enabled: bool },
+    Reverse { generations: usize },
+    Undo,
+    Redo,
+    Back { generations: usize },
+    Period,
+    Stats,
+    Tui,
     Quit
 }
 
 pub fn parse_command(input: &str) -> Result<Command, &str> {
     let tokens: Vec<&str> = input.split_whitespace().collect();
 
-    if tokens.len() != 0 {
+    if !tokens.is_empty() {
 
         match tokens[0] {
             "quit" | "q" => return Ok(Command::Quit),
-            "play" | "p" => return Ok(Command::Play),
+            "play" | "p" => {
+                match tokens.len() {
+                    1 => return Ok(Command::Play { generations: None }),
+                    2 => {
+                        let generations: usize = match tokens[1].parse() {
+                            Ok(num) => num,
+                            Err(_) => return Err("Invalid first argument value: not a number"),
+                        };
+
+                        if generations == 0 {
+                            return Err("Invalid argument value");
+                        }
+
+                        return Ok(Command::Play { generations: Some(generations) });
+                    },
+                    _ => return Err("Invalid syntax for command"),
+                }
+            },
+            "speed" => {
+                if tokens.len() == 2 {
+                    if tokens[1] == "max" {
+                        return Ok(Command::Speed { delay: 0 });
+                    }
+                    match tokens[1].parse() {
+                        Ok(delay) => return Ok(Command::Speed { delay }),
+                        Err(_) => return Err("Invalid first argument value: not a number"),
+                    }
+                } else {
+                    return Err("Invalid syntax for command");
+                }
+            },
             "step" | "s" => return Ok(Command::Step),
-            c @ "new" | c @ "n" | c @ "toggle" | c @ "t" => {
-                if tokens.len() == 3 {
+            "universe" | "u" => return Ok(Command::Universe),
+            c @ "load" | c @ "l" | c @ "save" => {
+                if tokens.len() == 2 {
+                    let path = tokens[1].to_string();
+                    if c == "save" {
+                        return Ok(Command::Save { path });
+                    } else {
+                        return Ok(Command::Load { path });
+                    }
+                } else {
+                    return Err("Invalid syntax for command");
+                }
+            },
+            "undo" => return Ok(Command::Undo),
+            "redo" => return Ok(Command::Redo),
+            "period" => return Ok(Command::Period),
+            "stats" => return Ok(Command::Stats),
+            "tui" => return Ok(Command::Tui),
+            "threads" => {
+                if tokens.len() == 2 {
+                    let threads: usize = match tokens[1].parse() {
+                        Ok(num) => num,
+                        Err(_) => return Err("Invalid first argument value: not a number"),
+                    };
+
+                    if threads == 0 {
+                        return Err("Invalid argument value");
+                    }
+
+                    return Ok(Command::Threads { threads });
+                } else {
+                    return Err("Invalid syntax for command");
+                }
+            },
+            "seed" => {
+                if tokens.len() == 2 {
+                    return match tokens[1].parse() {
+                        Ok(seed) => Ok(Command::Seed { seed }),
+                        Err(_) => Err("Invalid first argument value: not a number"),
+                    };
+                } else {
+                    return Err("Invalid syntax for command");
+                }
+            },
+            "reversible" => {
+                return match tokens.get(1..) {
+                    Some(["on"]) => Ok(Command::Reversible { enabled: true }),
+                    Some(["off"]) => Ok(Command::Reversible { enabled: false }),
+                    _ => Err("Invalid syntax for command"),
+                };
+            },
+            "reverse" => {
+                match tokens.len() {
+                    1 => return Ok(Command::Reverse { generations: 1 }),
+                    2 => {
+                        let generations: usize = match tokens[1].parse() {
+                            Ok(num) => num,
+                            Err(_) => return Err("Invalid first argument value: not a number"),
+                        };
+
+                        if generations == 0 {
+                            return Err("Invalid argument value");
+                        }
+
+                        return Ok(Command::Reverse { generations });
+                    },
+                    _ => return Err("Invalid syntax for command"),
+                }
+            },
+            "schedule" => return schedule::parse_schedule(&tokens[1..]).map(|schedule| Command::Schedule { schedule }),
+            c @ "jump" | c @ "j" | c @ "back" | c @ "b" => {
+                if tokens.len() == 2 {
+                    let generations: usize = match tokens[1].parse() {
+                        Ok(num) => num,
+                        Err(_) => return Err("Invalid first argument value: not a number"),
+                    };
+
+                    if generations == 0 {
+                        return Err("Invalid argument value");
+                    }
+
+                    if c == "back" || c == "b" {
+                        return Ok(Command::Back { generations });
+                    } else {
+                        return Ok(Command::Jump { generations });
+                    }
+                } else {
+                    return Err("Invalid syntax for command");
+                }
+            },
+            "ant" => {
+                if tokens.len() == 2 && tokens[1] == "clear" {
+                    return Ok(Command::ClearAnts);
+                } else if tokens.len() == 4 {
+                    let x: usize = match tokens[1].parse() {
+                        Ok(num) => num,
+                        Err(_) => return Err("Invalid first argument value: not a number"),
+                    };
+                    let y: usize = match tokens[2].parse() {
+                        Ok(num) => num,
+                        Err(_) => return Err("Invalid second argument value: not a number"),
+                    };
+
+                    if x == 0 || y == 0 {
+                        return Err("Invalid argument value");
+                    }
+
+                    return ant::parse_ant_rule(tokens[3]).map(|rule| Command::Ant { x, y, rule });
+                } else {
+                    return Err("Invalid syntax for command");
+                }
+            },
+            "wolfram" => {
+                // wolfram <rule> <width> [height], by default high enough for a triangle from the seed
+                if tokens.len() == 3 || tokens.len() == 4 {
+                    let rule: usize = match tokens[1].parse() {
+                        Ok(num) => num,
+                        Err(_) => return Err("Invalid first argument value: not a number"),
+                    };
+                    let width: usize = match tokens[2].parse() {
+                        Ok(num) => num,
+                        Err(_) => return Err("Invalid second argument value: not a number"),
+                    };
+                    let height: usize = match tokens.get(3).map(|token| token.parse()) {
+                        Some(Ok(num)) => num,
+                        Some(Err(_)) => return Err("Invalid third argument value: not a number"),
+                        None => width / 2 + 1,
+                    };
+
+                    if rule > 255 || width == 0 || height == 0 {
+                        return Err("Invalid argument value");
+                    }
+
+                    return Ok(Command::Wolfram { rule: rule as u8, width, height });
+                } else {
+                    return Err("Invalid syntax for command");
+                }
+            },
+            "rule" | "r" => {
+                if tokens.len() == 2 {
+                    return rule::parse_rule(tokens[1]).map(|rule| Command::Rule { rule });
+                } else {
+                    return Err("Invalid syntax for command");
+                }
+            },
+            c @ "new" | c @ "n" | c @ "toggle" | c @ "t" | c @ "wire" | c @ "electron" => {
+                let is_new = c == "new" || c == "n";
+                if tokens.len() == 3 || (is_new && (tokens.len() == 4 || tokens.len() == 5)) {
                     let first: usize = match tokens[1].parse() {
                         Ok(num) => num,
                         Err(_) => return Err("Invalid first argument value: not a number"),
@@ -33,10 +235,27 @@ pub fn parse_command(input: &str) -> Result<Command, &str> {
                         return Err("Invalid argument value");
                     }
 
-                    match c {
-                        "new" | "n" => return Ok(Command::New{ width: first, height: second}),
-                        "toggle" | "t" => return Ok(Command::Toggle{ x: first, y: second}),
-                        _ => return Err("Invalid syntax for command"),
+                    if is_new {
+                        // new <width> <height> [topology] [storage], the topology can be omitted
+                        let mut topology = board::Topology::Bounded;
+                        let mut storage = board::Storage::Cells;
+                        match (tokens.get(3), tokens.get(4)) {
+                            (Some(token), None) if board::parse_storage(token).is_ok() => storage = board::parse_storage(token)?,
+                            (Some(token), last) => {
+                                topology = board::parse_topology(token)?;
+                                if let Some(token) = last {
+                                    storage = board::parse_storage(token).map_err(|_| "Invalid syntax for command")?;
+                                }
+                            },
+                            _ => {},
+                        }
+                        return Ok(Command::New{ width: first, height: second, topology, storage });
+                    } else if c == "wire" {
+                        return Ok(Command::Wire { x: first, y: second });
+                    } else if c == "electron" {
+                        return Ok(Command::Electron { x: first, y: second });
+                    } else {
+                        return Ok(Command::Toggle{ x: first, y: second});
                     }
                 } else {
                     return Err("Invalid syntax for command");
@@ -55,10 +274,34 @@ impl fmt::Display for Command {
 
         match *self {
             Command::Quit => write!(f, "Quit"),
-            Command::Play => write!(f, "Play"),
+            Command::Play { generations: None } => write!(f, "Play"),
+            Command::Play { generations: Some(generations) } => write!(f, "Play [generations: {}]", generations),
+            Command::Speed { delay: 0 } => write!(f, "Speed [max]"),
+            Command::Speed { delay } => write!(f, "Speed [delay: {} ms]", delay),
             Command::Step => write!(f, "Step"),
-            Command::New { width, height } => write!(f, "New [width: {} height: {}]", width, height),
+            Command::Rule { rule } => write!(f, "Rule [{}]", rule),
+            Command::Universe => write!(f, "Universe"),
+            Command::Jump { generations } => write!(f, "Jump [generations: {}]", generations),
+            Command::Load { ref path } => write!(f, "Load [file: {}]", path),
+            Command::Save { ref path } => write!(f, "Save [file: {}]", path),
+            Command::Undo => write!(f, "Undo"),
+            Command::Redo => write!(f, "Redo"),
+            Command::Back { generations } => write!(f, "Back [generations: {}]", generations),
+            Command::Threads { threads } => write!(f, "Threads [threads: {}]", threads),
+            Command::Seed { seed } => write!(f, "Seed [seed: {}]", seed),
+            Command::Schedule { schedule } => write!(f, "Schedule [schedule: {}]", schedule),
+            Command::Reversible { enabled } => write!(f, "Reversible [enabled: {}]", enabled),
+            Command::Reverse { generations } => write!(f, "Reverse [generations: {}]", generations),
+            Command::Period => write!(f, "Period"),
+            Command::Stats => write!(f, "Stats"),
+            Command::Tui => write!(f, "Tui"),
+            Command::New { width, height, topology, storage } => write!(f, "New [width: {} height: {} topology: {} storage: {}]", width, height, topology, storage),
             Command::Toggle { x, y } => write!(f, "Toggle [x: {} y: {}]", x, y),
+            Command::Wire { x, y } => write!(f, "Wire [x: {} y: {}]", x, y),
+            Command::Electron { x, y } => write!(f, "Electron [x: {} y: {}]", x, y),
+            Command::Ant { x, y, ref rule } => write!(f, "Ant [x: {} y: {} rule: {}]", x, y, rule),
+            Command::ClearAnts => write!(f, "Clear ants"),
+            Command::Wolfram { rule, width, height } => write!(f, "Wolfram [rule: {} width: {} height: {}]", rule, width, height),
         }
 
     }
@@ -66,18 +309,18 @@ impl fmt::Display for Command {
 
 #[test]
 fn command_new() {
-    let c = Command::New { width: 30, height: 20 };
-    assert_eq!(c.to_string(), "New [width: 30 height: 20]");
+    let c = Command::New { width: 30, height: 20, topology: board::Topology::Bounded, storage: board::Storage::Cells };
+    assert_eq!(c.to_string(), "New [width: 30 height: 20 topology: bounded storage: cells]");
 
     // parse
     let pc = parse_command("new 30 20").ok().unwrap();
-    if let Command::New { width: 30, height: 20} = pc { assert!(true); } else { assert!(false); }
+    assert!(matches!(pc, Command::New { width: 30, height: 20, topology: board::Topology::Bounded, storage: board::Storage::Cells }));
 
     let pc = parse_command("new 20 80").ok().unwrap();
-    if let Command::New { width: 20, height: 80} = pc { assert!(true); } else { assert!(false); }
+    assert!(matches!(pc, Command::New { width: 20, height: 80, topology: board::Topology::Bounded, storage: board::Storage::Cells }));
 
     let pc = parse_command("n 30 20").ok().unwrap();
-    if let Command::New { width: 30, height: 20} = pc { assert!(true); } else { assert!(false); }
+    assert!(matches!(pc, Command::New { width: 30, height: 20, topology: board::Topology::Bounded, storage: board::Storage::Cells }));
 
     let error = parse_command("new thirty twenty").err().unwrap();
     assert_eq!(error, "Invalid first argument value: not a number");
@@ -101,6 +344,54 @@ fn command_new() {
     assert_eq!(error, "Invalid argument value");
 }
 
+#[test]
+fn command_new_topology() {
+    let c = Command::New { width: 30, height: 20, topology: board::Topology::Torus, storage: board::Storage::Cells };
+    assert_eq!(c.to_string(), "New [width: 30 height: 20 topology: torus storage: cells]");
+
+    let pc = parse_command("new 30 20 torus").ok().unwrap();
+    assert!(matches!(pc, Command::New { width: 30, height: 20, topology: board::Topology::Torus, storage: board::Storage::Cells }));
+
+    let pc = parse_command("n 30 20 c").ok().unwrap();
+    assert!(matches!(pc, Command::New { width: 30, height: 20, topology: board::Topology::Cylinder, storage: board::Storage::Cells }));
+
+    let pc = parse_command("new 30 20 bounded").ok().unwrap();
+    assert!(matches!(pc, Command::New { width: 30, height: 20, topology: board::Topology::Bounded, storage: board::Storage::Cells }));
+
+    let error = parse_command("new 30 20 sphere").err().unwrap();
+    assert_eq!(error, "Invalid topology: expected bounded, torus or cylinder");
+
+    let error = parse_command("new 30 20 torus 5").err().unwrap();
+    assert_eq!(error, "Invalid syntax for command");
+
+    let error = parse_command("toggle 3 2 torus").err().unwrap();
+    assert_eq!(error, "Invalid syntax for command");
+}
+
+#[test]
+fn command_new_storage() {
+    let c = Command::New { width: 30, height: 20, topology: board::Topology::Torus, storage: board::Storage::Packed };
+    assert_eq!(c.to_string(), "New [width: 30 height: 20 topology: torus storage: packed]");
+
+    let pc = parse_command("new 30 20 packed").ok().unwrap();
+    assert!(matches!(pc, Command::New { width: 30, height: 20, topology: board::Topology::Bounded, storage: board::Storage::Packed }));
+
+    let pc = parse_command("new 30 20 t packed").ok().unwrap();
+    assert!(matches!(pc, Command::New { width: 30, height: 20, topology: board::Topology::Torus, storage: board::Storage::Packed }));
+
+    let pc = parse_command("new 30 20 cylinder cells").ok().unwrap();
+    assert!(matches!(pc, Command::New { width: 30, height: 20, topology: board::Topology::Cylinder, storage: board::Storage::Cells }));
+
+    let error = parse_command("new 30 20 packed torus").err().unwrap();
+    assert_eq!(error, "Invalid topology: expected bounded, torus or cylinder");
+
+    let error = parse_command("new 30 20 torus compact").err().unwrap();
+    assert_eq!(error, "Invalid syntax for command");
+
+    let error = parse_command("new 30 20 torus packed 5").err().unwrap();
+    assert_eq!(error, "Invalid syntax for command");
+}
+
 #[test]
 fn command_toggle() {
     let c = Command::Toggle { x: 4, y: 7 };
@@ -108,13 +399,13 @@ fn command_toggle() {
 
     // parse
     let pc = parse_command("toggle 4 7").ok().unwrap();
-    if let Command::Toggle { x: 4, y: 7} = pc { assert!(true); } else { assert!(false); }
+    assert!(matches!(pc, Command::Toggle { x: 4, y: 7}));
 
     let pc = parse_command("toggle 2 8").ok().unwrap();
-    if let Command::Toggle { x: 2, y: 8} = pc { assert!(true); } else { assert!(false); }
+    assert!(matches!(pc, Command::Toggle { x: 2, y: 8}));
 
     let pc = parse_command("t 4 7").ok().unwrap();
-    if let Command::Toggle { x: 4, y: 7} = pc { assert!(true); } else { assert!(false); }
+    assert!(matches!(pc, Command::Toggle { x: 4, y: 7}));
 
     let error = parse_command("toggle four seven").err().unwrap();
     assert_eq!(error, "Invalid first argument value: not a number");
@@ -140,30 +431,69 @@ fn command_toggle() {
 
 #[test]
 fn command_play() {
-    let c = Command::Play;
+    let c = Command::Play { generations: None };
     assert_eq!(c.to_string(), "Play");
 
     let pc = parse_command("play").ok().unwrap();
-    if let Command::Play = pc { assert!(true); } else { assert!(false); }
+    assert!(matches!(pc, Command::Play { generations: None }));
 
     let pc = parse_command("p").ok().unwrap();
-    if let Command::Play = pc { assert!(true); } else { assert!(false); }
+    assert!(matches!(pc, Command::Play { generations: None }));
 
     let error = parse_command("pla").err().unwrap();
     assert_eq!(error, "Unknown command");
 
 }
 
+#[test]
+fn command_play_generations() {
+    let c = Command::Play { generations: Some(20) };
+    assert_eq!(c.to_string(), "Play [generations: 20]");
+
+    let pc = parse_command("play 20").ok().unwrap();
+    assert!(matches!(pc, Command::Play { generations: Some(20) }));
+
+    let pc = parse_command("p 5").ok().unwrap();
+    assert!(matches!(pc, Command::Play { generations: Some(5) }));
+
+    let error = parse_command("play twenty").err().unwrap();
+    assert_eq!(error, "Invalid first argument value: not a number");
+
+    let error = parse_command("play 0").err().unwrap();
+    assert_eq!(error, "Invalid argument value");
+
+    let error = parse_command("play 5 5").err().unwrap();
+    assert_eq!(error, "Invalid syntax for command");
+}
+
+#[test]
+fn command_speed() {
+    assert_eq!(Command::Speed { delay: 250 }.to_string(), "Speed [delay: 250 ms]");
+    assert_eq!(Command::Speed { delay: 0 }.to_string(), "Speed [max]");
+
+    let pc = parse_command("speed 250").ok().unwrap();
+    assert!(matches!(pc, Command::Speed { delay: 250 }));
+
+    let pc = parse_command("speed max").ok().unwrap();
+    assert!(matches!(pc, Command::Speed { delay: 0 }));
+
+    let error = parse_command("speed fast").err().unwrap();
+    assert_eq!(error, "Invalid first argument value: not a number");
+
+    let error = parse_command("speed").err().unwrap();
+    assert_eq!(error, "Invalid syntax for command");
+}
+
 #[test]
 fn command_quit() {
     let c = Command::Quit;
     assert_eq!(c.to_string(), "Quit");
 
     let pc = parse_command("quit").ok().unwrap();
-    if let Command::Quit = pc { assert!(true); } else { assert!(false); }
+    assert!(matches!(pc, Command::Quit));
 
     let pc = parse_command("q").ok().unwrap();
-    if let Command::Quit = pc { assert!(true); } else { assert!(false); }
+    assert!(matches!(pc, Command::Quit));
 
     let error = parse_command("qui").err().unwrap();
     assert_eq!(error, "Unknown command");
@@ -175,11 +505,323 @@ fn command_step() {
     assert_eq!(c.to_string(), "Step");
 
     let pc = parse_command("step").ok().unwrap();
-    if let Command::Step = pc { assert!(true); } else { assert!(false); }
+    assert!(matches!(pc, Command::Step));
 
     let pc = parse_command("s").ok().unwrap();
-    if let Command::Step = pc { assert!(true); } else { assert!(false); }
+    assert!(matches!(pc, Command::Step));
 
     let error = parse_command("st").err().unwrap();
     assert_eq!(error, "Unknown command");
 }
+
+#[test]
+fn command_rule() {
+    let c = Command::Rule { rule: rule::Rule::conway() };
+    assert_eq!(c.to_string(), "Rule [B3/S23]");
+
+    let pc = parse_command("rule B36/S23").ok().unwrap();
+    if let Command::Rule { rule } = pc { assert_eq!(rule.to_string(), "B36/S23"); } else { panic!("unexpected command"); }
+
+    let pc = parse_command("r 23/36").ok().unwrap();
+    if let Command::Rule { rule } = pc { assert_eq!(rule.to_string(), "B36/S23"); } else { panic!("unexpected command"); }
+
+    let error = parse_command("rule B9/S23").err().unwrap();
+    assert_eq!(error, "Invalid rule: neighbour counts must be digits between 0 and 8");
+
+    let error = parse_command("rule").err().unwrap();
+    assert_eq!(error, "Invalid syntax for command");
+
+    let error = parse_command("rule B3/S23 B36/S23").err().unwrap();
+    assert_eq!(error, "Invalid syntax for command");
+
+    let error = parse_command("rul B3/S23").err().unwrap();
+    assert_eq!(error, "Unknown command");
+}
+
+#[test]
+fn command_universe() {
+    let c = Command::Universe;
+    assert_eq!(c.to_string(), "Universe");
+
+    let pc = parse_command("universe").ok().unwrap();
+    assert!(matches!(pc, Command::Universe));
+
+    let pc = parse_command("u").ok().unwrap();
+    assert!(matches!(pc, Command::Universe));
+
+    let error = parse_command("univ").err().unwrap();
+    assert_eq!(error, "Unknown command");
+}
+
+#[test]
+fn command_jump() {
+    let c = Command::Jump { generations: 1024 };
+    assert_eq!(c.to_string(), "Jump [generations: 1024]");
+
+    let pc = parse_command("jump 1024").ok().unwrap();
+    assert!(matches!(pc, Command::Jump { generations: 1024 }));
+
+    let pc = parse_command("j 5").ok().unwrap();
+    assert!(matches!(pc, Command::Jump { generations: 5 }));
+
+    let error = parse_command("jump ten").err().unwrap();
+    assert_eq!(error, "Invalid first argument value: not a number");
+
+    let error = parse_command("jump 0").err().unwrap();
+    assert_eq!(error, "Invalid argument value");
+
+    let error = parse_command("jump").err().unwrap();
+    assert_eq!(error, "Invalid syntax for command");
+
+    let error = parse_command("jum 5").err().unwrap();
+    assert_eq!(error, "Unknown command");
+}
+
+#[test]
+fn command_load() {
+    let c = Command::Load { path: "gun.rle".to_string() };
+    assert_eq!(c.to_string(), "Load [file: gun.rle]");
+
+    let pc = parse_command("load patterns/gun.rle").ok().unwrap();
+    if let Command::Load { path } = pc { assert_eq!(path, "patterns/gun.rle"); } else { panic!("unexpected command"); }
+
+    let pc = parse_command("l gun.rle").ok().unwrap();
+    if let Command::Load { path } = pc { assert_eq!(path, "gun.rle"); } else { panic!("unexpected command"); }
+
+    let error = parse_command("load").err().unwrap();
+    assert_eq!(error, "Invalid syntax for command");
+
+    let error = parse_command("load gun.rle glider.rle").err().unwrap();
+    assert_eq!(error, "Invalid syntax for command");
+}
+
+#[test]
+fn command_save() {
+    let c = Command::Save { path: "gun.rle".to_string() };
+    assert_eq!(c.to_string(), "Save [file: gun.rle]");
+
+    let pc = parse_command("save gun.rle").ok().unwrap();
+    if let Command::Save { path } = pc { assert_eq!(path, "gun.rle"); } else { panic!("unexpected command"); }
+
+    let error = parse_command("save").err().unwrap();
+    assert_eq!(error, "Invalid syntax for command");
+
+    let error = parse_command("sav gun.rle").err().unwrap();
+    assert_eq!(error, "Unknown command");
+}
+
+#[test]
+fn command_undo_redo() {
+    assert_eq!(Command::Undo.to_string(), "Undo");
+    assert_eq!(Command::Redo.to_string(), "Redo");
+
+    let pc = parse_command("undo").ok().unwrap();
+    assert!(matches!(pc, Command::Undo));
+
+    let pc = parse_command("redo").ok().unwrap();
+    assert!(matches!(pc, Command::Redo));
+
+    let error = parse_command("und").err().unwrap();
+    assert_eq!(error, "Unknown command");
+}
+
+#[test]
+fn command_back() {
+    let c = Command::Back { generations: 3 };
+    assert_eq!(c.to_string(), "Back [generations: 3]");
+
+    let pc = parse_command("back 3").ok().unwrap();
+    assert!(matches!(pc, Command::Back { generations: 3 }));
+
+    let pc = parse_command("b 10").ok().unwrap();
+    assert!(matches!(pc, Command::Back { generations: 10 }));
+
+    let error = parse_command("back three").err().unwrap();
+    assert_eq!(error, "Invalid first argument value: not a number");
+
+    let error = parse_command("back 0").err().unwrap();
+    assert_eq!(error, "Invalid argument value");
+
+    let error = parse_command("back").err().unwrap();
+    assert_eq!(error, "Invalid syntax for command");
+}
+
+#[test]
+fn command_period() {
+    assert_eq!(Command::Period.to_string(), "Period");
+
+    let pc = parse_command("period").ok().unwrap();
+    assert!(matches!(pc, Command::Period));
+
+    let error = parse_command("per").err().unwrap();
+    assert_eq!(error, "Unknown command");
+}
+
+#[test]
+fn command_tui() {
+    assert_eq!(Command::Tui.to_string(), "Tui");
+
+    let pc = parse_command("tui").ok().unwrap();
+    assert!(matches!(pc, Command::Tui));
+}
+
+#[test]
+fn command_threads() {
+    assert_eq!(Command::Threads { threads: 4 }.to_string(), "Threads [threads: 4]");
+
+    let pc = parse_command("threads 4").ok().unwrap();
+    assert!(matches!(pc, Command::Threads { threads: 4 }));
+
+    let error = parse_command("threads").err().unwrap();
+    assert_eq!(error, "Invalid syntax for command");
+
+    let error = parse_command("threads four").err().unwrap();
+    assert_eq!(error, "Invalid first argument value: not a number");
+
+    let error = parse_command("threads 0").err().unwrap();
+    assert_eq!(error, "Invalid argument value");
+}
+
+#[test]
+fn command_seed() {
+    assert_eq!(Command::Seed { seed: 42 }.to_string(), "Seed [seed: 42]");
+
+    let pc = parse_command("seed 42").ok().unwrap();
+    assert!(matches!(pc, Command::Seed { seed: 42 }));
+
+    let error = parse_command("seed").err().unwrap();
+    assert_eq!(error, "Invalid syntax for command");
+
+    let error = parse_command("seed -1").err().unwrap();
+    assert_eq!(error, "Invalid first argument value: not a number");
+}
+
+#[test]
+fn command_schedule() {
+    assert_eq!(Command::Schedule { schedule: schedule::Schedule::Asynchronous(0.5) }.to_string(), "Schedule [schedule: alpha 0.5]");
+
+    let pc = parse_command("schedule sweep").ok().unwrap();
+    assert!(matches!(pc, Command::Schedule { schedule: schedule::Schedule::Sweep }));
+
+    let pc = parse_command("schedule alpha 0.25").ok().unwrap();
+    if let Command::Schedule { schedule: schedule::Schedule::Asynchronous(alpha) } = pc { assert_eq!(alpha, 0.25); } else { panic!("unexpected command"); }
+
+    let error = parse_command("schedule").err().unwrap();
+    assert_eq!(error, "Invalid schedule: expected sync, random, sweep or alpha <probability>");
+
+    let error = parse_command("schedule alpha x").err().unwrap();
+    assert_eq!(error, "Invalid schedule: alpha must be a probability between 0 and 1");
+}
+
+#[test]
+fn command_stats() {
+    assert_eq!(Command::Stats.to_string(), "Stats");
+
+    let pc = parse_command("stats").ok().unwrap();
+    assert!(matches!(pc, Command::Stats));
+}
+
+#[test]
+fn command_wireworld() {
+    assert_eq!(Command::Wire { x: 3, y: 4 }.to_string(), "Wire [x: 3 y: 4]");
+    assert_eq!(Command::Electron { x: 3, y: 4 }.to_string(), "Electron [x: 3 y: 4]");
+
+    let pc = parse_command("wire 3 4").ok().unwrap();
+    assert!(matches!(pc, Command::Wire { x: 3, y: 4 }));
+
+    let pc = parse_command("electron 5 1").ok().unwrap();
+    assert!(matches!(pc, Command::Electron { x: 5, y: 1 }));
+
+    let pc = parse_command("rule wireworld").ok().unwrap();
+    if let Command::Rule { rule } = pc { assert!(rule.is_wireworld()); } else { panic!("unexpected command"); }
+
+    let error = parse_command("wire 3").err().unwrap();
+    assert_eq!(error, "Invalid syntax for command");
+
+    let error = parse_command("electron 0 2").err().unwrap();
+    assert_eq!(error, "Invalid argument value");
+
+    let error = parse_command("wire 3 4 torus").err().unwrap();
+    assert_eq!(error, "Invalid syntax for command");
+}
+
+#[test]
+fn command_ant() {
+    let rule = ant::parse_ant_rule("RL").ok().unwrap();
+    assert_eq!(Command::Ant { x: 3, y: 4, rule }.to_string(), "Ant [x: 3 y: 4 rule: RL]");
+    assert_eq!(Command::ClearAnts.to_string(), "Clear ants");
+
+    let pc = parse_command("ant 3 4 llrr").ok().unwrap();
+    if let Command::Ant { x: 3, y: 4, rule } = pc { assert_eq!(rule.to_string(), "LLRR"); } else { panic!("unexpected command"); }
+
+    let pc = parse_command("ant clear").ok().unwrap();
+    assert!(matches!(pc, Command::ClearAnts));
+
+    let error = parse_command("ant 3 4").err().unwrap();
+    assert_eq!(error, "Invalid syntax for command");
+
+    let error = parse_command("ant 3 four RL").err().unwrap();
+    assert_eq!(error, "Invalid second argument value: not a number");
+
+    let error = parse_command("ant 0 4 RL").err().unwrap();
+    assert_eq!(error, "Invalid argument value");
+
+    let error = parse_command("ant 3 4 RX").err().unwrap();
+    assert_eq!(error, "Invalid ant rule: expected a string of L, R, N or U");
+}
+
+#[test]
+fn command_wolfram() {
+    assert_eq!(Command::Wolfram { rule: 30, width: 11, height: 6 }.to_string(), "Wolfram [rule: 30 width: 11 height: 6]");
+
+    let pc = parse_command("wolfram 30 11").ok().unwrap();
+    assert!(matches!(pc, Command::Wolfram { rule: 30, width: 11, height: 6 }));
+
+    let pc = parse_command("wolfram 110 80 20").ok().unwrap();
+    assert!(matches!(pc, Command::Wolfram { rule: 110, width: 80, height: 20 }));
+
+    let pc = parse_command("wolfram 0 1").ok().unwrap();
+    assert!(matches!(pc, Command::Wolfram { rule: 0, width: 1, height: 1 }));
+
+    let error = parse_command("wolfram 256 11").err().unwrap();
+    assert_eq!(error, "Invalid argument value");
+
+    let error = parse_command("wolfram 30 0").err().unwrap();
+    assert_eq!(error, "Invalid argument value");
+
+    let error = parse_command("wolfram thirty 11").err().unwrap();
+    assert_eq!(error, "Invalid first argument value: not a number");
+
+    let error = parse_command("wolfram 30 11 six").err().unwrap();
+    assert_eq!(error, "Invalid third argument value: not a number");
+
+    let error = parse_command("wolfram 30").err().unwrap();
+    assert_eq!(error, "Invalid syntax for command");
+}
+
+#[test]
+fn command_reversible() {
+    assert_eq!(Command::Reversible { enabled: true }.to_string(), "Reversible [enabled: true]");
+    assert_eq!(Command::Reverse { generations: 3 }.to_string(), "Reverse [generations: 3]");
+
+    let pc = parse_command("reversible on").ok().unwrap();
+    assert!(matches!(pc, Command::Reversible { enabled: true }));
+
+    let pc = parse_command("reversible off").ok().unwrap();
+    assert!(matches!(pc, Command::Reversible { enabled: false }));
+
+    let pc = parse_command("reverse").ok().unwrap();
+    assert!(matches!(pc, Command::Reverse { generations: 1 }));
+
+    let pc = parse_command("reverse 10").ok().unwrap();
+    assert!(matches!(pc, Command::Reverse { generations: 10 }));
+
+    let error = parse_command("reversible").err().unwrap();
+    assert_eq!(error, "Invalid syntax for command");
+
+    let error = parse_command("reverse 0").err().unwrap();
+    assert_eq!(error, "Invalid argument value");
+
+    let error = parse_command("reverse ten").err().unwrap();
+    assert_eq!(error, "Invalid first argument value: not a number");
+}
@@ -1,28 +1,263 @@
+use ::game::ant;
 use ::game::board;
 use ::game::command;
+use ::game::hashlife;
+use ::game::history;
+use ::game::pattern;
+use ::game::period;
+use ::game::random;
+use ::game::rule;
+use ::game::schedule;
+use ::game::universe;
+use ::game::wolfram;
+use std::fs;
+use std::mem;
+
+// Records the cells of the board changed since before, in lists reused from the dropped changes
+fn record_board(history: &mut history::History, board: &board::Board, before: &board::Board, generations: usize) {
+    let (mut cells, mut dying) = (history.cells_buffer(), history.dying_buffer());
+    board.diff_into(before, &mut cells);
+    board.dying_diff_into(before, &mut dying);
+    history.record(history::Change::Board { cells, dying, generations });
+}
 
+// The game is played either on a fixed size board or on an unbounded universe,
+// at most one of them is set at any time
 pub struct Engine {
     board: Option<board::Board>,
+    // buffer where the next generation of the board is written, then swapped with it
+    spare: Option<board::Board>,
+    universe: Option<universe::Universe>,
+    // ants walking on the board, while there are any they are moved instead of applying the rule
+    ants: Vec<ant::Ant>,
+    // Wolfram code of the elementary automaton drawn on the board as a space-time diagram
+    wolfram: Option<u8>,
+    rule: rule::Rule,
+    hashlife: hashlife::HashLife,
+    history: history::History,
+    generation: usize,
+    period: period::PeriodDetector,
+    // milliseconds between generations when playing continuously
+    delay: u64,
+    // worker threads used to step boards
+    threads: usize,
+    // draws the random choices of stochastic rules and schedules, one key per generation
+    random: random::Random,
+    // order in which the cells of boards are updated
+    schedule: schedule::Schedule,
+    // second-order reversible mode, the previous generation of the board takes part in the next one
+    reversible: bool,
+    previous: Option<board::Board>,
+    // generation the mode was enabled at or the game created at, it can not be reversed past it
+    reversible_start: usize,
 }
 
 impl Engine {
     pub fn new() -> Engine {
         Engine {
-            board: None
+            board: None,
+            spare: None,
+            universe: None,
+            ants: Vec::new(),
+            wolfram: None,
+            rule: rule::Rule::conway(),
+            hashlife: hashlife::HashLife::new(rule::Rule::conway()),
+            history: history::History::new(history::HISTORY_SIZE),
+            generation: 0,
+            period: period::PeriodDetector::new(),
+            delay: 1000,
+            threads: 1,
+            random: random::Random::new(0),
+            schedule: schedule::Schedule::Synchronous,
+            reversible: false,
+            previous: None,
+            reversible_start: 0,
+        }
+    }
+
+    pub fn board(&self) -> Option<&board::Board> {
+        self.board.as_ref()
+    }
+
+    pub fn universe(&self) -> Option<&universe::Universe> {
+        self.universe.as_ref()
+    }
+
+    pub fn ants(&self) -> &[ant::Ant] {
+        &self.ants
+    }
+
+    // Board or universe as printed after every command, None without a game
+    pub fn render(&self) -> Option<String> {
+        if let Some(board) = self.board.as_ref() {
+            if self.ants.is_empty() {
+                Some(board.render(&self.rule))
+            } else {
+                Some(ant::render(board, &self.ants))
+            }
+        } else {
+            self.universe.as_ref().map(|universe| universe.to_string())
+        }
+    }
+
+    pub fn wolfram(&self) -> Option<u8> {
+        self.wolfram
+    }
+
+    pub fn rule(&self) -> rule::Rule {
+        self.rule
+    }
+
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    pub fn delay(&self) -> u64 {
+        self.delay
+    }
+
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
+
+    pub fn population(&self) -> usize {
+        if let Some(board) = self.board.as_ref() {
+            board.population()
+        } else if let Some(universe) = self.universe.as_ref() {
+            universe.population()
+        } else {
+            0
+        }
+    }
+
+    // Checks if the last command was a step that did not change any cell
+    pub fn is_static(&self) -> bool {
+        match self.history.last() {
+            Some(change) => change.generations() > 0 && change.is_empty(),
+            None => false,
+        }
+    }
+
+    // Cells evaluated to compute the last generation of the board, None for universes
+    pub fn evaluated(&self) -> Option<usize> {
+        self.board.as_ref().map(|board| board.evaluated())
+    }
+
+    // Period of the pattern detected while stepping, None until it repeats
+    pub fn period(&self) -> Option<period::Period> {
+        self.period.period()
+    }
+
+    // Records the current generation in the period detector
+    fn record_period(&mut self) {
+        if let Some(board) = self.board.as_ref() {
+            self.period.record(self.generation, board.signature(), |cells| board.cells_into(cells));
+        } else if let Some(universe) = self.universe.as_ref() {
+            self.period.record_cells(self.generation, universe.cells().iter().cloned());
+        }
+    }
+
+    // Saves the whole game before a command replaces it
+    fn record_game(&mut self) {
+        self.period.clear();
+        let snapshot = history::Snapshot {
+            board: self.board.clone(),
+            universe: self.universe.clone(),
+            rule: self.rule,
+            ants: mem::take(&mut self.ants),
+            wolfram: self.wolfram.take(),
+            previous: self.previous.take(),
+            reversible_start: mem::replace(&mut self.reversible_start, 0),
+            generation: self.generation
+        };
+        self.history.record(history::Change::Game(Box::new(snapshot)));
+    }
+
+    // Sets the state of a board cell, coordinates are 1 based. The change is recorded in the history
+    fn set_state(&mut self, x: usize, y: usize, alive: bool, dying: u8) -> Result<(), &str> {
+        self.period.clear();
+        let board = match self.board.as_mut() {
+            Some(board) => board,
+            None => return Err("Game not created yet"),
+        };
+        if x == 0 || x > board.width || y == 0 || y > board.height {
+            return Err("Index out of bounds");
+        }
+
+        let (x, y) = (x - 1, y - 1);
+        let mut change = (Vec::new(), Vec::new());
+        if board.get(x, y).ok().unwrap() != alive {
+            board.toggle(x, y).ok();
+            change.0.push((x, y));
+        }
+        let previous = board.dying(x, y).ok().unwrap();
+        if previous != dying {
+            board.set_dying(x, y, dying).ok();
+            change.1.push((x, y, previous ^ dying));
+        }
+        self.history.record(history::Change::Board { cells: change.0, dying: change.1, generations: 0 });
+        Ok(())
+    }
+
+    // Paints a conductor on an empty cell, or empties any other cell
+    fn wire(&mut self, x: usize, y: usize) -> Result<(), &str> {
+        let state = self.wireworld_state(x, y)?;
+        if state == (false, 0) {
+            self.set_state(x, y, false, rule::CONDUCTOR)
+        } else {
+            self.set_state(x, y, false, 0)
+        }
+    }
+
+    // Turns a conductor into an electron head, a head into a tail and a tail back into a conductor,
+    // so the direction of the electron is given by placing its tail
+    fn electron(&mut self, x: usize, y: usize) -> Result<(), &str> {
+        match self.wireworld_state(x, y)? {
+            (true, _) => self.set_state(x, y, false, rule::TAIL),
+            (false, rule::TAIL) => self.set_state(x, y, false, rule::CONDUCTOR),
+            (false, rule::CONDUCTOR) => self.set_state(x, y, true, 0),
+            _ => Err("Electrons can only be injected into conductors"),
         }
     }
 
-    pub fn board(&self) -> Option<board::Board> {
-        self.board.clone()
+    // Alive and dying state of a cell of a Wireworld board, coordinates are 1 based
+    fn wireworld_state(&self, x: usize, y: usize) -> Result<(bool, u8), &'static str> {
+        if !self.rule.is_wireworld() {
+            return Err("Wireworld rule not selected");
+        }
+        match self.board.as_ref() {
+            Some(board) if x > 0 && x <= board.width && y > 0 && y <= board.height => {
+                Ok((board.get(x - 1, y - 1).ok().unwrap(), board.dying(x - 1, y - 1).ok().unwrap()))
+            },
+            Some(_) => Err("Index out of bounds"),
+            None if self.universe.is_some() => Err("Wireworld is only supported on boards"),
+            None => Err("Game not created yet"),
+        }
     }
 
     fn toggle(&mut self, x: usize, y: usize) -> Result<(), &str> {
-        if self.board.is_some() {
-            let board = self.board.as_mut().unwrap();
+        self.period.clear();
+        if let Some(board) = self.board.as_mut() {
             if x == 0 || x > board.width || y == 0 || y > board.height {
                 Err("Index out of bounds")
             } else {
-                board.toggle(x - 1, y - 1)
+                // toggling a dying cell makes it dead
+                let dying = board.dying(x - 1, y - 1).ok().unwrap();
+                if dying > 0 {
+                    self.history.record(history::Change::Board { cells: vec![], dying: vec![(x - 1, y - 1, dying)], generations: 0 });
+                    board.set_dying(x - 1, y - 1, 0)
+                } else {
+                    self.history.record(history::Change::Board { cells: vec![(x - 1, y - 1)], dying: vec![], generations: 0 });
+                    board.toggle(x - 1, y - 1)
+                }
+            }
+        } else if let Some(universe) = self.universe.as_mut() {
+            if x == 0 || y == 0 {
+                Err("Index out of bounds")
+            } else {
+                self.history.record(history::Change::Universe { cells: vec![(x as i64 - 1, y as i64 - 1)], generations: 0 });
+                universe.toggle(x as i64 - 1, y as i64 - 1);
+                Ok(())
             }
         } else {
             Err("Game not created yet")
@@ -30,57 +265,518 @@ impl Engine {
     }
 
     fn play(&mut self) -> Result<(), &str> {
-        if self.board.is_some() {
-            let board = self.board.as_mut().unwrap();
-            let cloned = board.clone();
-
-            for i in 0..board.width {
-                for j in 0..board.height {
-                    let is_alive = cloned.get(i, j).ok().unwrap();
-                    let neighbours = cloned.neighbours(i, j);
-                    if is_alive {
-                        match neighbours {
-                                0 | 1 => { board.set(i, j, false).is_ok(); },  // dies, by under-population
-                                2 | 3 => {},                                   // lives
-                                _ => { board.set(i, j, false).is_ok(); },      // dies, by over-population
-                        }
-                    } else {
-                        match neighbours {
-                                3 => { board.set(i, j, true).is_ok(); },       // borns, by reproduction
-                                _ => {},                                       // remains death
-                        }
+        self.advance(1)
+    }
+
+    // Boards are stepped one generation at a time, with the schedule when it is not synchronous.
+    // Universes are advanced with HashLife when jumping more than one generation. B0 rules are
+    // stepped with the rule for each generation, see Rule::strobing. The cells that changed
+    // are recorded in the history
+    fn advance(&mut self, generations: usize) -> Result<(), &str> {
+        if self.period.is_empty() {
+            self.record_period();
+        }
+
+        if self.reversible && self.board.is_some() {
+            self.advance_reversible(generations, false)?;
+        } else if !self.ants.is_empty() {
+            self.advance_ants(generations);
+        } else if let (Some(rule), Some(board)) = (self.wolfram, self.board.as_mut()) {
+            let before = board.clone();
+            for generation in self.generation..self.generation + generations {
+                wolfram::step(rule, board, generation);
+            }
+            record_board(&mut self.history, board, &before, generations);
+        } else if let (Some(board), true) = (self.board.as_mut(), self.schedule != schedule::Schedule::Synchronous) {
+            if self.rule.range().is_some() || self.rule.strobing() {
+                return Err("Only synchronous updates are supported with B0 and Larger than Life rules");
+            }
+            let before = board.clone();
+            for _ in 0..generations {
+                schedule::step(self.schedule, &self.rule, board, self.random.next_u64());
+            }
+            record_board(&mut self.history, board, &before, generations);
+        } else if let Some(board) = self.board.as_mut() {
+            // the spare buffer is only allocated again when the board changes its shape
+            if !self.spare.as_ref().is_some_and(|spare| spare.same_shape(board)) {
+                self.spare = Some(board.clone());
+            }
+            let spare = self.spare.as_mut().unwrap();
+
+            // a single generation is compared with the previous one, left in the spare buffer
+            let before = if generations > 1 { Some(board.clone()) } else { None };
+            for generation in self.generation..self.generation + generations {
+                let key = if self.rule.chances().is_some() { self.random.next_u64() } else { 0 };
+                board.step_into(&self.rule.at_generation(generation), self.threads, key, spare);
+                mem::swap(board, spare);
+            }
+
+            let before = before.as_ref().unwrap_or(spare);
+            record_board(&mut self.history, board, before, generations);
+        } else if let Some(universe) = self.universe.as_mut() {
+            if self.rule.states() > 2 {
+                return Err("Generations rules are only supported on boards");
+            }
+            if self.rule.range().is_some() {
+                return Err("Larger than Life rules are only supported on boards");
+            }
+            if self.rule.neighbourhood() != rule::Neighbourhood::Moore {
+                return Err("Only the Moore neighbourhood is supported on universes");
+            }
+            if self.rule.isotropic().is_some() {
+                return Err("Non-totalistic rules are only supported on boards");
+            }
+            if self.rule.chances().is_some() {
+                return Err("Stochastic rules are only supported on boards");
+            }
+            if self.schedule != schedule::Schedule::Synchronous {
+                return Err("Asynchronous updates are only supported on boards");
+            }
+            if self.reversible {
+                return Err("Reversible mode is only supported on boards");
+            }
+            let before = universe.clone();
+
+            if generations == 1 || self.rule.strobing() {
+                // B0 rules change on every generation so they can not be jumped with HashLife
+                for generation in self.generation..self.generation + generations {
+                    universe.step(&self.rule.at_generation(generation));
+                }
+            } else {
+                if self.hashlife.rule() != self.rule {
+                    self.hashlife = hashlife::HashLife::new(self.rule);
+                }
+                *universe = self.hashlife.jump(universe, generations as u64)?;
+            }
+
+            self.history.record(history::Change::Universe { cells: universe.diff(&before), generations });
+        } else {
+            return Err("Game not created yet");
+        }
+
+        self.generation += generations;
+        self.record_period();
+        Ok(())
+    }
+
+    // Steps the board in reversible mode: the next generation is the outcome of the rule xor'ed with
+    // the previous one, so the previous one is also the outcome of the rule on the current one xor'ed
+    // with the next. Going backward swaps the roles of both generations. The generation before the
+    // first one is empty
+    fn advance_reversible(&mut self, generations: usize, backward: bool) -> Result<(), &'static str> {
+        if self.rule.states() > 2 {
+            return Err("Reversible mode only supports two state rules");
+        }
+        if self.rule.chances().is_some() {
+            return Err("Stochastic rules can not be reversed");
+        }
+        if self.schedule != schedule::Schedule::Synchronous {
+            return Err("Reversible mode only supports synchronous updates");
+        }
+        if !self.ants.is_empty() || self.wolfram.is_some() {
+            return Err("Reversible mode is not supported with ants or Wolfram rules");
+        }
+        if backward && generations > self.generation {
+            return Err("Can not reverse before generation 0");
+        }
+        if backward && generations > self.generation - self.reversible_start {
+            return Err("Can not reverse before the generation reversible mode was enabled at");
+        }
+        let board = match self.board.as_mut() {
+            Some(board) => board,
+            None => return Err("Game not created yet"),
+        };
+        if !self.previous.as_ref().is_some_and(|previous| previous.same_shape(board)) {
+            self.previous = Some(board::Board::with_storage(board.width, board.height, board.topology, board.storage));
+        }
+        if !self.spare.as_ref().is_some_and(|spare| spare.same_shape(board)) {
+            self.spare = Some(board.clone());
+        }
+        let previous = self.previous.as_mut().unwrap();
+        let spare = self.spare.as_mut().unwrap();
+
+        // a single generation is compared with the boards left in previous and spare
+        let before = if generations > 1 { Some((board.clone(), previous.clone())) } else { None };
+        for i in 0..generations {
+            if backward {
+                previous.step_into(&self.rule.at_generation(self.generation - i - 1), self.threads, 0, spare);
+                spare.xor(board);
+                mem::swap(board, previous);
+                mem::swap(previous, spare);
+            } else {
+                board.step_into(&self.rule.at_generation(self.generation + i), self.threads, 0, spare);
+                spare.xor(previous);
+                mem::swap(previous, board);
+                mem::swap(board, spare);
+            }
+        }
+        let (board_before, previous_before) = match before {
+            Some((ref board, ref previous)) => (board, previous),
+            None if backward => (&*spare, &*board),
+            None => (&*previous, &*spare),
+        };
+        let (mut cells, mut previous_cells) = (self.history.cells_buffer(), self.history.cells_buffer());
+        board.diff_into(board_before, &mut cells);
+        previous.diff_into(previous_before, &mut previous_cells);
+        self.history.record(history::Change::Reversible { cells, previous: previous_cells, generations, backward });
+        Ok(())
+    }
+
+    // Steps the board backward in reversible mode
+    fn reverse(&mut self, generations: usize) -> Result<(), &str> {
+        if !self.reversible {
+            return Err("Reversible mode is not enabled");
+        }
+        if self.universe.is_some() {
+            return Err("Reversible mode is only supported on boards");
+        }
+        self.advance_reversible(generations, true)?;
+        self.period.clear();
+        self.generation -= generations;
+        Ok(())
+    }
+
+    // Moves every ant in turn once per generation, the ants that walk off the board are dropped
+    fn advance_ants(&mut self, generations: usize) {
+        let board = match self.board.as_mut() {
+            Some(board) => board,
+            None => return,
+        };
+        let before = self.ants.clone();
+        let mut cells = Vec::new();
+        let mut dying = Vec::new();
+
+        for _ in 0..generations {
+            self.ants.retain_mut(|ant| {
+                let (x, y) = (ant.x, ant.y);
+                let (alive, previous) = (board.get(x, y).ok().unwrap(), board.dying(x, y).ok().unwrap());
+                let walks = ant.step(board);
+                // flips and xor'ed states of the same cell add up when applied again
+                if board.get(x, y).ok().unwrap() != alive {
+                    cells.push((x, y));
+                }
+                let state = board.dying(x, y).ok().unwrap();
+                if state != previous {
+                    dying.push((x, y, state ^ previous));
+                }
+                walks
+            });
+        }
+
+        self.history.record(history::Change::Ants { cells, dying, ants: before, generations });
+    }
+
+    // Places an ant facing up on a board cell, coordinates are 1 based
+    fn add_ant(&mut self, x: usize, y: usize, rule: ant::AntRule) -> Result<(), &str> {
+        match self.board.as_ref() {
+            Some(board) if x == 0 || x > board.width || y == 0 || y > board.height => return Err("Index out of bounds"),
+            Some(_) => {},
+            None if self.universe.is_some() => return Err("Ants are only supported on boards"),
+            None => return Err("Game not created yet"),
+        }
+        self.period.clear();
+        self.history.record(history::Change::Ants { cells: vec![], dying: vec![], ants: self.ants.clone(), generations: 0 });
+        self.ants.push(ant::Ant::new(x - 1, y - 1, rule));
+        Ok(())
+    }
+
+    fn clear_ants(&mut self) -> Result<(), &str> {
+        if self.ants.is_empty() {
+            return Err("No ants on the board");
+        }
+        self.period.clear();
+        self.history.record(history::Change::Ants { cells: vec![], dying: vec![], ants: mem::take(&mut self.ants), generations: 0 });
+        Ok(())
+    }
+
+    fn jump(&mut self, generations: usize) -> Result<(), &str> {
+        self.advance(generations)
+    }
+
+    // Applies a change from the history, leaving in it what is needed to revert it again
+    fn apply(&mut self, change: &mut history::Change, undo: bool) {
+        self.period.clear();
+        match *change {
+            history::Change::Board { ref cells, ref dying, generations } => {
+                if let Some(board) = self.board.as_mut() {
+                    for &(x, y) in cells {
+                        board.toggle(x, y).ok();
+                    }
+                    for &(x, y, flipped) in dying {
+                        let state = board.dying(x, y).ok().unwrap();
+                        board.set_dying(x, y, state ^ flipped).ok();
+                    }
+                }
+                self.generation = if undo { self.generation - generations } else { self.generation + generations };
+            },
+            history::Change::Reversible { ref cells, ref previous, generations, backward } => {
+                if let Some(board) = self.board.as_mut() {
+                    for &(x, y) in cells {
+                        board.toggle(x, y).ok();
+                    }
+                }
+                if let Some(board) = self.previous.as_mut() {
+                    for &(x, y) in previous {
+                        board.toggle(x, y).ok();
+                    }
+                }
+                self.generation = if undo != backward { self.generation - generations } else { self.generation + generations };
+            },
+            history::Change::Ants { ref cells, ref dying, ref mut ants, generations } => {
+                if let Some(board) = self.board.as_mut() {
+                    for &(x, y) in cells {
+                        board.toggle(x, y).ok();
+                    }
+                    for &(x, y, flipped) in dying {
+                        let state = board.dying(x, y).ok().unwrap();
+                        board.set_dying(x, y, state ^ flipped).ok();
+                    }
+                }
+                mem::swap(&mut self.ants, ants);
+                self.generation = if undo { self.generation - generations } else { self.generation + generations };
+            },
+            history::Change::Universe { ref cells, generations } => {
+                if let Some(universe) = self.universe.as_mut() {
+                    for &(x, y) in cells {
+                        universe.toggle(x, y);
                     }
                 }
+                self.generation = if undo { self.generation - generations } else { self.generation + generations };
+            },
+            history::Change::Rule(ref mut rule) => {
+                mem::swap(&mut self.rule, rule);
+            },
+            history::Change::ReversibleMode { ref mut enabled, ref mut previous, ref mut start } => {
+                mem::swap(&mut self.reversible, enabled);
+                mem::swap(&mut self.previous, previous);
+                mem::swap(&mut self.reversible_start, start);
+            },
+            history::Change::Game(ref mut snapshot) => {
+                mem::swap(&mut self.board, &mut snapshot.board);
+                mem::swap(&mut self.universe, &mut snapshot.universe);
+                mem::swap(&mut self.rule, &mut snapshot.rule);
+                mem::swap(&mut self.ants, &mut snapshot.ants);
+                mem::swap(&mut self.wolfram, &mut snapshot.wolfram);
+                mem::swap(&mut self.previous, &mut snapshot.previous);
+                mem::swap(&mut self.reversible_start, &mut snapshot.reversible_start);
+                mem::swap(&mut self.generation, &mut snapshot.generation);
+            },
+        }
+    }
+
+    fn undo(&mut self) -> Result<(), &str> {
+        match self.history.undo() {
+            Some(mut change) => {
+                self.apply(&mut change, true);
+                self.history.undone(change);
+                Ok(())
+            },
+            None => Err("Nothing to undo"),
+        }
+    }
+
+    fn redo(&mut self) -> Result<(), &str> {
+        match self.history.redo() {
+            Some(mut change) => {
+                self.apply(&mut change, false);
+                self.history.redone(change);
+                Ok(())
+            },
+            None => Err("Nothing to redo"),
+        }
+    }
+
+    // Undoes changes until the given number of generations have been reverted,
+    // stopping before any edit of the game
+    fn back(&mut self, generations: usize) -> Result<(), &str> {
+        let mut reverted = 0;
+
+        while reverted < generations {
+            match self.history.last().map(|change| change.generations()) {
+                Some(n) if n > 0 => {
+                    self.undo().ok();
+                    reverted += n;
+                },
+                _ => break,
             }
+        }
+
+        if reverted > 0 {
             Ok(())
         } else {
-            Err("Game not created yet")
+            Err("No generations to go back to")
+        }
+    }
+
+    // Loads the pattern into the current universe or board, replacing its content.
+    // Without a game, a new board with the size of the pattern is created
+    fn load(&mut self, path: &str) -> Result<(), String> {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(_) => return Err("Unable to read file".to_string()),
+        };
+        let (pattern, rule) = match pattern::parse_pattern(&content) {
+            Ok(result) => result,
+            Err(error) => return Err(format!("Invalid pattern file: {}", error)),
+        };
+
+        if let Some(board) = self.board.as_ref() {
+            if pattern.width > board.width || pattern.height > board.height {
+                return Err("Pattern does not fit in the board".to_string());
+            }
+        }
+
+        self.record_game();
+        self.generation = 0;
+
+        if let Some(universe) = self.universe.as_mut() {
+            *universe = universe::Universe::from_board(&pattern);
+        } else if let Some(board) = self.board.as_mut() {
+
+            // centre the pattern in the board
+            let x_offset = (board.width - pattern.width) / 2;
+            let y_offset = (board.height - pattern.height) / 2;
+            *board = board::Board::with_storage(board.width, board.height, board.topology, board.storage);
+            for x in 0..pattern.width {
+                for y in 0..pattern.height {
+                    board.set(x + x_offset, y + y_offset, pattern.get(x, y).ok().unwrap()).ok();
+                }
+            }
+        } else {
+            self.board = Some(pattern);
+        }
+
+        if let Some(rule) = rule {
+            self.rule = rule;
         }
+        Ok(())
     }
 
-    pub fn process_command(&mut self, command: command::Command) -> Result<(), &str> {
-        println!("Command: {}", command);
+    // The format is chosen from the file extension
+    fn save(&self, path: &str) -> Result<(), &str> {
+        let format = pattern::format_from_path(path);
+        let content = if let Some(board) = self.board.as_ref() {
+            pattern::write_pattern(board, &self.rule, format)
+        } else if let Some(universe) = self.universe.as_ref() {
+            pattern::write_pattern(&universe.to_board(), &self.rule, format)
+        } else {
+            return Err("Game not created yet");
+        };
+
+        match fs::write(path, content) {
+            Ok(_) => Ok(()),
+            Err(_) => Err("Unable to write file"),
+        }
+    }
 
-        match command {
+    pub fn process_command(&mut self, command: command::Command) -> Result<(), String> {
+        let result = match command {
             command::Command::Quit => {
                 self.board = None;
+                self.universe = None;
+                self.ants.clear();
+                self.wolfram = None;
+                self.previous = None;
+                self.history.clear();
+                Ok(())
+            },
+            command::Command::Play { .. } => self.play(),
+            command::Command::Speed { delay } => {
+                self.delay = delay;
+                Ok(())
+            },
+            command::Command::Threads { threads } => {
+                self.threads = threads;
+                Ok(())
+            },
+            command::Command::Seed { seed } => {
+                self.random = random::Random::new(seed);
+                Ok(())
+            },
+            command::Command::Schedule { schedule } => {
+                self.schedule = schedule;
+                Ok(())
+            },
+            command::Command::Reversible { enabled } => {
+                // the generation before the current one is taken as empty from now on
+                self.history.record(history::Change::ReversibleMode { enabled: self.reversible, previous: self.previous.take(), start: self.reversible_start });
+                self.period.clear();
+                self.reversible = enabled;
+                self.reversible_start = self.generation;
                 Ok(())
             },
-            command::Command::Play => self.play(),
+            command::Command::Reverse { generations } => self.reverse(generations),
             command::Command::Step => self.play(),
-            command::Command::New { width, height } => {
-                self.board = Some(board::Board::new(width, height));
+            command::Command::New { width, height, topology, storage } => {
+                self.record_game();
+                self.board = Some(board::Board::with_storage(width, height, topology, storage));
+                self.universe = None;
+                self.generation = 0;
+                Ok(())
+            },
+            command::Command::Wolfram { rule, width, height } => {
+                self.record_game();
+                self.board = Some(wolfram::seed(width, height));
+                self.universe = None;
+                self.wolfram = Some(rule);
+                self.generation = 0;
+                Ok(())
+            },
+            command::Command::Universe => {
+                self.record_game();
+                self.board = None;
+                self.universe = Some(universe::Universe::new());
+                self.generation = 0;
                 Ok(())
             },
+            command::Command::Jump { generations } => self.jump(generations),
+            command::Command::Load { path } => return self.load(&path),
+            command::Command::Save { path } => self.save(&path),
             command::Command::Toggle { x, y } => self.toggle(x, y),
-        }
+            command::Command::Wire { x, y } => self.wire(x, y),
+            command::Command::Electron { x, y } => self.electron(x, y),
+            command::Command::Ant { x, y, rule } => self.add_ant(x, y, rule),
+            command::Command::ClearAnts => self.clear_ants(),
+            command::Command::Rule { rule } => {
+                self.history.record(history::Change::Rule(self.rule));
+                self.period.clear();
+                self.rule = rule;
+                Ok(())
+            },
+            command::Command::Undo => self.undo(),
+            command::Command::Redo => self.redo(),
+            command::Command::Back { generations } => self.back(generations),
+            command::Command::Tui => Ok(()),
+            command::Command::Period | command::Command::Stats => {
+                if self.board.is_some() || self.universe.is_some() {
+                    Ok(())
+                } else {
+                    Err("Game not created yet")
+                }
+            },
+        };
+        result.map_err(String::from)
+    }
+}
+
+impl Default for Engine {
+    fn default() -> Engine {
+        Engine::new()
     }
 }
 
+// Command creating a bounded board of cells, the game most tests play on
+#[cfg(test)]
+fn new_board(width: usize, height: usize) -> command::Command {
+    command::Command::New { width, height, topology: board::Topology::Bounded, storage: board::Storage::Cells }
+}
+
 #[test]
 fn engine_constructor() {
     let e = Engine::new();
     assert!(e.board.is_none());
+    assert!(e.universe.is_none());
+    assert_eq!(e.rule(), rule::Rule::conway());
 }
 
 #[test]
@@ -93,7 +789,7 @@ fn engine_board() {
 #[test]
 fn engine_cmd_new() {
     let mut e = Engine::new();
-    assert!(e.process_command(command::Command::New { width: 10, height: 10 } ).is_ok());
+    assert!(e.process_command(new_board(10, 10)).is_ok());
 
     let b = e.board();
     assert!(b.is_some());
@@ -101,7 +797,7 @@ fn engine_cmd_new() {
     assert_eq!(b.width, 10);
     assert_eq!(b.height, 10);
 
-    assert!(e.process_command(command::Command::New { width: 30, height: 20 } ).is_ok());
+    assert!(e.process_command(new_board(30, 20)).is_ok());
     let b = e.board().unwrap();
     assert_eq!(b.width, 30);
     assert_eq!(b.height, 20);
@@ -112,10 +808,15 @@ fn engine_cmd_quit() {
     let mut e = Engine::new();
     assert!(e.process_command(command::Command::Quit).is_ok());
 
-    assert!(e.process_command(command::Command::New { width: 10, height: 10 } ).is_ok());
+    assert!(e.process_command(new_board(10, 10)).is_ok());
     assert!(e.process_command(command::Command::Quit).is_ok());
 
     assert!(e.board.is_none());
+
+    assert!(e.process_command(command::Command::Universe).is_ok());
+    assert!(e.process_command(command::Command::Quit).is_ok());
+
+    assert!(e.universe.is_none());
 }
 
 #[test]
@@ -124,12 +825,12 @@ fn engine_toggle() {
     assert!(e.toggle(3, 7).is_err());
     assert_eq!(e.toggle(3, 7).err().unwrap(), "Game not created yet");
 
-    assert!(e.process_command(command::Command::New { width: 10, height: 10 } ).is_ok());
+    assert!(e.process_command(new_board(10, 10)).is_ok());
     let b = e.board().unwrap();
-    assert_eq!(b.get(2, 6).ok().unwrap(), false);
+    assert!(!b.get(2, 6).ok().unwrap());
     assert!(e.toggle(3, 7).is_ok());
     let b = e.board().unwrap();
-    assert_eq!(b.get(2, 6).ok().unwrap(), true);
+    assert!(b.get(2, 6).ok().unwrap());
 
     assert!(e.toggle(0, 0).is_err());
     assert!(e.toggle(1, 0).is_err());
@@ -146,22 +847,22 @@ fn engine_cmd_toggle() {
     assert!(e.process_command(command::Command::Toggle { x: 3, y: 7 } ).is_err());
     assert_eq!(e.process_command(command::Command::Toggle { x: 3, y: 7 } ).err().unwrap(), "Game not created yet");
 
-    assert!(e.process_command(command::Command::New { width: 10, height: 10 } ).is_ok());
+    assert!(e.process_command(new_board(10, 10)).is_ok());
     let b = e.board().unwrap();
-    assert_eq!(b.get(2, 6).ok().unwrap(), false);
+    assert!(!b.get(2, 6).ok().unwrap());
     assert!(e.process_command(command::Command::Toggle { x: 3, y: 7 } ).is_ok());
     let b = e.board().unwrap();
-    assert_eq!(b.get(2, 6).ok().unwrap(), true);
+    assert!(b.get(2, 6).ok().unwrap());
 }
 
 #[test]
 fn engine_cmd_play() {
     let mut e = Engine::new();
-    assert!(e.process_command(command::Command::Play).is_err());
-    assert_eq!(e.process_command(command::Command::Play).err().unwrap(), "Game not created yet");
+    assert!(e.process_command(command::Command::Play { generations: None }).is_err());
+    assert_eq!(e.process_command(command::Command::Play { generations: None }).err().unwrap(), "Game not created yet");
 
-    assert!(e.process_command(command::Command::New { width: 10, height: 10 } ).is_ok());
-    assert!(e.process_command(command::Command::Play).is_ok());
+    assert!(e.process_command(new_board(10, 10)).is_ok());
+    assert!(e.process_command(command::Command::Play { generations: None }).is_ok());
 }
 
 #[test]
@@ -170,7 +871,7 @@ fn engine_cmd_step() {
     assert!(e.process_command(command::Command::Step).is_err());
     assert_eq!(e.process_command(command::Command::Step).err().unwrap(), "Game not created yet");
 
-    assert!(e.process_command(command::Command::New { width: 10, height: 10 } ).is_ok());
+    assert!(e.process_command(new_board(10, 10)).is_ok());
     assert!(e.process_command(command::Command::Step).is_ok());
 }
 
@@ -180,7 +881,7 @@ fn engine_play() {
     assert!(e.play().is_err());
     assert_eq!(e.play().err().unwrap(), "Game not created yet");
 
-    assert!(e.process_command(command::Command::New { width: 6, height: 6 } ).is_ok());
+    assert!(e.process_command(new_board(6, 6)).is_ok());
     assert!(e.play().is_ok());
     let b = e.board().unwrap();
 
@@ -198,14 +899,14 @@ fn engine_play() {
 fn engine_play_starve_underpopulation() {
     let mut e = Engine::new();
 
-    assert!(e.process_command(command::Command::New { width: 3, height: 3 } ).is_ok());
+    assert!(e.process_command(new_board(3, 3)).is_ok());
 
     // starve 0 neighbours
     assert!(e.toggle(1, 1).is_ok());
     assert!(e.play().is_ok());
 
     let b = e.board().unwrap();
-    assert_eq!(b.get(0,0).unwrap(), false);
+    assert!(!b.get(0,0).unwrap());
 
     // starve 1 neighbour
     assert!(e.toggle(1, 1).is_ok());
@@ -213,7 +914,7 @@ fn engine_play_starve_underpopulation() {
     assert!(e.play().is_ok());
 
     let b = e.board().unwrap();
-    assert_eq!(b.get(0,0).unwrap(), false);
+    assert!(!b.get(0,0).unwrap());
 }
 
 #[test]
@@ -221,17 +922,17 @@ fn engine_play_survive() {
     let mut e = Engine::new();
 
     // survive 2 neighbours
-    assert!(e.process_command(command::Command::New { width: 3, height: 3 } ).is_ok());
+    assert!(e.process_command(new_board(3, 3)).is_ok());
     assert!(e.toggle(1, 1).is_ok());
     assert!(e.toggle(2, 1).is_ok());
     assert!(e.toggle(3, 1).is_ok());
     assert!(e.play().is_ok());
 
     let b = e.board().unwrap();
-    assert_eq!(b.get(1,0).unwrap(), true);
+    assert!(b.get(1,0).unwrap());
 
     // survive 3 neighbours
-    assert!(e.process_command(command::Command::New { width: 3, height: 3 } ).is_ok());
+    assert!(e.process_command(new_board(3, 3)).is_ok());
     assert!(e.toggle(1, 1).is_ok());
     assert!(e.toggle(2, 1).is_ok());
     assert!(e.toggle(3, 1).is_ok());
@@ -239,7 +940,7 @@ fn engine_play_survive() {
     assert!(e.play().is_ok());
 
     let b = e.board().unwrap();
-    assert_eq!(b.get(1,1).unwrap(), true);
+    assert!(b.get(1,1).unwrap());
 }
 
 #[test]
@@ -247,7 +948,7 @@ fn engine_play_starve_overpopulation() {
     let mut e = Engine::new();
 
     // starve 4 neighbours
-    assert!(e.process_command(command::Command::New { width: 3, height: 3 } ).is_ok());
+    assert!(e.process_command(new_board(3, 3)).is_ok());
     assert!(e.toggle(1, 1).is_ok());
     assert!(e.toggle(2, 1).is_ok());
     assert!(e.toggle(3, 1).is_ok());
@@ -256,10 +957,10 @@ fn engine_play_starve_overpopulation() {
     assert!(e.play().is_ok());
 
     let b = e.board().unwrap();
-    assert_eq!(b.get(1,0).unwrap(), false);
+    assert!(!b.get(1,0).unwrap());
 
     // starve 5 neighbours
-    assert!(e.process_command(command::Command::New { width: 3, height: 3 } ).is_ok());
+    assert!(e.process_command(new_board(3, 3)).is_ok());
     assert!(e.toggle(1, 1).is_ok());
     assert!(e.toggle(2, 1).is_ok());
     assert!(e.toggle(3, 1).is_ok());
@@ -269,10 +970,10 @@ fn engine_play_starve_overpopulation() {
     assert!(e.play().is_ok());
 
     let b = e.board().unwrap();
-    assert_eq!(b.get(1,0).unwrap(), false);
+    assert!(!b.get(1,0).unwrap());
 
     // starve 6 neighbours
-    assert!(e.process_command(command::Command::New { width: 3, height: 3 } ).is_ok());
+    assert!(e.process_command(new_board(3, 3)).is_ok());
     assert!(e.toggle(1, 1).is_ok());
     assert!(e.toggle(2, 1).is_ok());
     assert!(e.toggle(3, 1).is_ok());
@@ -283,10 +984,10 @@ fn engine_play_starve_overpopulation() {
     assert!(e.play().is_ok());
 
     let b = e.board().unwrap();
-    assert_eq!(b.get(1,0).unwrap(), false);
+    assert!(!b.get(1,0).unwrap());
 
     // starve 7 neighbours
-    assert!(e.process_command(command::Command::New { width: 3, height: 3 } ).is_ok());
+    assert!(e.process_command(new_board(3, 3)).is_ok());
     assert!(e.toggle(1, 1).is_ok());
     assert!(e.toggle(2, 1).is_ok());
     assert!(e.toggle(3, 1).is_ok());
@@ -298,10 +999,10 @@ fn engine_play_starve_overpopulation() {
     assert!(e.play().is_ok());
 
     let b = e.board().unwrap();
-    assert_eq!(b.get(1,0).unwrap(), false);
+    assert!(!b.get(1,0).unwrap());
 
     // starve 8 neighbours
-    assert!(e.process_command(command::Command::New { width: 3, height: 3 } ).is_ok());
+    assert!(e.process_command(new_board(3, 3)).is_ok());
     assert!(e.toggle(1, 1).is_ok());
     assert!(e.toggle(2, 1).is_ok());
     assert!(e.toggle(3, 1).is_ok());
@@ -314,7 +1015,7 @@ fn engine_play_starve_overpopulation() {
     assert!(e.play().is_ok());
 
     let b = e.board().unwrap();
-    assert_eq!(b.get(1,0).unwrap(), false);
+    assert!(!b.get(1,0).unwrap());
 }
 
 #[test]
@@ -322,34 +1023,34 @@ fn engine_play_born() {
     let mut e = Engine::new();
 
     // 1 neighbour no born
-    assert!(e.process_command(command::Command::New { width: 3, height: 3 } ).is_ok());
+    assert!(e.process_command(new_board(3, 3)).is_ok());
     assert!(e.toggle(1, 1).is_ok());
     assert!(e.play().is_ok());
 
     let b = e.board().unwrap();
-    assert_eq!(b.get(1,1).unwrap(), false);
+    assert!(!b.get(1,1).unwrap());
 
     // 2 neighbours no born
-    assert!(e.process_command(command::Command::New { width: 3, height: 3 } ).is_ok());
+    assert!(e.process_command(new_board(3, 3)).is_ok());
     assert!(e.toggle(1, 1).is_ok());
     assert!(e.toggle(2, 1).is_ok());
     assert!(e.play().is_ok());
 
     let b = e.board().unwrap();
-    assert_eq!(b.get(1,1).unwrap(), false);
+    assert!(!b.get(1,1).unwrap());
 
     // 3 neighbours new born!
-    assert!(e.process_command(command::Command::New { width: 3, height: 3 } ).is_ok());
+    assert!(e.process_command(new_board(3, 3)).is_ok());
     assert!(e.toggle(1, 1).is_ok());
     assert!(e.toggle(2, 1).is_ok());
     assert!(e.toggle(3, 1).is_ok());
     assert!(e.play().is_ok());
 
     let b = e.board().unwrap();
-    assert_eq!(b.get(1,1).unwrap(), true);
+    assert!(b.get(1,1).unwrap());
 
     // 4 neighbours no born
-    assert!(e.process_command(command::Command::New { width: 3, height: 3 } ).is_ok());
+    assert!(e.process_command(new_board(3, 3)).is_ok());
     assert!(e.toggle(1, 1).is_ok());
     assert!(e.toggle(2, 1).is_ok());
     assert!(e.toggle(3, 1).is_ok());
@@ -357,10 +1058,10 @@ fn engine_play_born() {
     assert!(e.play().is_ok());
 
     let b = e.board().unwrap();
-    assert_eq!(b.get(1,1).unwrap(), false);
+    assert!(!b.get(1,1).unwrap());
 
     // 5 neighbours no born
-    assert!(e.process_command(command::Command::New { width: 3, height: 3 } ).is_ok());
+    assert!(e.process_command(new_board(3, 3)).is_ok());
     assert!(e.toggle(1, 1).is_ok());
     assert!(e.toggle(2, 1).is_ok());
     assert!(e.toggle(3, 1).is_ok());
@@ -369,10 +1070,10 @@ fn engine_play_born() {
     assert!(e.play().is_ok());
 
     let b = e.board().unwrap();
-    assert_eq!(b.get(1,1).unwrap(), false);
+    assert!(!b.get(1,1).unwrap());
 
     // 6 neighbours no born
-    assert!(e.process_command(command::Command::New { width: 3, height: 3 } ).is_ok());
+    assert!(e.process_command(new_board(3, 3)).is_ok());
     assert!(e.toggle(1, 1).is_ok());
     assert!(e.toggle(2, 1).is_ok());
     assert!(e.toggle(3, 1).is_ok());
@@ -382,10 +1083,10 @@ fn engine_play_born() {
     assert!(e.play().is_ok());
 
     let b = e.board().unwrap();
-    assert_eq!(b.get(1,1).unwrap(), false);
+    assert!(!b.get(1,1).unwrap());
 
     // 7 neighbours no born
-    assert!(e.process_command(command::Command::New { width: 3, height: 3 } ).is_ok());
+    assert!(e.process_command(new_board(3, 3)).is_ok());
     assert!(e.toggle(1, 1).is_ok());
     assert!(e.toggle(2, 1).is_ok());
     assert!(e.toggle(3, 1).is_ok());
@@ -396,10 +1097,10 @@ fn engine_play_born() {
     assert!(e.play().is_ok());
 
     let b = e.board().unwrap();
-    assert_eq!(b.get(1,1).unwrap(), false);
+    assert!(!b.get(1,1).unwrap());
 
     // 8 neighbours no born
-    assert!(e.process_command(command::Command::New { width: 3, height: 3 } ).is_ok());
+    assert!(e.process_command(new_board(3, 3)).is_ok());
     assert!(e.toggle(1, 1).is_ok());
     assert!(e.toggle(2, 1).is_ok());
     assert!(e.toggle(3, 1).is_ok());
@@ -411,14 +1112,14 @@ fn engine_play_born() {
     assert!(e.play().is_ok());
 
     let b = e.board().unwrap();
-    assert_eq!(b.get(1,1).unwrap(), false);
+    assert!(!b.get(1,1).unwrap());
 }
 
 #[test]
 fn engine_play_blinker() {
     let mut e = Engine::new();
 
-    assert!(e.process_command(command::Command::New { width: 3, height: 3 } ).is_ok());
+    assert!(e.process_command(new_board(3, 3)).is_ok());
     assert!(e.toggle(1, 2).is_ok());
     assert!(e.toggle(2, 2).is_ok());
     assert!(e.toggle(3, 2).is_ok());
@@ -443,7 +1144,7 @@ fn engine_play_blinker() {
 fn engine_play_block() {
     let mut e = Engine::new();
 
-    assert!(e.process_command(command::Command::New { width: 4, height: 4 } ).is_ok());
+    assert!(e.process_command(new_board(4, 4)).is_ok());
     assert!(e.toggle(2, 2).is_ok());
     assert!(e.toggle(2, 3).is_ok());
     assert!(e.toggle(3, 2).is_ok());
@@ -462,3 +1163,1387 @@ fn engine_play_block() {
     let b = e.board().unwrap();
     assert_eq!(b.to_string(), expected);
 }
+
+#[test]
+fn engine_cmd_rule() {
+    let mut e = Engine::new();
+    let highlife = rule::parse_rule("B36/S23").ok().unwrap();
+    assert!(e.process_command(command::Command::Rule { rule: highlife }).is_ok());
+    assert_eq!(e.rule(), highlife);
+
+    // the rule is kept when a new board is created
+    assert!(e.process_command(new_board(3, 3)).is_ok());
+    assert_eq!(e.rule(), highlife);
+}
+
+#[test]
+fn engine_play_highlife() {
+    let mut e = Engine::new();
+    assert!(e.process_command(command::Command::Rule { rule: rule::parse_rule("B36/S23").ok().unwrap() }).is_ok());
+
+    // 6 neighbours new born
+    assert!(e.process_command(new_board(3, 3)).is_ok());
+    assert!(e.toggle(1, 1).is_ok());
+    assert!(e.toggle(2, 1).is_ok());
+    assert!(e.toggle(3, 1).is_ok());
+    assert!(e.toggle(1, 3).is_ok());
+    assert!(e.toggle(2, 3).is_ok());
+    assert!(e.toggle(3, 3).is_ok());
+    assert!(e.play().is_ok());
+
+    let b = e.board().unwrap();
+    assert!(b.get(1, 1).unwrap());
+}
+
+#[test]
+fn engine_play_seeds() {
+    let mut e = Engine::new();
+    assert!(e.process_command(command::Command::Rule { rule: rule::parse_rule("B2/S").ok().unwrap() }).is_ok());
+
+    assert!(e.process_command(new_board(4, 4)).is_ok());
+    assert!(e.toggle(2, 2).is_ok());
+    assert!(e.toggle(3, 2).is_ok());
+
+    let expected = "_XX_\n\
+                    ____\n\
+                    _XX_\n\
+                    ____\n";
+
+    assert!(e.play().is_ok());
+    let b = e.board().unwrap();
+    assert_eq!(b.to_string(), expected);
+}
+
+#[test]
+fn engine_play_day_and_night() {
+    let mut e = Engine::new();
+    assert!(e.process_command(command::Command::Rule { rule: rule::parse_rule("B3678/S34678").ok().unwrap() }).is_ok());
+
+    // a block is still stable in Day & Night: every cell has 3 neighbours
+    assert!(e.process_command(new_board(4, 4)).is_ok());
+    assert!(e.toggle(2, 2).is_ok());
+    assert!(e.toggle(2, 3).is_ok());
+    assert!(e.toggle(3, 2).is_ok());
+    assert!(e.toggle(3, 3).is_ok());
+
+    let expected = "____\n\
+                    _XX_\n\
+                    _XX_\n\
+                    ____\n";
+
+    assert!(e.play().is_ok());
+    let b = e.board().unwrap();
+    assert_eq!(b.to_string(), expected);
+
+    // a blinker does not oscillate: the ends have 1 neighbour and the centre 2, so they all die
+    assert!(e.process_command(new_board(3, 3)).is_ok());
+    assert!(e.toggle(1, 2).is_ok());
+    assert!(e.toggle(2, 2).is_ok());
+    assert!(e.toggle(3, 2).is_ok());
+
+    let expected = "_X_\n\
+                    ___\n\
+                    _X_\n";
+
+    assert!(e.play().is_ok());
+    let b = e.board().unwrap();
+    assert_eq!(b.to_string(), expected);
+}
+
+#[test]
+fn engine_cmd_new_topology() {
+    let mut e = Engine::new();
+    assert!(e.process_command(command::Command::New { width: 10, height: 10, topology: board::Topology::Torus, storage: board::Storage::Cells } ).is_ok());
+    assert_eq!(e.board().unwrap().topology, board::Topology::Torus);
+
+    assert!(e.process_command(command::Command::New { width: 10, height: 10, topology: board::Topology::Cylinder, storage: board::Storage::Cells } ).is_ok());
+    assert_eq!(e.board().unwrap().topology, board::Topology::Cylinder);
+}
+
+#[test]
+fn engine_play_glider_torus() {
+    let mut e = Engine::new();
+
+    assert!(e.process_command(command::Command::New { width: 6, height: 6, topology: board::Topology::Torus, storage: board::Storage::Cells } ).is_ok());
+    assert!(e.toggle(2, 1).is_ok());
+    assert!(e.toggle(3, 2).is_ok());
+    assert!(e.toggle(1, 3).is_ok());
+    assert!(e.toggle(2, 3).is_ok());
+    assert!(e.toggle(3, 3).is_ok());
+
+    let start = e.board().unwrap().to_string();
+
+    // the glider moves one cell diagonally every 4 generations, so it needs
+    // 4 * 6 generations to go around the whole board
+    for generation in 1..24 {
+        assert!(e.play().is_ok());
+        assert!(e.board().unwrap().to_string() != start, "generation {}", generation);
+    }
+    assert!(e.play().is_ok());
+    assert_eq!(e.board().unwrap().to_string(), start);
+}
+
+#[test]
+fn engine_play_glider_bounded() {
+    let mut e = Engine::new();
+
+    assert!(e.process_command(new_board(6, 6)).is_ok());
+    assert!(e.toggle(2, 1).is_ok());
+    assert!(e.toggle(3, 2).is_ok());
+    assert!(e.toggle(1, 3).is_ok());
+    assert!(e.toggle(2, 3).is_ok());
+    assert!(e.toggle(3, 3).is_ok());
+
+    let start = e.board().unwrap().to_string();
+
+    // the glider crashes against the bottom right corner and becomes a block
+    for _ in 0..24 {
+        assert!(e.play().is_ok());
+    }
+    let expected = "______\n\
+                    ______\n\
+                    ______\n\
+                    ______\n\
+                    ____XX\n\
+                    ____XX\n";
+    assert!(e.board().unwrap().to_string() != start);
+    assert_eq!(e.board().unwrap().to_string(), expected);
+}
+
+#[test]
+fn engine_play_blinker_cylinder() {
+    let mut e = Engine::new();
+
+    // a blinker crossing the vertical edges keeps oscillating
+    assert!(e.process_command(command::Command::New { width: 5, height: 5, topology: board::Topology::Cylinder, storage: board::Storage::Cells } ).is_ok());
+    assert!(e.toggle(5, 3).is_ok());
+    assert!(e.toggle(1, 3).is_ok());
+    assert!(e.toggle(2, 3).is_ok());
+
+    let expected = "_____\n\
+                    X____\n\
+                    X____\n\
+                    X____\n\
+                    _____\n";
+
+    assert!(e.play().is_ok());
+    assert_eq!(e.board().unwrap().to_string(), expected);
+
+    // but the top and bottom edges are still bounded
+    assert!(e.process_command(command::Command::New { width: 5, height: 5, topology: board::Topology::Cylinder, storage: board::Storage::Cells } ).is_ok());
+    assert!(e.toggle(3, 5).is_ok());
+    assert!(e.toggle(3, 1).is_ok());
+    assert!(e.toggle(3, 2).is_ok());
+
+    let expected = "_____\n\
+                    _____\n\
+                    _____\n\
+                    _____\n\
+                    _____\n";
+
+    assert!(e.play().is_ok());
+    assert_eq!(e.board().unwrap().to_string(), expected);
+}
+
+#[test]
+fn engine_cmd_universe() {
+    let mut e = Engine::new();
+    assert!(e.universe().is_none());
+
+    assert!(e.process_command(command::Command::Universe).is_ok());
+    assert!(e.board().is_none());
+    assert_eq!(e.universe().unwrap().population(), 0);
+
+    assert!(e.process_command(command::Command::Toggle { x: 3, y: 7 } ).is_ok());
+    assert!(e.universe().unwrap().get(2, 6));
+    assert!(e.toggle(0, 1).is_err());
+    assert_eq!(e.toggle(1, 0).err().unwrap(), "Index out of bounds");
+
+    // creating a board discards the universe and the other way round
+    assert!(e.process_command(new_board(10, 10)).is_ok());
+    assert!(e.universe().is_none());
+    assert!(e.board().is_some());
+
+    assert!(e.process_command(command::Command::Universe).is_ok());
+    assert!(e.board().is_none());
+    assert!(e.universe().is_some());
+}
+
+#[test]
+fn engine_play_universe_glider() {
+    let mut e = Engine::new();
+
+    assert!(e.process_command(command::Command::Universe).is_ok());
+    assert!(e.toggle(2, 1).is_ok());
+    assert!(e.toggle(3, 2).is_ok());
+    assert!(e.toggle(1, 3).is_ok());
+    assert!(e.toggle(2, 3).is_ok());
+    assert!(e.toggle(3, 3).is_ok());
+
+    let start = e.universe().unwrap().to_string();
+
+    // the glider flies away past any fixed size board instead of crashing
+    for _ in 0..40 {
+        assert!(e.process_command(command::Command::Step).is_ok());
+    }
+
+    let u = e.universe().unwrap();
+    assert_eq!(u.population(), 5);
+    assert_eq!(u.bounding_box(), Some((10, 10, 12, 12)));
+    assert_eq!(u.to_string(), start);
+}
+
+#[test]
+fn engine_cmd_jump() {
+    let mut e = Engine::new();
+    assert!(e.process_command(command::Command::Jump { generations: 4 }).is_err());
+    assert_eq!(e.process_command(command::Command::Jump { generations: 4 }).err().unwrap(), "Game not created yet");
+
+    // on a board, jumping is the same as stepping
+    assert!(e.process_command(new_board(3, 3)).is_ok());
+    assert!(e.toggle(1, 2).is_ok());
+    assert!(e.toggle(2, 2).is_ok());
+    assert!(e.toggle(3, 2).is_ok());
+    let start = e.board().unwrap().to_string();
+
+    assert!(e.process_command(command::Command::Jump { generations: 3 }).is_ok());
+    assert!(e.board().unwrap().to_string() != start);
+    assert!(e.process_command(command::Command::Jump { generations: 1 }).is_ok());
+    assert_eq!(e.board().unwrap().to_string(), start);
+}
+
+#[test]
+fn engine_jump_universe() {
+    let mut e = Engine::new();
+
+    assert!(e.process_command(command::Command::Universe).is_ok());
+    assert!(e.toggle(2, 1).is_ok());
+    assert!(e.toggle(3, 2).is_ok());
+    assert!(e.toggle(1, 3).is_ok());
+    assert!(e.toggle(2, 3).is_ok());
+    assert!(e.toggle(3, 3).is_ok());
+
+    let mut stepped = e.universe().unwrap().clone();
+    for _ in 0..1001 {
+        stepped.step(&e.rule());
+    }
+
+    assert!(e.process_command(command::Command::Jump { generations: 1001 }).is_ok());
+    let u = e.universe().unwrap();
+    assert_eq!(u.cells(), stepped.cells());
+
+    // the memoised results are discarded when the rule changes
+    assert!(e.process_command(command::Command::Rule { rule: rule::parse_rule("B2/S").ok().unwrap() }).is_ok());
+    let mut stepped = e.universe().unwrap().clone();
+    stepped.step(&e.rule());
+    assert!(e.process_command(command::Command::Jump { generations: 1 }).is_ok());
+    assert_eq!(e.universe().unwrap().cells(), stepped.cells());
+}
+
+#[cfg(test)]
+fn temp_file(name: &str, content: &str) -> String {
+    let path = ::std::env::temp_dir().join(format!("rust_of_life_{}_{}", ::std::process::id(), name));
+    fs::write(&path, content).ok().unwrap();
+    path.to_str().unwrap().to_string()
+}
+
+#[test]
+fn engine_cmd_load() {
+    let glider = temp_file("load_glider.rle", "#N Glider\nx = 3, y = 3, rule = B36/S23\nbo$2bo$3o!\n");
+    let expected = "_X_\n\
+                    __X\n\
+                    XXX\n";
+
+    // without a game a board of the size of the pattern is created
+    let mut e = Engine::new();
+    assert!(e.process_command(command::Command::Load { path: glider.clone() }).is_ok());
+    assert_eq!(e.board().unwrap().to_string(), expected);
+    assert_eq!(e.rule(), rule::parse_rule("B36/S23").ok().unwrap());
+
+    // the pattern is centred in the current board
+    assert!(e.process_command(command::Command::New { width: 5, height: 4, topology: board::Topology::Torus, storage: board::Storage::Cells } ).is_ok());
+    assert!(e.toggle(1, 1).is_ok());
+    assert!(e.process_command(command::Command::Load { path: glider.clone() }).is_ok());
+    let b = e.board().unwrap();
+    assert_eq!(b.topology, board::Topology::Torus);
+    let expected_board = "__X__\n\
+                          ___X_\n\
+                          _XXX_\n\
+                          _____\n";
+    assert_eq!(b.to_string(), expected_board);
+
+    assert!(e.process_command(new_board(2, 2)).is_ok());
+    assert_eq!(e.process_command(command::Command::Load { path: glider.clone() }).err().unwrap(), "Pattern does not fit in the board");
+
+    assert!(e.process_command(command::Command::Universe).is_ok());
+    assert!(e.process_command(command::Command::Load { path: glider.clone() }).is_ok());
+    assert_eq!(e.universe().unwrap().to_string(), expected);
+
+    assert_eq!(e.process_command(command::Command::Load { path: "/nonexistent/glider.rle".to_string() }).err().unwrap(), "Unable to read file");
+    let invalid = temp_file("load_invalid.rle", "#N Glider\nx = 3, y = 3\nbo$2bo$3o!\n");
+    assert!(e.process_command(command::Command::Load { path: invalid.clone() }).is_ok());
+    fs::write(&invalid, "#N Glider\nx = 3, y = 3\nbo$2bo$3x!\n").ok().unwrap();
+    assert_eq!(e.process_command(command::Command::Load { path: invalid.clone() }).err().unwrap(), "Invalid pattern file: Line 3: Invalid RLE: unexpected character");
+
+    fs::remove_file(glider).ok();
+    fs::remove_file(invalid).ok();
+}
+
+#[test]
+fn engine_cmd_save() {
+    let path = temp_file("save_glider.rle", "");
+
+    let mut e = Engine::new();
+    assert_eq!(e.process_command(command::Command::Save { path: path.clone() }).err().unwrap(), "Game not created yet");
+
+    assert!(e.process_command(new_board(4, 4)).is_ok());
+    assert!(e.toggle(2, 1).is_ok());
+    assert!(e.toggle(3, 2).is_ok());
+    assert!(e.toggle(1, 3).is_ok());
+    assert!(e.toggle(2, 3).is_ok());
+    assert!(e.toggle(3, 3).is_ok());
+    assert!(e.process_command(command::Command::Save { path: path.clone() }).is_ok());
+    assert_eq!(fs::read_to_string(&path).ok().unwrap(), "x = 4, y = 4, rule = B3/S23\nbo$2bo$3o!\n");
+
+    // universes are saved with the size of their bounding box
+    assert!(e.process_command(command::Command::Universe).is_ok());
+    assert!(e.toggle(5, 5).is_ok());
+    assert!(e.toggle(7, 6).is_ok());
+    assert!(e.process_command(command::Command::Save { path: path.clone() }).is_ok());
+    assert_eq!(fs::read_to_string(&path).ok().unwrap(), "x = 3, y = 2, rule = B3/S23\no$2bo!\n");
+
+    assert!(e.process_command(new_board(4, 4)).is_ok());
+    assert!(e.process_command(command::Command::Load { path: path.clone() }).is_ok());
+    let expected = "____\n\
+                    X___\n\
+                    __X_\n\
+                    ____\n";
+    assert_eq!(e.board().unwrap().to_string(), expected);
+
+    fs::remove_file(path).ok();
+}
+
+#[test]
+fn engine_load_save_formats() {
+    let expected = "_X_\n\
+                    __X\n\
+                    XXX\n";
+
+    let mut e = Engine::new();
+    let cells = temp_file("glider.cells", "!Name: Glider\n.O.\n..O\nOOO\n");
+    assert!(e.process_command(command::Command::Load { path: cells.clone() }).is_ok());
+    assert_eq!(e.board().unwrap().to_string(), expected);
+
+    let life = temp_file("glider.lif", "#Life 1.06\n0 -1\n1 0\n-1 1\n0 1\n1 1\n");
+    assert!(e.process_command(command::Command::Load { path: life.clone() }).is_ok());
+    assert_eq!(e.board().unwrap().to_string(), expected);
+
+    // the saved format depends on the extension, the loaded one on the content
+    assert!(e.process_command(command::Command::Save { path: cells.clone() }).is_ok());
+    assert_eq!(fs::read_to_string(&cells).ok().unwrap(), ".O.\n..O\nOOO\n");
+    assert!(e.process_command(command::Command::Save { path: life.clone() }).is_ok());
+    assert_eq!(fs::read_to_string(&life).ok().unwrap(), "#Life 1.06\n1 0\n2 1\n0 2\n1 2\n2 2\n");
+    assert!(e.process_command(command::Command::Load { path: life.clone() }).is_ok());
+    assert_eq!(e.board().unwrap().to_string(), expected);
+
+    fs::write(&cells, "!Name: Glider\n.O.\n..O\nO#O\n").ok().unwrap();
+    assert_eq!(e.process_command(command::Command::Load { path: cells.clone() }).err().unwrap(), "Invalid pattern file: Line 4: Invalid plaintext: expected '.' or 'O'");
+
+    fs::remove_file(cells).ok();
+    fs::remove_file(life).ok();
+}
+
+#[test]
+fn engine_generation() {
+    let mut e = Engine::new();
+    assert_eq!(e.generation(), 0);
+
+    assert!(e.process_command(new_board(5, 5)).is_ok());
+    assert!(e.process_command(command::Command::Step).is_ok());
+    assert!(e.process_command(command::Command::Jump { generations: 10 }).is_ok());
+    assert_eq!(e.generation(), 11);
+
+    assert!(e.process_command(command::Command::Universe).is_ok());
+    assert_eq!(e.generation(), 0);
+    assert!(e.process_command(command::Command::Jump { generations: 10 }).is_ok());
+    assert_eq!(e.generation(), 10);
+}
+
+#[test]
+fn engine_cmd_undo_redo() {
+    let mut e = Engine::new();
+    assert_eq!(e.process_command(command::Command::Undo).err().unwrap(), "Nothing to undo");
+    assert_eq!(e.process_command(command::Command::Redo).err().unwrap(), "Nothing to redo");
+
+    assert!(e.process_command(new_board(3, 3)).is_ok());
+    assert!(e.process_command(command::Command::Toggle { x: 1, y: 2 } ).is_ok());
+    assert!(e.process_command(command::Command::Toggle { x: 2, y: 2 } ).is_ok());
+    assert!(e.process_command(command::Command::Toggle { x: 3, y: 2 } ).is_ok());
+    assert!(e.process_command(command::Command::Toggle { x: 3, y: 3 } ).is_ok());
+
+    let blinker = "___\n\
+                   XXX\n\
+                   ___\n";
+
+    // a mistaken toggle
+    assert!(e.process_command(command::Command::Undo).is_ok());
+    assert_eq!(e.board().unwrap().to_string(), blinker);
+
+    // an accidental step
+    assert!(e.process_command(command::Command::Step).is_ok());
+    assert_eq!(e.generation(), 1);
+    assert!(e.process_command(command::Command::Undo).is_ok());
+    assert_eq!(e.board().unwrap().to_string(), blinker);
+    assert_eq!(e.generation(), 0);
+
+    assert!(e.process_command(command::Command::Redo).is_ok());
+    assert_eq!(e.generation(), 1);
+    assert_eq!(e.board().unwrap().to_string(), "_X_\n_X_\n_X_\n");
+    assert!(e.process_command(command::Command::Redo).is_err());
+
+    // undoing a new game brings the previous board back
+    assert!(e.process_command(command::Command::New { width: 5, height: 5, topology: board::Topology::Torus, storage: board::Storage::Cells } ).is_ok());
+    assert!(e.process_command(command::Command::Undo).is_ok());
+    assert_eq!(e.board().unwrap().to_string(), "_X_\n_X_\n_X_\n");
+    assert_eq!(e.generation(), 1);
+    assert!(e.process_command(command::Command::Redo).is_ok());
+    assert_eq!(e.board().unwrap().topology, board::Topology::Torus);
+    assert_eq!(e.generation(), 0);
+
+    // and the rule changes can be undone too
+    assert!(e.process_command(command::Command::Rule { rule: rule::parse_rule("B36/S23").ok().unwrap() }).is_ok());
+    assert!(e.process_command(command::Command::Undo).is_ok());
+    assert_eq!(e.rule(), rule::Rule::conway());
+}
+
+#[test]
+fn engine_undo_universe() {
+    let mut e = Engine::new();
+
+    assert!(e.process_command(command::Command::Universe).is_ok());
+    assert!(e.toggle(2, 1).is_ok());
+    assert!(e.toggle(3, 2).is_ok());
+    assert!(e.toggle(1, 3).is_ok());
+    assert!(e.toggle(2, 3).is_ok());
+    assert!(e.toggle(3, 3).is_ok());
+    let start = e.universe().unwrap().clone();
+
+    assert!(e.process_command(command::Command::Step).is_ok());
+    assert!(e.process_command(command::Command::Jump { generations: 100 }).is_ok());
+    assert!(e.process_command(command::Command::Undo).is_ok());
+    assert!(e.process_command(command::Command::Undo).is_ok());
+    assert_eq!(e.universe().unwrap().cells(), start.cells());
+    assert_eq!(e.generation(), 0);
+
+    // undoing the universe command goes back to no game at all
+    for _ in 0..5 {
+        assert!(e.process_command(command::Command::Undo).is_ok());
+    }
+    assert!(e.process_command(command::Command::Undo).is_ok());
+    assert!(e.universe().is_none());
+    assert!(e.process_command(command::Command::Undo).is_err());
+}
+
+#[test]
+fn engine_cmd_back() {
+    let mut e = Engine::new();
+    assert_eq!(e.process_command(command::Command::Back { generations: 1 }).err().unwrap(), "No generations to go back to");
+
+    assert!(e.process_command(command::Command::New { width: 6, height: 6, topology: board::Topology::Torus, storage: board::Storage::Cells } ).is_ok());
+    assert!(e.toggle(2, 1).is_ok());
+    assert!(e.toggle(3, 2).is_ok());
+    assert!(e.toggle(1, 3).is_ok());
+    assert!(e.toggle(2, 3).is_ok());
+    assert!(e.toggle(3, 3).is_ok());
+    let start = e.board().unwrap().to_string();
+
+    let mut generations = vec![start.clone()];
+    for _ in 0..10 {
+        assert!(e.process_command(command::Command::Step).is_ok());
+        generations.push(e.board().unwrap().to_string());
+    }
+
+    assert!(e.process_command(command::Command::Back { generations: 3 }).is_ok());
+    assert_eq!(e.generation(), 7);
+    assert_eq!(e.board().unwrap().to_string(), generations[7]);
+
+    // back stops at the last edit of the board
+    assert!(e.process_command(command::Command::Back { generations: 100 }).is_ok());
+    assert_eq!(e.generation(), 0);
+    assert_eq!(e.board().unwrap().to_string(), start);
+    assert!(e.process_command(command::Command::Back { generations: 1 }).is_err());
+
+    assert!(e.process_command(command::Command::Redo).is_ok());
+    assert_eq!(e.board().unwrap().to_string(), generations[1]);
+}
+
+#[test]
+fn engine_cmd_speed() {
+    let mut e = Engine::new();
+    assert_eq!(e.delay(), 1000);
+
+    assert!(e.process_command(command::Command::Speed { delay: 250 }).is_ok());
+    assert_eq!(e.delay(), 250);
+    assert!(e.process_command(command::Command::Speed { delay: 0 }).is_ok());
+    assert_eq!(e.delay(), 0);
+}
+
+#[test]
+fn engine_population() {
+    let mut e = Engine::new();
+    assert_eq!(e.population(), 0);
+
+    assert!(e.process_command(new_board(3, 3)).is_ok());
+    assert!(e.toggle(1, 1).is_ok());
+    assert!(e.toggle(2, 2).is_ok());
+    assert_eq!(e.population(), 2);
+
+    assert!(e.process_command(command::Command::Play { generations: None }).is_ok());
+    assert_eq!(e.population(), 0);
+
+    assert!(e.process_command(command::Command::Universe).is_ok());
+    assert!(e.toggle(100, 100).is_ok());
+    assert_eq!(e.population(), 1);
+}
+
+#[test]
+fn engine_is_static() {
+    let mut e = Engine::new();
+    assert!(!e.is_static());
+
+    assert!(e.process_command(new_board(4, 4)).is_ok());
+    assert!(e.toggle(2, 2).is_ok());
+    assert!(e.toggle(2, 3).is_ok());
+    assert!(e.toggle(3, 2).is_ok());
+    assert!(!e.is_static());
+
+    // three cells become a block in one generation, then it does not change anymore
+    assert!(e.process_command(command::Command::Play { generations: None }).is_ok());
+    assert!(!e.is_static());
+    assert!(e.process_command(command::Command::Play { generations: None }).is_ok());
+    assert!(e.is_static());
+
+    // a blinker is not static
+    assert!(e.process_command(new_board(3, 3)).is_ok());
+    assert!(e.toggle(1, 2).is_ok());
+    assert!(e.toggle(2, 2).is_ok());
+    assert!(e.toggle(3, 2).is_ok());
+    assert!(e.process_command(command::Command::Play { generations: None }).is_ok());
+    assert!(!e.is_static());
+}
+
+#[cfg(test)]
+fn run_until_period(e: &mut Engine, generations: usize) -> Option<period::Period> {
+    for _ in 0..generations {
+        assert!(e.process_command(command::Command::Step).is_ok());
+        if e.period().is_some() {
+            break;
+        }
+    }
+    e.period()
+}
+
+#[test]
+fn engine_period_still_life() {
+    let mut e = Engine::new();
+    assert!(e.process_command(command::Command::Period).is_err());
+
+    assert!(e.process_command(new_board(4, 4)).is_ok());
+    assert!(e.process_command(command::Command::Period).is_ok());
+    assert!(e.toggle(2, 2).is_ok());
+    assert!(e.toggle(2, 3).is_ok());
+    assert!(e.toggle(3, 2).is_ok());
+    assert!(e.toggle(3, 3).is_ok());
+    assert_eq!(e.period(), None);
+
+    assert_eq!(run_until_period(&mut e, 10), Some(period::Period::StillLife));
+    assert_eq!(e.generation(), 1);
+
+    // editing the board forgets the period
+    assert!(e.toggle(1, 1).is_ok());
+    assert_eq!(e.period(), None);
+}
+
+#[test]
+fn engine_period_blinker() {
+    let mut e = Engine::new();
+
+    assert!(e.process_command(new_board(5, 5)).is_ok());
+    assert!(e.toggle(2, 3).is_ok());
+    assert!(e.toggle(3, 3).is_ok());
+    assert!(e.toggle(4, 3).is_ok());
+
+    assert_eq!(run_until_period(&mut e, 10), Some(period::Period::Oscillator(2)));
+    assert_eq!(e.generation(), 2);
+}
+
+#[test]
+fn engine_period_pulsar() {
+    let mut e = Engine::new();
+
+    assert!(e.process_command(new_board(17, 17)).is_ok());
+    for &(x, y) in &[(3, 1), (4, 1), (5, 1), (1, 3), (6, 3), (1, 4), (6, 4), (1, 5), (6, 5), (3, 6), (4, 6), (5, 6)] {
+        // the four symmetric quarters of the pulsar
+        assert!(e.toggle(x + 2, y + 2).is_ok());
+        assert!(e.toggle(16 - x, y + 2).is_ok());
+        assert!(e.toggle(x + 2, 16 - y).is_ok());
+        assert!(e.toggle(16 - x, 16 - y).is_ok());
+    }
+    assert_eq!(e.population(), 48);
+
+    assert_eq!(run_until_period(&mut e, 10), Some(period::Period::Oscillator(3)));
+    assert_eq!(e.generation(), 3);
+}
+
+#[test]
+fn engine_period_glider() {
+    let mut e = Engine::new();
+
+    assert!(e.process_command(command::Command::Universe).is_ok());
+    assert!(e.toggle(2, 1).is_ok());
+    assert!(e.toggle(3, 2).is_ok());
+    assert!(e.toggle(1, 3).is_ok());
+    assert!(e.toggle(2, 3).is_ok());
+    assert!(e.toggle(3, 3).is_ok());
+
+    assert_eq!(run_until_period(&mut e, 10), Some(period::Period::Spaceship { dx: 1, dy: 1, period: 4 }));
+    assert_eq!(e.generation(), 4);
+
+    // also detected after jumps
+    assert!(e.process_command(command::Command::Jump { generations: 100 }).is_ok());
+    assert_eq!(e.period(), Some(period::Period::Spaceship { dx: 1, dy: 1, period: 4 }));
+}
+
+#[test]
+fn engine_packed_storage() {
+    let mut cells = Engine::new();
+    let mut packed = Engine::new();
+
+    assert!(cells.process_command(command::Command::New { width: 70, height: 10, topology: board::Topology::Torus, storage: board::Storage::Cells }).is_ok());
+    assert!(packed.process_command(command::Command::New { width: 70, height: 10, topology: board::Topology::Torus, storage: board::Storage::Packed }).is_ok());
+    assert_eq!(packed.board().unwrap().storage, board::Storage::Packed);
+
+    // a glider crossing the word boundary and the edges
+    for e in [&mut cells, &mut packed].iter_mut() {
+        for &(x, y) in &[(63, 1), (64, 2), (62, 3), (63, 3), (64, 3)] {
+            assert!(e.toggle(x, y).is_ok());
+        }
+    }
+
+    for _ in 0..40 {
+        assert!(cells.process_command(command::Command::Step).is_ok());
+        assert!(packed.process_command(command::Command::Step).is_ok());
+        assert_eq!(packed.board().unwrap().to_string(), cells.board().unwrap().to_string());
+    }
+    assert_eq!(packed.population(), 5);
+
+    // undo flips the recorded cells of the packed board back
+    assert!(packed.process_command(command::Command::Back { generations: 40 }).is_ok());
+    assert!(cells.process_command(command::Command::Back { generations: 40 }).is_ok());
+    assert_eq!(packed.board().unwrap().to_string(), cells.board().unwrap().to_string());
+    assert_eq!(packed.generation(), 0);
+}
+
+#[test]
+fn engine_threads() {
+    let mut e = Engine::new();
+    assert_eq!(e.threads(), 1);
+    assert!(e.process_command(command::Command::Threads { threads: 4 }).is_ok());
+    assert_eq!(e.threads(), 4);
+
+    for &storage in &[board::Storage::Cells, board::Storage::Packed] {
+        let mut sequential = Engine::new();
+        assert!(sequential.process_command(command::Command::New { width: 40, height: 23, topology: board::Topology::Torus, storage }).is_ok());
+        assert!(e.process_command(command::Command::New { width: 40, height: 23, topology: board::Topology::Torus, storage }).is_ok());
+
+        // an R-pentomino and a glider, so the bands see cells crossing them
+        for &(x, y) in &[(20, 10), (21, 10), (19, 11), (20, 11), (20, 12), (3, 1), (4, 2), (2, 3), (3, 3), (4, 3)] {
+            assert!(sequential.toggle(x, y).is_ok());
+            assert!(e.toggle(x, y).is_ok());
+        }
+
+        for _ in 0..30 {
+            assert!(sequential.process_command(command::Command::Step).is_ok());
+            assert!(e.process_command(command::Command::Step).is_ok());
+            assert_eq!(e.board().unwrap().to_string(), sequential.board().unwrap().to_string());
+        }
+    }
+}
+
+#[test]
+fn engine_double_buffer() {
+    let mut e = Engine::new();
+    assert!(e.process_command(new_board(5, 5)).is_ok());
+    assert!(e.spare.is_none());
+
+    assert!(e.toggle(2, 3).is_ok());
+    assert!(e.toggle(3, 3).is_ok());
+    assert!(e.toggle(4, 3).is_ok());
+    let first = e.board().unwrap().to_string();
+
+    // the previous generation is left in the spare buffer
+    assert!(e.process_command(command::Command::Step).is_ok());
+    let second = e.board().unwrap().to_string();
+    assert_eq!(e.spare.as_ref().unwrap().to_string(), first);
+    assert!(e.process_command(command::Command::Step).is_ok());
+    assert_eq!(e.board().unwrap().to_string(), first);
+    assert_eq!(e.spare.as_ref().unwrap().to_string(), second);
+    assert!(!e.is_static());
+
+    // a board with another shape gets a new spare buffer
+    assert!(e.process_command(command::Command::New { width: 7, height: 4, topology: board::Topology::Torus, storage: board::Storage::Packed }).is_ok());
+    assert!(e.process_command(command::Command::Step).is_ok());
+    assert!(e.spare.as_ref().unwrap().same_shape(e.board().unwrap()));
+    assert!(e.process_command(command::Command::Back { generations: 1 }).is_ok());
+    assert_eq!(e.board().unwrap().population(), 0);
+}
+
+#[test]
+fn engine_evaluated() {
+    let mut e = Engine::new();
+    assert!(e.process_command(command::Command::Stats).is_err());
+    assert_eq!(e.evaluated(), None);
+
+    assert!(e.process_command(command::Command::New { width: 200, height: 100, topology: board::Topology::Torus, storage: board::Storage::Packed }).is_ok());
+    assert!(e.process_command(command::Command::Stats).is_ok());
+    assert_eq!(e.evaluated(), Some(0));
+
+    // a glider on a large empty board only keeps a few tiles awake
+    for &(x, y) in &[(101, 50), (102, 51), (100, 52), (101, 52), (102, 52)] {
+        assert!(e.toggle(x, y).is_ok());
+    }
+    assert!(e.process_command(command::Command::Step).is_ok());
+    assert_eq!(e.evaluated(), Some(200 * 100));
+    for _ in 0..10 {
+        assert!(e.process_command(command::Command::Step).is_ok());
+        assert!(e.evaluated().unwrap() <= 3 * 64 * 3 * 16);
+    }
+    assert_eq!(e.population(), 5);
+
+    // going back restores the cells, and the next steps stay identical to a full scan
+    assert!(e.process_command(command::Command::Back { generations: 6 }).is_ok());
+    let mut b = e.board().unwrap().clone();
+    for _ in 0..20 {
+        assert!(e.process_command(command::Command::Step).is_ok());
+        let mut expected = board::Board::with_topology(b.width, b.height, b.topology);
+        for x in 0..b.width {
+            for y in 0..b.height {
+                expected.set(x, y, e.rule().next(b.get(x, y).ok().unwrap(), b.neighbours(x, y))).ok();
+            }
+        }
+        assert_eq!(e.board().unwrap().to_string(), expected.to_string());
+        b = expected;
+    }
+
+    assert!(e.process_command(command::Command::Universe).is_ok());
+    assert_eq!(e.evaluated(), None);
+}
+
+#[test]
+fn engine_generations() {
+    let mut e = Engine::new();
+    assert!(e.process_command(new_board(6, 5)).is_ok());
+    assert!(e.process_command(command::Command::Rule { rule: rule::parse_rule("B2/S/C3").ok().unwrap() }).is_ok());
+    assert!(e.toggle(2, 3).is_ok());
+    assert!(e.toggle(3, 3).is_ok());
+    let start = e.board().unwrap().to_string();
+
+    assert!(e.process_command(command::Command::Step).is_ok());
+    assert!(e.process_command(command::Command::Step).is_ok());
+    let second = e.board().unwrap().to_string();
+    assert!(second.contains('1'));
+
+    // going back restores the dying states
+    assert!(e.process_command(command::Command::Back { generations: 2 }).is_ok());
+    assert_eq!(e.board().unwrap().to_string(), start);
+    assert!(e.process_command(command::Command::Redo).is_ok());
+    assert!(e.process_command(command::Command::Redo).is_ok());
+    assert_eq!(e.board().unwrap().to_string(), second);
+
+    // toggling a dying cell makes it dead, undo brings it back
+    let (x, y) = (0..6).flat_map(|x| (0..5).map(move |y| (x, y))).find(|&(x, y)| e.board().unwrap().dying(x, y).ok() == Some(1)).unwrap();
+    assert!(e.toggle(x + 1, y + 1).is_ok());
+    assert_eq!(e.board().unwrap().cell_char(x, y, &e.rule()).ok(), Some('_'));
+    assert!(e.process_command(command::Command::Undo).is_ok());
+    assert_eq!(e.board().unwrap().cell_char(x, y, &e.rule()).ok(), Some('1'));
+
+    // universes only hold alive cells
+    assert!(e.process_command(command::Command::Universe).is_ok());
+    assert!(e.toggle(1, 1).is_ok());
+    assert_eq!(e.process_command(command::Command::Step).err().as_deref(), Some("Generations rules are only supported on boards"));
+}
+
+#[test]
+fn engine_wireworld() {
+    let mut e = Engine::new();
+    assert_eq!(e.process_command(command::Command::Wire { x: 1, y: 1 }).err().as_deref(), Some("Wireworld rule not selected"));
+    assert!(e.process_command(command::Command::Rule { rule: rule::Rule::wireworld() }).is_ok());
+    assert_eq!(e.process_command(command::Command::Wire { x: 1, y: 1 }).err().as_deref(), Some("Game not created yet"));
+
+    assert!(e.process_command(new_board(6, 3)).is_ok());
+    for x in 1..7 {
+        assert!(e.process_command(command::Command::Wire { x, y: 2 }).is_ok());
+    }
+    assert_eq!(e.process_command(command::Command::Wire { x: 7, y: 2 }).err().as_deref(), Some("Index out of bounds"));
+    assert_eq!(e.process_command(command::Command::Electron { x: 1, y: 1 }).err().as_deref(), Some("Electrons can only be injected into conductors"));
+    assert!(e.process_command(command::Command::Electron { x: 2, y: 2 }).is_ok());
+    assert_eq!(e.board().unwrap().render(&e.rule()), "______\n#H####\n______\n");
+
+    // injecting again turns the head into a tail, then into a conductor
+    assert!(e.process_command(command::Command::Electron { x: 1, y: 2 }).is_ok());
+    assert!(e.process_command(command::Command::Electron { x: 1, y: 2 }).is_ok());
+    assert_eq!(e.board().unwrap().render(&e.rule()), "______\ntH####\n______\n");
+    assert!(e.process_command(command::Command::Electron { x: 1, y: 2 }).is_ok());
+    assert_eq!(e.board().unwrap().render(&e.rule()), "______\n#H####\n______\n");
+    assert!(e.process_command(command::Command::Undo).is_ok());
+    assert_eq!(e.board().unwrap().render(&e.rule()), "______\ntH####\n______\n");
+
+    // the electron moves along the wire, leaving a tail behind
+    assert!(e.process_command(command::Command::Step).is_ok());
+    assert_eq!(e.board().unwrap().render(&e.rule()), "______\n#tH###\n______\n");
+    assert!(e.process_command(command::Command::Step).is_ok());
+    assert_eq!(e.board().unwrap().render(&e.rule()), "______\n##tH##\n______\n");
+
+    // the wire can be cut
+    assert!(e.process_command(command::Command::Wire { x: 6, y: 2 }).is_ok());
+    assert!(e.process_command(command::Command::Wire { x: 5, y: 2 }).is_ok());
+    assert_eq!(e.board().unwrap().render(&e.rule()), "______\n##tH__\n______\n");
+    assert!(e.process_command(command::Command::Step).is_ok());
+    assert_eq!(e.board().unwrap().render(&e.rule()), "______\n###t__\n______\n");
+    assert!(e.process_command(command::Command::Step).is_ok());
+    assert_eq!(e.board().unwrap().render(&e.rule()), "______\n####__\n______\n");
+
+    assert!(e.process_command(command::Command::Back { generations: 2 }).is_ok());
+    assert_eq!(e.board().unwrap().render(&e.rule()), "______\n##tH__\n______\n");
+
+    assert!(e.process_command(command::Command::Universe).is_ok());
+    assert_eq!(e.process_command(command::Command::Wire { x: 1, y: 1 }).err().as_deref(), Some("Wireworld is only supported on boards"));
+}
+
+#[test]
+fn engine_ant() {
+    let mut e = Engine::new();
+    let rule = ant::parse_ant_rule("RL").ok().unwrap();
+    assert_eq!(e.process_command(command::Command::Ant { x: 1, y: 1, rule: rule.clone() }).err().as_deref(), Some("Game not created yet"));
+    assert!(e.process_command(command::Command::Universe).is_ok());
+    assert_eq!(e.process_command(command::Command::Ant { x: 1, y: 1, rule: rule.clone() }).err().as_deref(), Some("Ants are only supported on boards"));
+
+    assert!(e.process_command(new_board(5, 3)).is_ok());
+    assert_eq!(e.process_command(command::Command::Ant { x: 6, y: 1, rule: rule.clone() }).err().as_deref(), Some("Index out of bounds"));
+    assert_eq!(e.process_command(command::Command::ClearAnts).err().as_deref(), Some("No ants on the board"));
+    assert!(e.process_command(command::Command::Ant { x: 3, y: 2, rule }).is_ok());
+    assert_eq!(e.render().unwrap(), "_____\n__^__\n_____\n");
+
+    // the ant moves instead of applying the rule
+    assert!(e.process_command(command::Command::Step).is_ok());
+    assert_eq!(e.render().unwrap(), "_____\n__X>_\n_____\n");
+    assert!(e.process_command(command::Command::Step).is_ok());
+    assert_eq!(e.render().unwrap(), "_____\n__XX_\n___v_\n");
+    assert!(e.process_command(command::Command::Jump { generations: 3 }).is_ok());
+    assert_eq!(e.render().unwrap(), "_____\n_<_X_\n__XX_\n");
+    assert_eq!(e.generation(), 5);
+    assert!(!e.is_static());
+
+    assert!(e.process_command(command::Command::Back { generations: 3 }).is_ok());
+    assert_eq!(e.render().unwrap(), "_____\n__XX_\n___v_\n");
+    assert!(e.process_command(command::Command::Redo).is_ok());
+    assert_eq!(e.render().unwrap(), "_____\n_<_X_\n__XX_\n");
+
+    // without ants the rule applies again
+    assert!(e.process_command(command::Command::ClearAnts).is_ok());
+    assert_eq!(e.render().unwrap(), "_____\n___X_\n__XX_\n");
+    assert!(e.process_command(command::Command::Step).is_ok());
+    assert_eq!(e.render().unwrap(), "_____\n__XX_\n__XX_\n");
+    assert!(e.process_command(command::Command::Undo).is_ok());
+    assert!(e.process_command(command::Command::Undo).is_ok());
+    assert_eq!(e.ants().len(), 1);
+
+    // a new game removes the ants
+    assert!(e.process_command(new_board(5, 3)).is_ok());
+    assert!(e.ants().is_empty());
+    assert!(e.process_command(command::Command::Undo).is_ok());
+    assert_eq!(e.ants().len(), 1);
+}
+
+#[test]
+fn engine_ants_multiple() {
+    let mut e = Engine::new();
+    assert!(e.process_command(new_board(4, 4)).is_ok());
+    let rule = ant::parse_ant_rule("NL").ok().unwrap();
+    assert!(e.process_command(command::Command::Ant { x: 1, y: 2, rule: rule.clone() }).is_ok());
+    assert!(e.process_command(command::Command::Ant { x: 3, y: 4, rule }).is_ok());
+
+    // both ants walk up, the first one falls off the board
+    assert!(e.process_command(command::Command::Jump { generations: 2 }).is_ok());
+    assert_eq!(e.ants().len(), 1);
+    assert_eq!(e.render().unwrap(), "X___\nX_^_\n__X_\n__X_\n");
+    assert!(e.process_command(command::Command::Back { generations: 2 }).is_ok());
+    assert_eq!(e.ants().len(), 2);
+    assert_eq!(e.population(), 0);
+}
+
+#[test]
+fn engine_ant_highway() {
+    let mut e = Engine::new();
+    assert!(e.process_command(command::Command::New { width: 200, height: 200, topology: board::Topology::Bounded, storage: board::Storage::Packed }).is_ok());
+    assert!(e.process_command(command::Command::Ant { x: 100, y: 100, rule: ant::parse_ant_rule("RL").ok().unwrap() }).is_ok());
+
+    // after about 10000 steps Langton's ant builds a highway, moving two cells diagonally every 104 steps
+    assert!(e.process_command(command::Command::Jump { generations: 10400 }).is_ok());
+    let (x, y) = (e.ants()[0].x as isize, e.ants()[0].y as isize);
+    let population = e.population();
+    assert!(e.process_command(command::Command::Jump { generations: 104 }).is_ok());
+    assert_eq!(((e.ants()[0].x as isize - x).abs(), (e.ants()[0].y as isize - y).abs()), (2, 2));
+    assert_eq!(e.population(), population + 12);
+}
+
+#[test]
+fn engine_wolfram() {
+    let mut e = Engine::new();
+    assert!(e.process_command(command::Command::Wolfram { rule: 90, width: 7, height: 3 }).is_ok());
+    assert_eq!(e.wolfram(), Some(90));
+    assert_eq!(e.render().unwrap(), "___X___\n_______\n_______\n");
+
+    assert!(e.process_command(command::Command::Step).is_ok());
+    assert_eq!(e.render().unwrap(), "___X___\n__X_X__\n_______\n");
+    assert!(e.process_command(command::Command::Jump { generations: 2 }).is_ok());
+    assert_eq!(e.render().unwrap(), "__X_X__\n_X___X_\nX_X_X_X\n");
+    assert_eq!(e.generation(), 3);
+
+    // going back restores the rows scrolled out
+    assert!(e.process_command(command::Command::Back { generations: 2 }).is_ok());
+    assert_eq!(e.render().unwrap(), "___X___\n__X_X__\n_______\n");
+    assert!(e.process_command(command::Command::Step).is_ok());
+    assert_eq!(e.render().unwrap(), "___X___\n__X_X__\n_X___X_\n");
+
+    // the seed can be edited before playing
+    assert!(e.process_command(command::Command::Wolfram { rule: 30, width: 5, height: 2 }).is_ok());
+    assert!(e.process_command(command::Command::Toggle { x: 3, y: 1 }).is_ok());
+    assert!(e.process_command(command::Command::Toggle { x: 1, y: 1 }).is_ok());
+    assert!(e.process_command(command::Command::Step).is_ok());
+    assert_eq!(e.render().unwrap(), "X____\nXX__X\n");
+
+    // a new game leaves the Wolfram mode, undoing it comes back
+    assert!(e.process_command(new_board(5, 2)).is_ok());
+    assert_eq!(e.wolfram(), None);
+    assert!(e.process_command(command::Command::Undo).is_ok());
+    assert_eq!(e.wolfram(), Some(30));
+}
+
+#[test]
+fn engine_neighbourhoods() {
+    let mut e = Engine::new();
+    let hexagonal = rule::parse_rule("B2/S34H").ok().unwrap();
+    assert!(e.process_command(command::Command::Rule { rule: hexagonal }).is_ok());
+    assert!(e.process_command(command::Command::New { width: 4, height: 3, topology: board::Topology::Bounded, storage: board::Storage::Packed }).is_ok());
+    assert!(e.process_command(command::Command::Toggle { x: 2, y: 2 }).is_ok());
+    assert!(e.process_command(command::Command::Toggle { x: 3, y: 2 }).is_ok());
+    assert_eq!(e.render().unwrap(), "  _ _ _ _\n _ X X _\n_ _ _ _\n");
+
+    // the two cells share two neighbours, which are born, and die with one neighbour each
+    assert!(e.process_command(command::Command::Step).is_ok());
+    assert_eq!(e.render().unwrap(), "  _ X _ _\n _ _ _ _\n_ _ X _\n");
+
+    assert!(e.process_command(command::Command::Universe).is_ok());
+    assert!(e.process_command(command::Command::Toggle { x: 1, y: 1 }).is_ok());
+    assert_eq!(e.process_command(command::Command::Step).err().as_deref(), Some("Only the Moore neighbourhood is supported on universes"));
+}
+
+#[test]
+fn engine_larger_than_life() {
+    let mut e = Engine::new();
+    // with radius 1 and without the middle cell it is Life
+    let life = rule::parse_rule("R1,C0,M0,S2..3,B3..3,NM").ok().unwrap();
+    assert!(e.process_command(command::Command::Rule { rule: life }).is_ok());
+    assert!(e.process_command(new_board(5, 5)).is_ok());
+    for x in 2..5 {
+        assert!(e.process_command(command::Command::Toggle { x, y: 3 }).is_ok());
+    }
+    assert!(e.process_command(command::Command::Step).is_ok());
+    assert_eq!(e.render().unwrap(), "_____\n__X__\n__X__\n__X__\n_____\n");
+
+    // a radius 2 majority vote fills the holes of a block
+    let majority = rule::parse_rule("R2,C0,M1,S13..25,B13..25,NM").ok().unwrap();
+    assert!(e.process_command(command::Command::Rule { rule: majority }).is_ok());
+    assert!(e.process_command(command::Command::New { width: 9, height: 9, topology: board::Topology::Torus, storage: board::Storage::Packed }).is_ok());
+    for x in 1..10 {
+        for y in 1..10 {
+            if x + y != 10 && !(x == 5 && y == 3) {
+                assert!(e.process_command(command::Command::Toggle { x, y }).is_ok());
+            }
+        }
+    }
+    assert_eq!(e.population(), 71);
+    assert!(e.process_command(command::Command::Step).is_ok());
+    assert_eq!(e.population(), 81);
+    assert_eq!(e.evaluated(), Some(81));
+
+    assert!(e.process_command(command::Command::Universe).is_ok());
+    assert_eq!(e.process_command(command::Command::Step).err().as_deref(), Some("Larger than Life rules are only supported on boards"));
+}
+
+#[test]
+fn engine_isotropic() {
+    let mut e = Engine::new();
+    // the centre of a blinker has two opposite neighbours (2i), its ends one side neighbour (1e)
+    // and the cells born beside it three neighbours in a row (3i)
+    let rule = rule::parse_rule("B3i/S2i").ok().unwrap();
+    assert!(e.process_command(command::Command::Rule { rule }).is_ok());
+    assert!(e.process_command(command::Command::New { width: 5, height: 5, topology: board::Topology::Bounded, storage: board::Storage::Packed }).is_ok());
+    for x in 2..5 {
+        assert!(e.process_command(command::Command::Toggle { x, y: 3 }).is_ok());
+    }
+    assert!(e.process_command(command::Command::Step).is_ok());
+    assert_eq!(e.render().unwrap(), "_____\n__X__\n__X__\n__X__\n_____\n");
+    assert!(e.process_command(command::Command::Step).is_ok());
+    assert_eq!(e.render().unwrap(), "_____\n_____\n_XXX_\n_____\n_____\n");
+
+    // without the 3i births the centre is left alone and dies
+    let rule = rule::parse_rule("B3-i/S23").ok().unwrap();
+    assert!(e.process_command(command::Command::Rule { rule }).is_ok());
+    assert!(e.process_command(command::Command::Step).is_ok());
+    assert_eq!(e.render().unwrap(), "_____\n_____\n__X__\n_____\n_____\n");
+    assert!(e.process_command(command::Command::Step).is_ok());
+    assert_eq!(e.population(), 0);
+
+    // tlife keeps the block, whose cells have three neighbours in an L (3a)
+    let rule = rule::parse_rule("B3/S2-i34q").ok().unwrap();
+    assert!(e.process_command(command::Command::Rule { rule }).is_ok());
+    for &(x, y) in [(2, 2), (3, 2), (2, 3), (3, 3)].iter() {
+        assert!(e.process_command(command::Command::Toggle { x, y }).is_ok());
+    }
+    assert!(e.process_command(command::Command::Step).is_ok());
+    assert_eq!(e.render().unwrap(), "_____\n_XX__\n_XX__\n_____\n_____\n");
+
+    // but unlike Conway's rule it kills the centre of a blinker, whose neighbours are opposite (2i)
+    for (rule, expected) in [("B3/S23", "_____\n_____\n_XXX_\n_____\n_____\n"), ("B3/S2-i34q", "_____\n_____\n_X_X_\n_____\n_____\n")] {
+        let rule = rule::parse_rule(rule).ok().unwrap();
+        assert!(e.process_command(command::Command::Rule { rule }).is_ok());
+        assert!(e.process_command(new_board(5, 5)).is_ok());
+        for y in 2..5 {
+            assert!(e.process_command(command::Command::Toggle { x: 3, y }).is_ok());
+        }
+        assert!(e.process_command(command::Command::Step).is_ok());
+        assert_eq!(e.render().unwrap(), expected);
+    }
+
+    assert!(e.process_command(command::Command::Universe).is_ok());
+    assert!(e.process_command(command::Command::Toggle { x: 1, y: 1 }).is_ok());
+    assert_eq!(e.process_command(command::Command::Step).err().as_deref(), Some("Non-totalistic rules are only supported on boards"));
+}
+
+#[test]
+fn engine_strobing() {
+    let mut e = Engine::new();
+    // with B0/S23 the background flashes: a lone cell leaves its neighbours dead while
+    // everything else is born, which is shown as a block, and then only the cell is born again
+    let rule = rule::parse_rule("B0/S23").ok().unwrap();
+    assert!(e.process_command(command::Command::Rule { rule }).is_ok());
+    assert!(e.process_command(command::Command::New { width: 5, height: 5, topology: board::Topology::Bounded, storage: board::Storage::Packed }).is_ok());
+    assert!(e.process_command(command::Command::Toggle { x: 3, y: 3 }).is_ok());
+    assert!(e.process_command(command::Command::Step).is_ok());
+    assert_eq!(e.render().unwrap(), "_____\n_XXX_\n_XXX_\n_XXX_\n_____\n");
+    assert!(e.process_command(command::Command::Step).is_ok());
+    assert_eq!(e.render().unwrap(), "_____\n_____\n__X__\n_____\n_____\n");
+    assert!(e.process_command(command::Command::Jump { generations: 3 }).is_ok());
+    assert_eq!(e.population(), 9);
+
+    // the complement of Life keeps the background alive and behaves as Life
+    let rule = rule::parse_rule("B0123478/S01234678").ok().unwrap();
+    assert!(e.process_command(command::Command::Rule { rule }).is_ok());
+    assert!(e.process_command(new_board(5, 5)).is_ok());
+    for x in 2..5 {
+        assert!(e.process_command(command::Command::Toggle { x, y: 3 }).is_ok());
+    }
+    assert!(e.process_command(command::Command::Step).is_ok());
+    assert_eq!(e.render().unwrap(), "_____\n__X__\n__X__\n__X__\n_____\n");
+
+    // universes are stepped one generation at a time
+    let rule = rule::parse_rule("B0/S23").ok().unwrap();
+    assert!(e.process_command(command::Command::Rule { rule }).is_ok());
+    assert!(e.process_command(command::Command::Universe).is_ok());
+    assert!(e.process_command(command::Command::Toggle { x: 3, y: 3 }).is_ok());
+    assert!(e.process_command(command::Command::Step).is_ok());
+    assert_eq!(e.population(), 9);
+    assert!(e.process_command(command::Command::Jump { generations: 5 }).is_ok());
+    assert_eq!(e.population(), 1);
+    assert!(e.universe().unwrap().get(2, 2));
+}
+
+#[test]
+fn engine_stochastic() {
+    let rule = rule::parse_rule("B3/S23/P0.5,0.9").ok().unwrap();
+    let run = |seed: u64| {
+        let mut e = Engine::new();
+        assert!(e.process_command(command::Command::Rule { rule }).is_ok());
+        assert!(e.process_command(command::Command::Seed { seed }).is_ok());
+        assert!(e.process_command(command::Command::New { width: 20, height: 20, topology: board::Topology::Torus, storage: board::Storage::Packed }).is_ok());
+        for x in 1..21 {
+            for y in 1..21 {
+                if (x * 7 + y * y * 3) % 5 < 2 {
+                    assert!(e.process_command(command::Command::Toggle { x, y }).is_ok());
+                }
+            }
+        }
+        assert!(e.process_command(command::Command::Jump { generations: 4 }).is_ok());
+        e.render().unwrap()
+    };
+    // the same seed gives the same run, and another seed another one
+    assert_eq!(run(1), run(1));
+    assert_ne!(run(1), run(2));
+
+    // the steps are undone as any other
+    let mut e = Engine::new();
+    assert!(e.process_command(command::Command::Rule { rule }).is_ok());
+    assert!(e.process_command(new_board(5, 5)).is_ok());
+    for x in 2..5 {
+        assert!(e.process_command(command::Command::Toggle { x, y: 3 }).is_ok());
+    }
+    let start = e.render().unwrap();
+    assert!(e.process_command(command::Command::Jump { generations: 3 }).is_ok());
+    assert!(e.process_command(command::Command::Undo).is_ok());
+    assert_eq!(e.render().unwrap(), start);
+
+    assert!(e.process_command(command::Command::Universe).is_ok());
+    assert!(e.process_command(command::Command::Toggle { x: 1, y: 1 }).is_ok());
+    assert_eq!(e.process_command(command::Command::Step).err().as_deref(), Some("Stochastic rules are only supported on boards"));
+}
+
+#[test]
+fn engine_schedule() {
+    let run = |schedule: schedule::Schedule, seed: u64| {
+        let mut e = Engine::new();
+        assert!(e.process_command(command::Command::Schedule { schedule }).is_ok());
+        assert!(e.process_command(command::Command::Seed { seed }).is_ok());
+        assert!(e.process_command(command::Command::New { width: 12, height: 12, topology: board::Topology::Torus, storage: board::Storage::Cells }).is_ok());
+        for x in 1..13 {
+            for y in 1..13 {
+                if (x * 7 + y * y * 3) % 5 < 2 {
+                    assert!(e.process_command(command::Command::Toggle { x, y }).is_ok());
+                }
+            }
+        }
+        assert!(e.process_command(command::Command::Jump { generations: 3 }).is_ok());
+        e.render().unwrap()
+    };
+    let synchronous = run(schedule::Schedule::Synchronous, 1);
+    assert_eq!(run(schedule::Schedule::Asynchronous(1.0), 1), synchronous);
+    assert_ne!(run(schedule::Schedule::Sweep, 1), synchronous);
+    assert_eq!(run(schedule::Schedule::RandomSequential, 1), run(schedule::Schedule::RandomSequential, 1));
+    assert_ne!(run(schedule::Schedule::RandomSequential, 1), run(schedule::Schedule::RandomSequential, 2));
+
+    // the generations are recorded and undone as synchronous ones
+    let mut e = Engine::new();
+    assert!(e.process_command(command::Command::Schedule { schedule: schedule::Schedule::Sweep }).is_ok());
+    assert!(e.process_command(command::Command::New { width: 5, height: 5, topology: board::Topology::Bounded, storage: board::Storage::Packed }).is_ok());
+    for x in 2..5 {
+        assert!(e.process_command(command::Command::Toggle { x, y: 3 }).is_ok());
+    }
+    let start = e.render().unwrap();
+    assert!(e.process_command(command::Command::Step).is_ok());
+    assert_eq!(e.render().unwrap(), "_____\n__XX_\n_X_X_\n_____\n_____\n");
+    assert_eq!(e.generation(), 1);
+    assert!(e.process_command(command::Command::Undo).is_ok());
+    assert_eq!(e.render().unwrap(), start);
+
+    let ltl = rule::parse_rule("R1,C0,M0,S2..3,B3..3,NM").ok().unwrap();
+    assert!(e.process_command(command::Command::Rule { rule: ltl }).is_ok());
+    assert_eq!(e.process_command(command::Command::Step).err().as_deref(), Some("Only synchronous updates are supported with B0 and Larger than Life rules"));
+
+    assert!(e.process_command(command::Command::Rule { rule: rule::Rule::conway() }).is_ok());
+    assert!(e.process_command(command::Command::Universe).is_ok());
+    assert!(e.process_command(command::Command::Toggle { x: 1, y: 1 }).is_ok());
+    assert_eq!(e.process_command(command::Command::Step).err().as_deref(), Some("Asynchronous updates are only supported on boards"));
+}
+
+#[test]
+fn engine_reversible() {
+    let mut e = Engine::new();
+    assert!(e.process_command(command::Command::Reversible { enabled: true }).is_ok());
+    assert!(e.process_command(new_board(5, 5)).is_ok());
+    for x in 2..5 {
+        assert!(e.process_command(command::Command::Toggle { x, y: 3 }).is_ok());
+    }
+    let blinker = e.render().unwrap();
+
+    // the generation before the first one is empty, so the first step is the rule's, and the
+    // next one xors the blinker with itself
+    assert!(e.process_command(command::Command::Step).is_ok());
+    assert_eq!(e.render().unwrap(), "_____\n__X__\n__X__\n__X__\n_____\n");
+    assert!(e.process_command(command::Command::Step).is_ok());
+    assert_eq!(e.population(), 0);
+    assert!(e.process_command(command::Command::Reverse { generations: 2 }).is_ok());
+    assert_eq!(e.render().unwrap(), blinker);
+    assert_eq!(e.generation(), 0);
+    assert_eq!(e.process_command(command::Command::Reverse { generations: 1 }).err().as_deref(), Some("Can not reverse before generation 0"));
+
+    // reversing is undone as any other change
+    assert!(e.process_command(command::Command::Undo).is_ok());
+    assert_eq!(e.population(), 0);
+    assert_eq!(e.generation(), 2);
+    assert!(e.process_command(command::Command::Undo).is_ok());
+    assert!(e.process_command(command::Command::Undo).is_ok());
+    assert_eq!(e.render().unwrap(), blinker);
+    assert!(e.process_command(command::Command::Redo).is_ok());
+    assert!(e.process_command(command::Command::Redo).is_ok());
+    assert!(e.process_command(command::Command::Reverse { generations: 2 }).is_ok());
+    assert_eq!(e.render().unwrap(), blinker);
+
+    let rule = rule::parse_rule("B2/S/C3").ok().unwrap();
+    assert!(e.process_command(command::Command::Rule { rule }).is_ok());
+    assert_eq!(e.process_command(command::Command::Step).err().as_deref(), Some("Reversible mode only supports two state rules"));
+
+    assert!(e.process_command(command::Command::Universe).is_ok());
+    assert_eq!(e.process_command(command::Command::Reverse { generations: 1 }).err().as_deref(), Some("Reversible mode is only supported on boards"));
+
+    assert!(e.process_command(command::Command::Reversible { enabled: false }).is_ok());
+    assert_eq!(e.process_command(command::Command::Reverse { generations: 1 }).err().as_deref(), Some("Reversible mode is not enabled"));
+}
+
+#[test]
+fn engine_reversible_mid_run() {
+    let mut e = Engine::new();
+    assert!(e.process_command(new_board(5, 5)).is_ok());
+    for x in 2..5 {
+        assert!(e.process_command(command::Command::Toggle { x, y: 3 }).is_ok());
+    }
+    assert!(e.process_command(command::Command::Jump { generations: 3 }).is_ok());
+    let vertical = e.render().unwrap();
+
+    // the mode starts at the current generation, the generation before it is taken as empty
+    assert!(e.process_command(command::Command::Reversible { enabled: true }).is_ok());
+    assert!(e.process_command(command::Command::Jump { generations: 4 }).is_ok());
+    assert_eq!(e.generation(), 7);
+    assert_eq!(e.process_command(command::Command::Reverse { generations: 5 }).err().as_deref(), Some("Can not reverse before the generation reversible mode was enabled at"));
+    assert!(e.process_command(command::Command::Reverse { generations: 4 }).is_ok());
+    assert_eq!(e.render().unwrap(), vertical);
+    assert_eq!(e.process_command(command::Command::Reverse { generations: 1 }).err().as_deref(), Some("Can not reverse before the generation reversible mode was enabled at"));
+
+    // switching the mode is undone with the previous generation it had
+    assert!(e.process_command(command::Command::Jump { generations: 2 }).is_ok());
+    let reversible = e.render().unwrap();
+    assert!(e.process_command(command::Command::Reversible { enabled: false }).is_ok());
+    assert!(e.process_command(command::Command::Reversible { enabled: true }).is_ok());
+    assert_eq!(e.process_command(command::Command::Reverse { generations: 1 }).err().as_deref(), Some("Can not reverse before the generation reversible mode was enabled at"));
+    assert!(e.process_command(command::Command::Undo).is_ok());
+    assert!(e.process_command(command::Command::Undo).is_ok());
+    assert!(e.process_command(command::Command::Reverse { generations: 2 }).is_ok());
+    assert_eq!(e.render().unwrap(), vertical);
+    assert_eq!(e.generation(), 3);
+    assert!(e.process_command(command::Command::Undo).is_ok());
+    assert_eq!(e.render().unwrap(), reversible);
+
+    // undoing the first switch goes back to the plain rule
+    for _ in 0..3 {
+        assert!(e.process_command(command::Command::Undo).is_ok());
+    }
+    assert_eq!(e.generation(), 3);
+    assert!(e.process_command(command::Command::Undo).is_ok());
+    assert_eq!(e.process_command(command::Command::Reverse { generations: 1 }).err().as_deref(), Some("Reversible mode is not enabled"));
+    assert!(e.process_command(command::Command::Step).is_ok());
+    assert_eq!(e.render().unwrap(), "_____\n_____\n_XXX_\n_____\n_____\n");
+
+    // ants do not follow the reversible rule
+    assert!(e.process_command(command::Command::Reversible { enabled: true }).is_ok());
+    assert!(e.process_command(command::Command::Ant { x: 3, y: 3, rule: ant::parse_ant_rule("RL").ok().unwrap() }).is_ok());
+    assert_eq!(e.process_command(command::Command::Step).err().as_deref(), Some("Reversible mode is not supported with ants or Wolfram rules"));
+}
+
+#[test]
+fn engine_reversible_round_trip() {
+    // N generations forward and N backward restore the board, whatever the rule and storage
+    let rules = ["B3/S23", "B36/S23", "B0/S23", "B2/S34H", "R2,C0,M1,S5..9,B6..8,NM"];
+    for (i, &rule) in rules.iter().enumerate() {
+        for &(topology, storage) in &[(board::Topology::Torus, board::Storage::Packed), (board::Topology::Bounded, board::Storage::Cells)] {
+            let mut e = Engine::new();
+            let rule = rule::parse_rule(rule).ok().unwrap();
+            assert!(e.process_command(command::Command::Rule { rule }).is_ok());
+            assert!(e.process_command(command::Command::Reversible { enabled: true }).is_ok());
+            assert!(e.process_command(command::Command::New { width: 30, height: 20, topology, storage }).is_ok());
+            for x in 1..31 {
+                for y in 1..21 {
+                    if random::mix((i * 1000 + y * 40 + x) as u64).is_multiple_of(3) {
+                        assert!(e.process_command(command::Command::Toggle { x, y }).is_ok());
+                    }
+                }
+            }
+            let start = e.render().unwrap();
+
+            assert!(e.process_command(command::Command::Jump { generations: 25 }).is_ok());
+            assert!(e.process_command(command::Command::Step).is_ok());
+            assert_ne!(e.render().unwrap(), start);
+            let (end, previous) = (e.render().unwrap(), e.previous.as_ref().unwrap().to_string());
+            for _ in 0..6 {
+                assert!(e.process_command(command::Command::Reverse { generations: 1 }).is_ok());
+            }
+
+            // every generation reversed is undone
+            for _ in 0..6 {
+                assert!(e.process_command(command::Command::Undo).is_ok());
+            }
+            assert_eq!((e.render().unwrap(), e.previous.as_ref().unwrap().to_string()), (end, previous));
+            for _ in 0..6 {
+                assert!(e.process_command(command::Command::Redo).is_ok());
+            }
+            assert!(e.process_command(command::Command::Reverse { generations: 20 }).is_ok());
+            assert_eq!(e.render().unwrap(), start);
+            assert_eq!(e.generation(), 0);
+        }
+    }
+}
+
+// Counts the bytes allocated and not freed yet by each thread, tests run on their own threads
+#[cfg(test)]
+struct CountingAllocator;
+
+#[cfg(test)]
+thread_local! {
+    static ALLOCATED: std::cell::Cell<isize> = const { std::cell::Cell::new(0) };
+}
+
+#[cfg(test)]
+unsafe impl std::alloc::GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: std::alloc::Layout) -> *mut u8 {
+        ALLOCATED.with(|allocated| allocated.set(allocated.get() + layout.size() as isize));
+        std::alloc::System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: std::alloc::Layout) {
+        ALLOCATED.with(|allocated| allocated.set(allocated.get() - layout.size() as isize));
+        std::alloc::System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: std::alloc::Layout, size: usize) -> *mut u8 {
+        ALLOCATED.with(|allocated| allocated.set(allocated.get() + size as isize - layout.size() as isize));
+        std::alloc::System.realloc(ptr, layout, size)
+    }
+}
+
+#[cfg(test)]
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+#[test]
+fn engine_step_memory() {
+    // blinkers far enough apart change the same number of cells on every generation
+    let mut e = Engine::new();
+    assert!(e.process_command(command::Command::New { width: 60, height: 60, topology: board::Topology::Torus, storage: board::Storage::Packed }).is_ok());
+    for x in (3..60).step_by(6) {
+        for y in (3..60).step_by(6) {
+            for dx in 0..3 {
+                assert!(e.toggle(x + dx, y).is_ok());
+            }
+        }
+    }
+
+    // once the history and the period detector are full, stepping does not take more memory
+    for _ in 0..history::HISTORY_SIZE + 10 {
+        assert!(e.process_command(command::Command::Step).is_ok());
+    }
+    assert_eq!(e.period(), Some(period::Period::Oscillator(2)));
+    let allocated = ALLOCATED.with(|allocated| allocated.get());
+    for _ in 0..200 {
+        assert!(e.process_command(command::Command::Step).is_ok());
+    }
+    assert_eq!(ALLOCATED.with(|allocated| allocated.get()), allocated);
+}
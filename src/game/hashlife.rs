@@ -0,0 +1,436 @@
+use std::collections::HashMap;
+use ::game::rule;
+use ::game::universe;
+
+// Deepest quadtree level allowed, so that coordinates always fit in an i64
+const MAX_LEVEL: u32 = 60;
+
+// Number of nodes after which the memoised nodes are discarded before the next jump
+const MAX_NODES: usize = 1 << 21;
+
+// Nodes are stored in an arena and referenced by index. Level 0 nodes are the dead (0) and
+// alive (1) cells, a node of level n is a square of 2^n x 2^n cells made of four level n - 1 nodes
+#[derive(Copy,Clone)]
+struct Node {
+    level: u32,
+    nw: usize,
+    ne: usize,
+    sw: usize,
+    se: usize
+}
+
+// Quadtree root placed at (x, y), the top left corner
+struct Pattern {
+    root: usize,
+    x: i64,
+    y: i64
+}
+
+// HashLife: every node is canonicalised, so identical squares are stored only once, and
+// the result of advancing the centre of a node is memoised, so repeated patterns (in space
+// and in time) are only computed once
+pub struct HashLife {
+    rule: rule::Rule,
+    nodes: Vec<Node>,
+    table: HashMap<(usize, usize, usize, usize), usize>,
+    empty: Vec<usize>,
+    results: HashMap<(usize, u32), usize>
+}
+
+impl HashLife {
+    pub fn new(rule: rule::Rule) -> HashLife {
+        let mut hashlife = HashLife {
+            rule,
+            nodes: Vec::new(),
+            table: HashMap::new(),
+            empty: Vec::new(),
+            results: HashMap::new()
+        };
+        hashlife.clear();
+        hashlife
+    }
+
+    pub fn rule(&self) -> rule::Rule {
+        self.rule
+    }
+
+    fn clear(&mut self) {
+        self.nodes = vec![
+            Node { level: 0, nw: 0, ne: 0, sw: 0, se: 0 },
+            Node { level: 0, nw: 0, ne: 0, sw: 0, se: 0 }
+        ];
+        self.table.clear();
+        self.empty = vec![0];
+        self.results.clear();
+    }
+
+    fn join(&mut self, nw: usize, ne: usize, sw: usize, se: usize) -> usize {
+        if let Some(&node) = self.table.get(&(nw, ne, sw, se)) {
+            return node;
+        }
+        let level = self.nodes[nw].level + 1;
+        self.nodes.push(Node { level, nw, ne, sw, se });
+        let node = self.nodes.len() - 1;
+        self.table.insert((nw, ne, sw, se), node);
+        node
+    }
+
+    fn empty(&mut self, level: u32) -> usize {
+        while self.empty.len() <= level as usize {
+            let e = *self.empty.last().unwrap();
+            let node = self.join(e, e, e, e);
+            self.empty.push(node);
+        }
+        self.empty[level as usize]
+    }
+
+    fn centre(&mut self, node: usize) -> usize {
+        let n = self.nodes[node];
+        let (nw, ne, sw, se) = (self.nodes[n.nw], self.nodes[n.ne], self.nodes[n.sw], self.nodes[n.se]);
+        self.join(nw.se, ne.sw, sw.ne, se.nw)
+    }
+
+    // Level 2 node: the 2 x 2 centre of the 4 x 4 square advanced one generation
+    fn base_case(&mut self, node: usize) -> usize {
+        let mut cells = [[false; 4]; 4];
+        let n = self.nodes[node];
+        for (i, &quadrant) in [n.nw, n.ne, n.sw, n.se].iter().enumerate() {
+            let q = self.nodes[quadrant];
+            for (j, &cell) in [q.nw, q.ne, q.sw, q.se].iter().enumerate() {
+                cells[(i / 2) * 2 + j / 2][(i % 2) * 2 + j % 2] = cell == 1;
+            }
+        }
+
+        let mut next = [0; 4];
+        for (k, cell) in next.iter_mut().enumerate() {
+            let (y, x) = (1 + k / 2, 1 + k % 2);
+            let mut neighbours = 0;
+            for (j, row) in cells.iter().enumerate().skip(y - 1).take(3) {
+                for (i, &alive) in row.iter().enumerate().skip(x - 1).take(3) {
+                    if !(i == x && j == y) && alive {
+                        neighbours += 1;
+                    }
+                }
+            }
+            *cell = if self.rule.next(cells[y][x], neighbours) { 1 } else { 0 };
+        }
+
+        self.join(next[0], next[1], next[2], next[3])
+    }
+
+    // Returns the centre of the node (one level down) advanced 2^step generations,
+    // step must be lower than the level of the node minus one
+    fn successor(&mut self, node: usize, step: u32) -> usize {
+        let level = self.nodes[node].level;
+
+        if node == self.empty(level) {
+            return self.empty(level - 1);
+        }
+        if let Some(&result) = self.results.get(&(node, step)) {
+            return result;
+        }
+
+        let result = if level == 2 {
+            self.base_case(node)
+        } else {
+            let n = self.nodes[node];
+            let (nw, ne, sw, se) = (self.nodes[n.nw], self.nodes[n.ne], self.nodes[n.sw], self.nodes[n.se]);
+
+            // nine overlapping squares of half the size of the node
+            let n01 = self.join(nw.ne, ne.nw, nw.se, ne.sw);
+            let n10 = self.join(nw.sw, nw.se, sw.nw, sw.ne);
+            let n11 = self.join(nw.se, ne.sw, sw.ne, se.nw);
+            let n12 = self.join(ne.sw, ne.se, se.nw, se.ne);
+            let n21 = self.join(sw.ne, se.nw, sw.se, se.sw);
+            let squares = [n.nw, n01, n.ne, n10, n11, n12, n.sw, n21, n.se];
+
+            // a full step is done in two halves, shorter steps only advance in the second half
+            let mut r = [0; 9];
+            for (r, &square) in r.iter_mut().zip(squares.iter()) {
+                *r = if step == level - 2 { self.successor(square, step - 1) } else { self.centre(square) };
+            }
+            let next_step = if step == level - 2 { step - 1 } else { step };
+
+            let nw = self.join(r[0], r[1], r[3], r[4]);
+            let ne = self.join(r[1], r[2], r[4], r[5]);
+            let sw = self.join(r[3], r[4], r[6], r[7]);
+            let se = self.join(r[4], r[5], r[7], r[8]);
+            let nw = self.successor(nw, next_step);
+            let ne = self.successor(ne, next_step);
+            let sw = self.successor(sw, next_step);
+            let se = self.successor(se, next_step);
+            self.join(nw, ne, sw, se)
+        };
+
+        self.results.insert((node, step), result);
+        result
+    }
+
+    fn build(&mut self, level: u32, x: i64, y: i64, cells: &[(i64, i64)]) -> usize {
+        if cells.is_empty() {
+            return self.empty(level);
+        }
+        if level == 0 {
+            return 1;
+        }
+
+        let half = 1i64 << (level - 1);
+        let mut quadrants: [Vec<(i64, i64)>; 4] = [Vec::new(), Vec::new(), Vec::new(), Vec::new()];
+        for &(i, j) in cells {
+            let index = if j < y + half { 0 } else { 2 } + if i < x + half { 0 } else { 1 };
+            quadrants[index].push((i, j));
+        }
+
+        let nw = self.build(level - 1, x, y, &quadrants[0]);
+        let ne = self.build(level - 1, x + half, y, &quadrants[1]);
+        let sw = self.build(level - 1, x, y + half, &quadrants[2]);
+        let se = self.build(level - 1, x + half, y + half, &quadrants[3]);
+        self.join(nw, ne, sw, se)
+    }
+
+    fn collect(&self, node: usize, x: i64, y: i64, universe: &mut universe::Universe) {
+        let n = self.nodes[node];
+        if n.level == 0 {
+            if node == 1 {
+                universe.set(x, y, true);
+            }
+        } else if self.empty.get(n.level as usize) != Some(&node) {
+            let half = 1i64 << (n.level - 1);
+            self.collect(n.nw, x, y, universe);
+            self.collect(n.ne, x + half, y, universe);
+            self.collect(n.sw, x, y + half, universe);
+            self.collect(n.se, x + half, y + half, universe);
+        }
+    }
+
+    // Wraps the pattern with empty space, keeping it centred
+    fn expand(&mut self, pattern: &mut Pattern) {
+        let level = self.nodes[pattern.root].level;
+        let n = self.nodes[pattern.root];
+        let e = self.empty(level - 1);
+
+        let nw = self.join(e, e, e, n.nw);
+        let ne = self.join(e, e, n.ne, e);
+        let sw = self.join(e, n.sw, e, e);
+        let se = self.join(n.se, e, e, e);
+        pattern.root = self.join(nw, ne, sw, se);
+        pattern.x -= 1 << (level - 1);
+        pattern.y -= 1 << (level - 1);
+    }
+
+    // Checks if all the alive cells are in the centre half of the root
+    fn is_centred(&mut self, pattern: &Pattern) -> bool {
+        let n = self.nodes[pattern.root];
+        let e = self.empty(n.level - 2);
+        let (nw, ne, sw, se) = (self.nodes[n.nw], self.nodes[n.ne], self.nodes[n.sw], self.nodes[n.se]);
+
+        [nw.nw, nw.ne, nw.sw, ne.nw, ne.ne, ne.se, sw.nw, sw.sw, sw.se, se.ne, se.sw, se.se].iter().all(|&q| q == e)
+    }
+
+    fn advance(&mut self, pattern: &mut Pattern, step: u32) -> Result<(), &'static str> {
+        // the pattern cannot grow more than 2^step cells in each direction, so after the
+        // last expansion it is guaranteed to stay inside the centre of the root
+        while self.nodes[pattern.root].level < step + 2 || !self.is_centred(pattern) {
+            if self.nodes[pattern.root].level >= MAX_LEVEL {
+                return Err("Jump too large");
+            }
+            self.expand(pattern);
+        }
+        self.expand(pattern);
+
+        let level = self.nodes[pattern.root].level;
+        pattern.root = self.successor(pattern.root, step);
+        pattern.x += 1 << (level - 2);
+        pattern.y += 1 << (level - 2);
+        Ok(())
+    }
+
+    // Advances the universe 2^step generations
+    pub fn advance_power(&mut self, universe: &universe::Universe, step: u32) -> Result<universe::Universe, &'static str> {
+        self.jump(universe, 1 << step)
+    }
+
+    // Advances the universe any number of generations, as a sequence of power of two steps
+    pub fn jump(&mut self, universe: &universe::Universe, generations: u64) -> Result<universe::Universe, &'static str> {
+        if self.rule.born(0) {
+            return Err("HashLife does not support rules with birth on 0 neighbours");
+        }
+        if self.nodes.len() > MAX_NODES {
+            self.clear();
+        }
+
+        let (x_min, y_min, x_max, y_max) = match universe.bounding_box() {
+            Some(bounding_box) => bounding_box,
+            None => return Ok(universe::Universe::new()),
+        };
+
+        let size = (x_max - x_min).max(y_max - y_min) as u64 + 1;
+        let mut level = 2;
+        while (1u64 << level) < size {
+            level += 1;
+        }
+
+        let cells: Vec<(i64, i64)> = universe.cells().iter().cloned().collect();
+        let root = self.build(level, x_min, y_min, &cells);
+        let mut pattern = Pattern { root, x: x_min, y: y_min };
+
+        for step in 0..64 {
+            if generations & (1 << step) != 0 {
+                self.advance(&mut pattern, step)?;
+            }
+        }
+
+        let mut result = universe::Universe::new();
+        self.collect(pattern.root, pattern.x, pattern.y, &mut result);
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+fn naive(universe: &universe::Universe, rule: &rule::Rule, generations: u64) -> universe::Universe {
+    let mut universe = universe.clone();
+    for _ in 0..generations {
+        universe.step(rule);
+    }
+    universe
+}
+
+#[cfg(test)]
+fn pattern(cells: &[&str]) -> universe::Universe {
+    let mut u = universe::Universe::new();
+    for (y, line) in cells.iter().enumerate() {
+        for (x, c) in line.chars().enumerate() {
+            u.set(x as i64, y as i64, c == 'X');
+        }
+    }
+    u
+}
+
+#[cfg(test)]
+fn assert_same_cells(a: &universe::Universe, b: &universe::Universe) {
+    assert_eq!(a.population(), b.population());
+    assert_eq!(a.bounding_box(), b.bounding_box());
+    assert_eq!(a.cells(), b.cells());
+}
+
+#[test]
+fn hashlife_empty() {
+    let mut h = HashLife::new(rule::Rule::conway());
+    let u = h.jump(&universe::Universe::new(), 1000).ok().unwrap();
+    assert_eq!(u.population(), 0);
+
+    let block = pattern(&["XX", "XX"]);
+    let u = h.jump(&block, 0).ok().unwrap();
+    assert_same_cells(&u, &block);
+}
+
+#[test]
+fn hashlife_power_steps() {
+    let glider = pattern(&["_X_",
+                           "__X",
+                           "XXX"]);
+    let mut h = HashLife::new(rule::Rule::conway());
+
+    for step in 0..8 {
+        let u = h.advance_power(&glider, step).ok().unwrap();
+        assert_same_cells(&u, &naive(&glider, &rule::Rule::conway(), 1 << step));
+    }
+}
+
+#[test]
+fn hashlife_glider() {
+    let glider = pattern(&["_X_",
+                           "__X",
+                           "XXX"]);
+    let mut h = HashLife::new(rule::Rule::conway());
+
+    for &generations in &[1, 2, 3, 5, 17, 100, 123] {
+        let u = h.jump(&glider, generations).ok().unwrap();
+        assert_same_cells(&u, &naive(&glider, &rule::Rule::conway(), generations));
+    }
+
+    // far away after a million generations, but still a glider
+    let u = h.jump(&glider, 1_000_000).ok().unwrap();
+    assert_eq!(u.population(), 5);
+    assert_eq!(u.bounding_box(), Some((250_000, 250_000, 250_002, 250_002)));
+}
+
+#[test]
+fn hashlife_oscillators() {
+    let pulsar = pattern(&["__XXX___XXX__",
+                           "_____________",
+                           "X____X_X____X",
+                           "X____X_X____X",
+                           "X____X_X____X",
+                           "__XXX___XXX__",
+                           "_____________",
+                           "__XXX___XXX__",
+                           "X____X_X____X",
+                           "X____X_X____X",
+                           "X____X_X____X",
+                           "_____________",
+                           "__XXX___XXX__"]);
+    let mut h = HashLife::new(rule::Rule::conway());
+
+    let u = h.jump(&pulsar, 1).ok().unwrap();
+    assert_same_cells(&u, &naive(&pulsar, &rule::Rule::conway(), 1));
+
+    // period 3
+    let u = h.jump(&pulsar, 3 * 1_000_000).ok().unwrap();
+    assert_same_cells(&u, &pulsar);
+    let u = h.jump(&pulsar, 3 * 1_000_000 + 2).ok().unwrap();
+    assert_same_cells(&u, &naive(&pulsar, &rule::Rule::conway(), 2));
+}
+
+#[test]
+fn hashlife_r_pentomino() {
+    let r_pentomino = pattern(&["_XX",
+                                "XX_",
+                                "_X_"]);
+    let mut h = HashLife::new(rule::Rule::conway());
+
+    for &generations in &[64, 200, 513] {
+        let u = h.jump(&r_pentomino, generations).ok().unwrap();
+        assert_same_cells(&u, &naive(&r_pentomino, &rule::Rule::conway(), generations));
+    }
+}
+
+#[test]
+fn hashlife_gosper_glider_gun() {
+    let gun = pattern(&["________________________X___________",
+                        "______________________X_X___________",
+                        "____________XX______XX____________XX",
+                        "___________X___X____XX____________XX",
+                        "XX________X_____X___XX______________",
+                        "XX________X___X_XX____X_X___________",
+                        "__________X_____X_______X___________",
+                        "___________X___X____________________",
+                        "____________XX______________________"]);
+    let mut h = HashLife::new(rule::Rule::conway());
+
+    let u = h.jump(&gun, 1000).ok().unwrap();
+    assert_same_cells(&u, &naive(&gun, &rule::Rule::conway(), 1000));
+
+    // a new glider every 30 generations
+    let before = h.jump(&gun, 30 * 10_000).ok().unwrap();
+    let after = h.jump(&gun, 30 * 10_001).ok().unwrap();
+    assert_eq!(after.population(), before.population() + 5);
+}
+
+#[test]
+fn hashlife_other_rules() {
+    let highlife = rule::parse_rule("B36/S23").ok().unwrap();
+    let replicator = pattern(&["__XXX",
+                               "_X__X",
+                               "X___X",
+                               "X__X_",
+                               "XXX__"]);
+    let mut h = HashLife::new(highlife);
+    assert_eq!(h.rule(), highlife);
+
+    let u = h.jump(&replicator, 96).ok().unwrap();
+    assert_same_cells(&u, &naive(&replicator, &highlife, 96));
+
+    let mut h = HashLife::new(rule::parse_rule("B0/S23").ok().unwrap());
+    assert!(h.jump(&replicator, 1).is_err());
+}
@@ -0,0 +1,270 @@
+use std::collections::VecDeque;
+use ::game::ant;
+use ::game::board;
+use ::game::rule;
+use ::game::universe;
+
+// Maximum number of changes that can be undone
+pub const HISTORY_SIZE: usize = 1000;
+
+// Maximum number of cells listed by the changes that can be undone, the oldest changes are dropped
+// past it so that the history of a large board does not take more memory than the board
+pub const HISTORY_CELLS: usize = 1 << 22;
+
+// Full state of the game, saved when a command replaces it
+pub struct Snapshot {
+    pub board: Option<board::Board>,
+    pub universe: Option<universe::Universe>,
+    pub rule: rule::Rule,
+    pub ants: Vec<ant::Ant>,
+    pub wolfram: Option<u8>,
+    pub previous: Option<board::Board>,
+    pub reversible_start: usize,
+    pub generation: usize
+}
+
+// A change of the game. Cell changes only keep the cells that were flipped, so the same
+// change is applied again to undo or redo it. Dying states are kept xor'ed with the previous
+// ones for the same reason. Rule and snapshot changes keep the other state, which is swapped
+// with the current one when they are applied, and so do ant changes with the ants.
+// Reversible changes also flip the cells of the previous generation, and go back in time
+// when they were made by reversing. Switching the reversible mode keeps the other mode, previous
+// generation and start, swapped as the snapshots
+pub enum Change {
+    Board { cells: Vec<(usize, usize)>, dying: Vec<(usize, usize, u8)>, generations: usize },
+    Reversible { cells: Vec<(usize, usize)>, previous: Vec<(usize, usize)>, generations: usize, backward: bool },
+    Ants { cells: Vec<(usize, usize)>, dying: Vec<(usize, usize, u8)>, ants: Vec<ant::Ant>, generations: usize },
+    Universe { cells: Vec<(i64, i64)>, generations: usize },
+    Rule(rule::Rule),
+    ReversibleMode { enabled: bool, previous: Option<board::Board>, start: usize },
+    Game(Box<Snapshot>)
+}
+
+impl Change {
+    // Number of generations advanced by the change, 0 for edits
+    pub fn generations(&self) -> usize {
+        match *self {
+            Change::Board { generations, .. } | Change::Ants { generations, .. } | Change::Universe { generations, .. } => generations,
+            Change::Reversible { generations, backward: false, .. } => generations,
+            _ => 0,
+        }
+    }
+
+    // Number of cells listed by the change
+    fn cells(&self) -> usize {
+        match *self {
+            Change::Board { ref cells, ref dying, .. } | Change::Ants { ref cells, ref dying, .. } => cells.len() + dying.len(),
+            Change::Reversible { ref cells, ref previous, .. } => cells.len() + previous.len(),
+            Change::Universe { ref cells, .. } => cells.len(),
+            _ => 0,
+        }
+    }
+
+    // Checks if the change did not modify any cell
+    pub fn is_empty(&self) -> bool {
+        match *self {
+            Change::Board { ref cells, ref dying, .. } | Change::Ants { ref cells, ref dying, .. } => cells.is_empty() && dying.is_empty(),
+            Change::Universe { ref cells, .. } => cells.is_empty(),
+            Change::Reversible { ref cells, ref previous, .. } => cells.is_empty() && previous.is_empty(),
+            _ => false,
+        }
+    }
+}
+
+pub struct History {
+    undo: VecDeque<Change>,
+    redo: Vec<Change>,
+    capacity: usize,
+    // cells listed by the changes that can be undone
+    cells: usize,
+    // lists of the dropped changes, emptied so that their memory is used again by the next ones
+    spare_cells: Vec<Vec<(usize, usize)>>,
+    spare_dying: Vec<Vec<(usize, usize, u8)>>
+}
+
+impl History {
+    pub fn new(capacity: usize) -> History {
+        History {
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+            capacity,
+            cells: 0,
+            spare_cells: Vec::new(),
+            spare_dying: Vec::new()
+        }
+    }
+
+    // Records a new change, discarding the changes that could be redone
+    pub fn record(&mut self, change: Change) {
+        while let Some(change) = self.redo.pop() {
+            self.recycle(change);
+        }
+        self.push_undo(change);
+    }
+
+    // Empty lists for the cells of a new change
+    pub fn cells_buffer(&mut self) -> Vec<(usize, usize)> {
+        self.spare_cells.pop().unwrap_or_default()
+    }
+
+    pub fn dying_buffer(&mut self) -> Vec<(usize, usize, u8)> {
+        self.spare_dying.pop().unwrap_or_default()
+    }
+
+    // Keeps the lists of a dropped change, two of each are enough for the changes of a generation
+    fn recycle(&mut self, change: Change) {
+        let (cells, other_cells, dying) = match change {
+            Change::Board { cells, dying, .. } | Change::Ants { cells, dying, .. } => (Some(cells), None, Some(dying)),
+            Change::Reversible { cells, previous, .. } => (Some(cells), Some(previous), None),
+            _ => (None, None, None),
+        };
+        for mut cells in cells.into_iter().chain(other_cells) {
+            if self.spare_cells.len() < 2 {
+                cells.clear();
+                self.spare_cells.push(cells);
+            }
+        }
+        if let Some(mut dying) = dying {
+            if self.spare_dying.len() < 2 {
+                dying.clear();
+                self.spare_dying.push(dying);
+            }
+        }
+    }
+
+    // Drops the oldest changes past the capacity, the last change is always kept
+    fn push_undo(&mut self, change: Change) {
+        self.cells += change.cells();
+        self.undo.push_back(change);
+        while self.undo.len() > self.capacity || (self.cells > HISTORY_CELLS && self.undo.len() > 1) {
+            let change = self.undo.pop_front().unwrap();
+            self.cells -= change.cells();
+            self.recycle(change);
+        }
+    }
+
+    // Last change that can be undone, it must be given back with undone once applied
+    pub fn undo(&mut self) -> Option<Change> {
+        let change = self.undo.pop_back();
+        self.cells -= change.as_ref().map_or(0, |change| change.cells());
+        change
+    }
+
+    pub fn undone(&mut self, change: Change) {
+        self.redo.push(change);
+    }
+
+    // Last undone change, it must be given back with redone once applied
+    pub fn redo(&mut self) -> Option<Change> {
+        self.redo.pop()
+    }
+
+    pub fn redone(&mut self, change: Change) {
+        self.push_undo(change);
+    }
+
+    pub fn last(&self) -> Option<&Change> {
+        self.undo.back()
+    }
+
+    pub fn undo_len(&self) -> usize {
+        self.undo.len()
+    }
+
+    pub fn redo_len(&self) -> usize {
+        self.redo.len()
+    }
+
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.cells = 0;
+    }
+}
+
+#[test]
+fn history_undo_redo() {
+    let mut h = History::new(HISTORY_SIZE);
+    assert!(h.undo().is_none());
+    assert!(h.redo().is_none());
+
+    h.record(Change::Board { cells: vec![(1, 1)], dying: vec![], generations: 0 });
+    h.record(Change::Board { cells: vec![(2, 2), (3, 3)], dying: vec![], generations: 1 });
+    assert_eq!(h.undo_len(), 2);
+    assert!(!h.last().unwrap().is_empty());
+    assert!(Change::Universe { cells: vec![], generations: 1 }.is_empty());
+    assert!(!Change::Rule(rule::Rule::conway()).is_empty());
+    assert_eq!(h.last().unwrap().generations(), 1);
+
+    let change = h.undo().unwrap();
+    assert_eq!(change.generations(), 1);
+    h.undone(change);
+    assert_eq!(h.undo_len(), 1);
+    assert_eq!(h.redo_len(), 1);
+
+    let change = h.redo().unwrap();
+    h.redone(change);
+    assert_eq!(h.undo_len(), 2);
+    assert_eq!(h.redo_len(), 0);
+
+    // a new change discards the redo list
+    let change = h.undo().unwrap();
+    h.undone(change);
+    h.record(Change::Rule(rule::Rule::conway()));
+    assert_eq!(h.undo_len(), 2);
+    assert_eq!(h.redo_len(), 0);
+    assert_eq!(h.last().unwrap().generations(), 0);
+
+    h.clear();
+    assert_eq!(h.undo_len(), 0);
+}
+
+#[test]
+fn history_capacity() {
+    let mut h = History::new(3);
+
+    for generation in 0..5 {
+        h.record(Change::Universe { cells: vec![(generation, 0)], generations: 1 });
+    }
+    assert_eq!(h.undo_len(), 3);
+
+    // the oldest changes are dropped
+    for generation in (2..5).rev() {
+        if let Some(Change::Universe { cells, .. }) = h.undo() {
+            assert_eq!(cells, vec![(generation, 0)]);
+        } else {
+            panic!("expected a universe change");
+        }
+    }
+    assert!(h.undo().is_none());
+}
+
+#[test]
+fn history_cells() {
+    let mut h = History::new(HISTORY_SIZE);
+    let change = |cells: usize| Change::Board { cells: vec![(0, 0); cells], dying: vec![], generations: 1 };
+
+    // the oldest changes are dropped when they list too many cells, but not the last one
+    for _ in 0..4 {
+        h.record(change(HISTORY_CELLS / 3));
+    }
+    assert_eq!(h.undo_len(), 3);
+    h.record(change(HISTORY_CELLS + 1));
+    assert_eq!(h.undo_len(), 1);
+    h.record(change(1));
+    assert_eq!(h.undo_len(), 1);
+
+    // undone changes do not count until they are redone
+    let undone = h.undo().unwrap();
+    h.undone(undone);
+    let redone = h.redo().unwrap();
+    h.redone(redone);
+    h.record(change(HISTORY_CELLS - 1));
+    assert_eq!(h.undo_len(), 2);
+
+    // the lists of the dropped changes are given back empty
+    let cells = h.cells_buffer();
+    assert!(cells.is_empty());
+    assert!(cells.capacity() >= HISTORY_CELLS / 3);
+    assert!(h.dying_buffer().is_empty());
+}
@@ -0,0 +1,311 @@
+use std::fmt;
+use std::sync::OnceLock;
+
+// Isotropic non-totalistic rules look at the arrangement of the 8 neighbours, not only at
+// their number. The 256 arrangements fall in 51 classes once rotations and reflections are
+// ignored, named in Hensel notation by the number of neighbours and a letter: "2a", "3i", "4z"...
+// A configuration has bit i set when neighbour i is alive, in the order NW, N, NE, W, E, SW, S, SE
+
+// Letters of the classes with up to 4 neighbours and a configuration of each class. The classes
+// with 5 to 8 neighbours take the letters of the complements of the ones with 3 to 0 neighbours
+const LETTERS: [&str; 5] = ["", "ce", "ceaikn", "ceaiknjqry", "ceaiknjqrytwz"];
+const CONFIGURATIONS: [&[u8]; 5] = [
+    &[0x00],
+    &[0x01, 0x02],
+    &[0x05, 0x0a, 0x03, 0x18, 0x11, 0x24],
+    &[0x25, 0x1a, 0x0b, 0x07, 0x32, 0x0d, 0x0e, 0x26, 0x19, 0x31],
+    &[0xa5, 0x5a, 0x0f, 0x1d, 0x33, 0x27, 0x3a, 0x36, 0x1b, 0x35, 0x39, 0x2e, 0x3c]
+];
+
+pub const CLASSES: usize = 51;
+
+// Positions of the neighbours in the configuration bits
+const POSITIONS: [(isize, isize); 8] = [(-1, -1), (0, -1), (1, -1), (-1, 0), (1, 0), (-1, 1), (0, 1), (1, 1)];
+
+fn letters(neighbours: usize) -> &'static str {
+    LETTERS[neighbours.min(8 - neighbours)]
+}
+
+// Index of the first class with the given number of neighbours
+fn first_class(neighbours: usize) -> usize {
+    (0..neighbours).map(|n| letters(n).len().max(1)).sum()
+}
+
+// Configuration moved by a rotation or reflection of the neighbourhood
+fn transform(configuration: u8, f: &dyn Fn(isize, isize) -> (isize, isize)) -> u8 {
+    POSITIONS.iter().enumerate()
+        .filter(|&(i, _)| configuration & (1 << i) != 0)
+        .fold(0, |acc, (_, &(x, y))| acc | 1 << POSITIONS.iter().position(|&p| p == f(x, y)).unwrap())
+}
+
+// Class of every configuration, built from one configuration of each class and its 8 symmetries
+fn classes() -> &'static [u8; 256] {
+    static CLASSES_TABLE: OnceLock<[u8; 256]> = OnceLock::new();
+    CLASSES_TABLE.get_or_init(|| {
+        let symmetries: [&dyn Fn(isize, isize) -> (isize, isize); 8] = [
+            &|x, y| (x, y), &|x, y| (-y, x), &|x, y| (-x, -y), &|x, y| (y, -x),
+            &|x, y| (-x, y), &|x, y| (x, -y), &|x, y| (y, x), &|x, y| (-y, -x)];
+        let mut table = [0; 256];
+        for (neighbours, configurations) in CONFIGURATIONS.iter().enumerate() {
+            for (i, &configuration) in configurations.iter().enumerate() {
+                for f in symmetries.iter() {
+                    table[transform(configuration, *f) as usize] = (first_class(neighbours) + i) as u8;
+                    // the complements of the classes with 4 neighbours are classes of their own
+                    if neighbours < 4 {
+                        table[transform(!configuration, *f) as usize] = (first_class(8 - neighbours) + i) as u8;
+                    }
+                }
+            }
+        }
+        table
+    })
+}
+
+pub fn class(configuration: u8) -> usize {
+    classes()[configuration as usize] as usize
+}
+
+// Classes causing a birth or a survival, bit c is set for class c
+#[derive(Copy,Clone,PartialEq,Debug)]
+pub struct Isotropic {
+    birth: u64,
+    survival: u64
+}
+
+impl Isotropic {
+    pub fn next(&self, is_alive: bool, configuration: u8) -> bool {
+        let mask = if is_alive { self.survival } else { self.birth };
+        mask & (1 << class(configuration)) != 0
+    }
+
+    // Rule giving the complement of the next state of every cell
+    pub fn complement_output(&self) -> Isotropic {
+        let all = (1 << CLASSES) - 1;
+        Isotropic { birth: !self.birth & all, survival: !self.survival & all }
+    }
+
+    // Rule applied to the complement of the cells: dead cells are born when the complemented
+    // configuration survives, and alive cells survive when it is born
+    pub fn complement_input(&self) -> Isotropic {
+        let complemented = |mask: u64| (0..=255u8)
+            .filter(|&c| mask & (1 << class(!c)) != 0)
+            .fold(0, |acc, c| acc | 1 << class(c));
+        Isotropic { birth: complemented(self.survival), survival: complemented(self.birth) }
+    }
+}
+
+// Parses a list of counts in Hensel notation: "2c3aei", "23-k". A count alone includes all its
+// classes, followed by letters only those ones, and followed by a minus all but those ones
+fn parse_classes(input: &str) -> Result<u64, &'static str> {
+    let mut mask = 0;
+    let mut chars = input.chars().peekable();
+    while let Some(c) = chars.next() {
+        let neighbours = match c.to_digit(10) {
+            Some(n) if n <= 8 => n as usize,
+            _ => return Err("Invalid rule: neighbour counts must be digits between 0 and 8"),
+        };
+        let negated = chars.peek() == Some(&'-');
+        if negated {
+            chars.next();
+        }
+        let mut classes = 0;
+        while let Some(&letter) = chars.peek() {
+            if !letter.is_ascii_alphabetic() {
+                break;
+            }
+            chars.next();
+            match letters(neighbours).find(letter.to_ascii_lowercase()) {
+                Some(i) => classes |= 1 << (first_class(neighbours) + i),
+                None => return Err("Invalid rule: unknown neighbourhood letter for the neighbour count"),
+            }
+        }
+        let all = ((1u64 << letters(neighbours).len().max(1)) - 1) << first_class(neighbours);
+        if negated && classes == 0 {
+            return Err("Invalid rule: a minus must be followed by neighbourhood letters");
+        }
+        mask |= if negated { all & !classes } else if classes == 0 { all } else { classes };
+    }
+    Ok(mask)
+}
+
+pub fn parse_isotropic(birth: &str, survival: &str) -> Result<Isotropic, &'static str> {
+    Ok(Isotropic { birth: parse_classes(birth)?, survival: parse_classes(survival)? })
+}
+
+// Writes the classes with the shortest notation for each count
+fn write_classes(f: &mut fmt::Formatter, mask: u64) -> fmt::Result {
+    for neighbours in 0..9 {
+        let letters = letters(neighbours);
+        let first = first_class(neighbours);
+        let included: String = letters.chars().enumerate().filter(|&(i, _)| mask & (1 << (first + i)) != 0).map(|(_, c)| c).collect();
+        let excluded: String = letters.chars().enumerate().filter(|&(i, _)| mask & (1 << (first + i)) == 0).map(|(_, c)| c).collect();
+        if letters.is_empty() {
+            if mask & (1 << first) != 0 {
+                write!(f, "{}", neighbours)?;
+            }
+        } else if excluded.is_empty() {
+            write!(f, "{}", neighbours)?;
+        } else if included.len() > excluded.len() {
+            write!(f, "{}-{}", neighbours, excluded)?;
+        } else if !included.is_empty() {
+            write!(f, "{}{}", neighbours, included)?;
+        }
+    }
+    Ok(())
+}
+
+impl fmt::Display for Isotropic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "B")?;
+        write_classes(f, self.birth)?;
+        write!(f, "/S")?;
+        write_classes(f, self.survival)
+    }
+}
+
+#[cfg(test)]
+fn configuration(neighbours: &[&str]) -> u8 {
+    let names = ["NW", "N", "NE", "W", "E", "SW", "S", "SE"];
+    neighbours.iter().fold(0, |acc, name| acc | 1 << names.iter().position(|n| n == name).unwrap())
+}
+
+#[cfg(test)]
+fn name(configuration: u8) -> String {
+    let neighbours = configuration.count_ones() as usize;
+    format!("{}{}", neighbours, letters(neighbours).chars().nth(class(configuration) - first_class(neighbours)).unwrap_or(' ')).trim().to_string()
+}
+
+#[test]
+fn isotropic_classes() {
+    // every class is reached and has the configurations with its number of neighbours
+    let mut sizes = [0; CLASSES];
+    for configuration in 0..256u32 {
+        let c = class(configuration as u8);
+        sizes[c] += 1;
+        assert!(c >= first_class(configuration.count_ones() as usize));
+        assert!(c < first_class(configuration.count_ones() as usize + 1));
+    }
+    assert!(sizes.iter().all(|&size| size > 0));
+    assert_eq!(first_class(9), CLASSES);
+
+    // the classes are named after the shape the cell makes with its neighbours
+    assert_eq!(name(configuration(&[])), "0");
+    assert_eq!(name(configuration(&["SE"])), "1c");
+    assert_eq!(name(configuration(&["W"])), "1e");
+    assert_eq!(name(configuration(&["N", "S"])), "2i");
+    assert_eq!(name(configuration(&["NW", "SE"])), "2n");
+    assert_eq!(name(configuration(&["N", "NE"])), "2a");
+    assert_eq!(name(configuration(&["N", "SE"])), "2k");
+    assert_eq!(name(configuration(&["SW", "S", "SE"])), "3i");
+    assert_eq!(name(configuration(&["NW", "N", "NE", "S"])), "4t");
+    assert_eq!(name(configuration(&["NW", "N", "S", "SE"])), "4z");
+    assert_eq!(name(configuration(&["N", "W", "E", "S"])), "4e");
+    assert_eq!(name(configuration(&["NW", "NE", "SW", "SE"])), "4c");
+    assert_eq!(name(configuration(&["NW", "N", "NE", "W", "E", "SW", "S"])), "7c");
+    assert_eq!(name(0xff), "8");
+
+    // the complement of a class with n neighbours has the same letter and 8 - n neighbours
+    for configuration in (0..=255u8).filter(|c| c.count_ones() != 4) {
+        let (name, complement) = (name(configuration), name(!configuration));
+        assert_eq!(name[1..], complement[1..]);
+    }
+}
+
+#[test]
+fn isotropic_letters() {
+    // one neighbourhood of every class, as drawn in the Hensel notation diagrams but turned or
+    // mirrored, so that they are not the configurations the classes are built from
+    let diagrams: [(&str, &[&str]); 37] = [
+        ("1c", &["NE"]),
+        ("1e", &["S"]),
+        ("2c", &["NE", "SE"]),
+        ("2e", &["W", "S"]),
+        ("2a", &["E", "SE"]),
+        ("2i", &["W", "E"]),
+        ("2k", &["NE", "S"]),
+        ("2n", &["NE", "SW"]),
+        ("3c", &["NW", "NE", "SE"]),
+        ("3e", &["N", "E", "S"]),
+        ("3a", &["E", "S", "SE"]),
+        ("3i", &["NE", "E", "SE"]),
+        ("3k", &["NW", "E", "S"]),
+        ("3n", &["E", "SW", "SE"]),
+        ("3j", &["E", "SW", "S"]),
+        ("3q", &["NE", "SW", "S"]),
+        ("3r", &["W", "E", "SE"]),
+        ("3y", &["NW", "NE", "S"]),
+        ("4c", &["NW", "NE", "SW", "SE"]),
+        ("4e", &["N", "W", "E", "S"]),
+        ("4a", &["E", "SW", "S", "SE"]),
+        ("4i", &["N", "NE", "S", "SE"]),
+        ("4k", &["NE", "W", "S", "SE"]),
+        ("4n", &["NE", "SW", "S", "SE"]),
+        ("4j", &["NE", "W", "E", "S"]),
+        ("4q", &["NE", "W", "SW", "S"]),
+        ("4r", &["W", "E", "S", "SE"]),
+        ("4y", &["NE", "W", "SW", "SE"]),
+        ("4t", &["NE", "W", "E", "SE"]),
+        ("4w", &["NE", "E", "SW", "S"]),
+        ("4z", &["NW", "W", "E", "SE"]),
+        ("5c", &["N", "NE", "W", "E", "S"]),
+        ("5k", &["N", "NE", "W", "SW", "SE"]),
+        ("5r", &["NW", "N", "NE", "SW", "S"]),
+        ("6a", &["NW", "N", "NE", "W", "SW", "S"]),
+        ("6i", &["NW", "N", "NE", "SW", "S", "SE"]),
+        ("7e", &["NW", "N", "NE", "W", "E", "SW", "SE"])
+    ];
+    for &(expected, neighbours) in diagrams.iter() {
+        assert_eq!(name(configuration(neighbours)), expected);
+    }
+
+    // every letter is drawn once
+    for neighbours in 1..5 {
+        let drawn: String = diagrams.iter().filter(|d| d.0.starts_with(&neighbours.to_string())).map(|d| &d.0[1..]).collect();
+        assert_eq!(drawn, letters(neighbours));
+    }
+}
+
+#[test]
+fn isotropic_parse() {
+    let rule = parse_isotropic("2c3aei", "23-k").ok().unwrap();
+    assert!(rule.next(false, configuration(&["NW", "NE"])));
+    assert!(!rule.next(false, configuration(&["N", "S"])));
+    assert!(rule.next(false, configuration(&["SW", "S", "SE"])));
+    assert!(!rule.next(false, configuration(&["N", "W", "SE"])));
+    assert!(rule.next(true, configuration(&["N", "S"])));
+    assert!(!rule.next(true, configuration(&["N", "E", "SW"])));
+    assert!(!rule.next(true, configuration(&["N", "W", "SE"])));
+    assert!(rule.next(true, configuration(&["NW", "N", "NE"])));
+    assert_eq!(rule.to_string(), "B2c3eai/S23-k");
+
+    // a count alone includes all its classes
+    let totalistic = parse_isotropic("3", "23").ok().unwrap();
+    for configuration in 0..=255u8 {
+        let n = configuration.count_ones();
+        assert_eq!(totalistic.next(false, configuration), n == 3);
+        assert_eq!(totalistic.next(true, configuration), n == 2 || n == 3);
+    }
+    assert_eq!(totalistic.to_string(), "B3/S23");
+    assert_eq!(parse_isotropic("3cekainyqjr", "2ceaikn3").ok(), Some(totalistic));
+
+    assert_eq!(parse_isotropic("2-a", "12").ok().unwrap().to_string(), "B2-a/S12");
+    assert_eq!(parse_isotropic("4ceaiknjq", "").ok().unwrap().to_string(), "B4-rytwz/S");
+    assert_eq!(parse_isotropic("1x", "").err(), Some("Invalid rule: unknown neighbourhood letter for the neighbour count"));
+    assert_eq!(parse_isotropic("8c", "").err(), Some("Invalid rule: unknown neighbourhood letter for the neighbour count"));
+    assert_eq!(parse_isotropic("2-", "").err(), Some("Invalid rule: a minus must be followed by neighbourhood letters"));
+    assert_eq!(parse_isotropic("9", "").err(), Some("Invalid rule: neighbour counts must be digits between 0 and 8"));
+}
+
+#[test]
+fn isotropic_complements() {
+    let rule = parse_isotropic("02a", "1e8").ok().unwrap();
+    let output = rule.complement_output();
+    let input = rule.complement_input();
+    for configuration in 0..=255u8 {
+        for &is_alive in &[false, true] {
+            assert_eq!(output.next(is_alive, configuration), !rule.next(is_alive, configuration));
+            assert_eq!(input.next(is_alive, configuration), rule.next(!is_alive, !configuration));
+        }
+    }
+    assert_eq!(input.to_string(), "B07e/S6a8");
+}
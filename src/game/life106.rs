@@ -0,0 +1,94 @@
+use ::game::board;
+use ::game::pattern::{ParseError, MAX_SIDE};
+
+const HEADER: &str = "#Life 1.06";
+
+// Parses a pattern in Life 1.06 format: a header line followed by the "x y" coordinates of
+// every alive cell. The board is the bounding box of the cells
+pub fn parse_life106(input: &str) -> Result<board::Board, ParseError> {
+    let mut lines = input.lines().enumerate().map(|(i, line)| (i + 1, line.trim()));
+
+    match lines.next() {
+        Some((_, line)) if line.starts_with(HEADER) => {},
+        _ => return Err(ParseError::new(1, "Invalid Life 1.06: missing header")),
+    }
+
+    let mut cells: Vec<(i64, i64)> = Vec::new();
+    let (mut x_min, mut x_max, mut y_min, mut y_max) = (i64::MAX, i64::MIN, i64::MAX, i64::MIN);
+    for (line_number, line) in lines.filter(|&(_, line)| !line.is_empty() && !line.starts_with('#')) {
+        let coordinates: Vec<Option<i64>> = line.split_whitespace().map(|token| token.parse().ok()).collect();
+        match coordinates.as_slice() {
+            [Some(x), Some(y)] => cells.push((*x, *y)),
+            _ => return Err(ParseError::new(line_number, "Invalid Life 1.06: expected two integer coordinates")),
+        }
+        let (x, y) = cells[cells.len() - 1];
+        x_min = x_min.min(x);
+        x_max = x_max.max(x);
+        y_min = y_min.min(y);
+        y_max = y_max.max(y);
+        if side(x_min, x_max).is_none() || side(y_min, y_max).is_none() {
+            return Err(ParseError::new(line_number, "Invalid Life 1.06: pattern too large"));
+        }
+    }
+
+    if cells.is_empty() {
+        return Err(ParseError::at_end(input, "Invalid Life 1.06: empty pattern"));
+    }
+
+    let mut board = board::Board::new(side(x_min, x_max).unwrap(), side(y_min, y_max).unwrap());
+    for (x, y) in cells {
+        board.set((x - x_min) as usize, (y - y_min) as usize, true).ok();
+    }
+
+    Ok(board)
+}
+
+// Number of cells from min to max, None when it is above MAX_SIDE
+fn side(min: i64, max: i64) -> Option<usize> {
+    let side = max.checked_sub(min)?.checked_add(1)? as u64;
+    if side > MAX_SIDE as u64 { None } else { Some(side as usize) }
+}
+
+pub fn to_life106(board: &board::Board) -> String {
+    let mut output = format!("{}\n", HEADER);
+    for y in 0..board.height {
+        for x in 0..board.width {
+            if board.get(x, y).ok().unwrap() {
+                output.push_str(&format!("{} {}\n", x, y));
+            }
+        }
+    }
+    output
+}
+
+#[test]
+fn life106_parse_glider() {
+    let b = parse_life106("#Life 1.06\n0 -1\n1 0\n-1 1\n0 1\n1 1\n").ok().unwrap();
+
+    assert_eq!(b.width, 3);
+    assert_eq!(b.height, 3);
+
+    let expected = "_X_\n\
+                    __X\n\
+                    XXX\n";
+
+    assert_eq!(b.to_string(), expected);
+}
+
+#[test]
+fn life106_parse_errors() {
+    assert_eq!(parse_life106("1 0\n2 1\n").err(), Some(ParseError::new(1, "Invalid Life 1.06: missing header")));
+    assert_eq!(parse_life106("").err(), Some(ParseError::new(1, "Invalid Life 1.06: missing header")));
+    assert_eq!(parse_life106("#Life 1.06\n").err(), Some(ParseError::new(1, "Invalid Life 1.06: empty pattern")));
+    assert_eq!(parse_life106("#Life 1.06\n1 0\n2 one\n").err(), Some(ParseError::new(3, "Invalid Life 1.06: expected two integer coordinates")));
+    assert_eq!(parse_life106("#Life 1.06\n1 0\n\n2 1 3\n").err(), Some(ParseError::new(4, "Invalid Life 1.06: expected two integer coordinates")));
+    assert_eq!(parse_life106("#Life 1.06\n0 0\n1000000000 1000000000\n").err(), Some(ParseError::new(3, "Invalid Life 1.06: pattern too large")));
+    assert_eq!(parse_life106("#Life 1.06\n-9223372036854775808 0\n9223372036854775807 0\n").err(), Some(ParseError::new(3, "Invalid Life 1.06: pattern too large")));
+}
+
+#[test]
+fn life106_round_trip() {
+    let b = parse_life106("#Life 1.06\n1 0\n2 1\n0 2\n1 2\n2 2\n").ok().unwrap();
+    assert_eq!(to_life106(&b), "#Life 1.06\n1 0\n2 1\n0 2\n1 2\n2 2\n");
+    assert_eq!(parse_life106(&to_life106(&b)).ok().unwrap().to_string(), b.to_string());
+}
@@ -0,0 +1,223 @@
+use std::fmt;
+use ::game::board;
+use ::game::rule;
+
+// Larger than Life: cells count the alive cells within a radius, and are born or survive when
+// the count falls in a range. The Moore neighbourhood is a square of side 2 * radius + 1 and the
+// von Neumann one a diamond. The cell itself is only counted when middle is set
+#[derive(Copy,Clone,PartialEq,Debug)]
+pub struct Range {
+    pub radius: usize,
+    pub middle: bool,
+    pub survival: (u16, u16),
+    pub birth: (u16, u16),
+    pub neighbourhood: rule::Neighbourhood
+}
+
+pub const MAX_RADIUS: usize = 10;
+
+impl Range {
+    pub fn survives(&self, count: u16) -> bool {
+        self.survival.0 <= count && count <= self.survival.1
+    }
+
+    pub fn born(&self, count: u16) -> bool {
+        self.birth.0 <= count && count <= self.birth.1
+    }
+
+    // Number of cells counted, including the middle one
+    fn cells(&self) -> usize {
+        let side = 2 * self.radius + 1;
+        match self.neighbourhood {
+            rule::Neighbourhood::VonNeumann => (side * side).div_ceil(2),
+            _ => side * side,
+        }
+    }
+}
+
+fn parse_number(input: &str) -> Result<usize, &'static str> {
+    input.parse().map_err(|_| "Invalid rule: expected R<radius>,C<states>,M<0|1>,S<min>..<max>,B<min>..<max>,N<M|N>")
+}
+
+fn parse_interval(input: &str) -> Result<(u16, u16), &'static str> {
+    let bounds: Vec<&str> = input.split("..").collect();
+    if bounds.len() != 2 {
+        return Err("Invalid rule: expected R<radius>,C<states>,M<0|1>,S<min>..<max>,B<min>..<max>,N<M|N>");
+    }
+    let (min, max) = (parse_number(bounds[0])?, parse_number(bounds[1])?);
+    if min > max || max > u16::MAX as usize {
+        return Err("Invalid rule: neighbour count ranges must go from the lowest to the highest count");
+    }
+    Ok((min as u16, max as u16))
+}
+
+// Parses the Larger than Life notation "R5,C0,M1,S34..58,B34..45,NM", returning the range and the
+// number of states. C0 and C1 mean 2 states, M and N are optional and default to M0 and NM
+pub fn parse_range(input: &str) -> Result<(Range, u8), &'static str> {
+    let mut radius = None;
+    let mut states = 2;
+    let mut middle = false;
+    let mut survival = None;
+    let mut birth = None;
+    let mut neighbourhood = rule::Neighbourhood::Moore;
+
+    for part in input.trim().split(',') {
+        let mut chars = part.chars();
+        let key = chars.next().map(|c| c.to_ascii_uppercase());
+        let value = chars.as_str();
+        match key {
+            Some('R') => radius = Some(parse_number(value)?),
+            Some('C') => states = match parse_number(value)? {
+                0 | 1 => 2,
+                states if states <= 255 => states as u8,
+                _ => return Err("Invalid rule: the number of states must be between 2 and 255"),
+            },
+            Some('M') => middle = match value {
+                "0" => false,
+                "1" => true,
+                _ => return Err("Invalid rule: the middle cell must be M0 or M1"),
+            },
+            Some('S') => survival = Some(parse_interval(value)?),
+            Some('B') => birth = Some(parse_interval(value)?),
+            Some('N') => neighbourhood = match value {
+                "M" | "m" => rule::Neighbourhood::Moore,
+                "N" | "n" => rule::Neighbourhood::VonNeumann,
+                _ => return Err("Invalid rule: the neighbourhood must be NM or NN"),
+            },
+            _ => return Err("Invalid rule: expected R<radius>,C<states>,M<0|1>,S<min>..<max>,B<min>..<max>,N<M|N>"),
+        }
+    }
+
+    let range = match (radius, survival, birth) {
+        (Some(radius), Some(survival), Some(birth)) => Range { radius, middle, survival, birth, neighbourhood },
+        _ => return Err("Invalid rule: expected R<radius>,C<states>,M<0|1>,S<min>..<max>,B<min>..<max>,N<M|N>"),
+    };
+    if range.radius == 0 || range.radius > MAX_RADIUS {
+        return Err("Invalid rule: the radius must be between 1 and 10");
+    }
+    if range.survival.1 as usize > range.cells() || range.birth.1 as usize > range.cells() {
+        return Err("Invalid rule: neighbour counts can not exceed the cells in the radius");
+    }
+    Ok((range, states))
+}
+
+// Writes the rule in Larger than Life notation with the given number of states
+pub fn write_range(f: &mut fmt::Formatter, range: &Range, states: u8) -> fmt::Result {
+    write!(f, "R{},C{},M{},S{}..{},B{}..{},N{}", range.radius, if states > 2 { states } else { 0 },
+        range.middle as u8, range.survival.0, range.survival.1, range.birth.0, range.birth.1,
+        if range.neighbourhood == rule::Neighbourhood::VonNeumann { 'N' } else { 'M' })
+}
+
+// Alive cells within the range of every cell of the board, row-major. The board is padded with
+// radius cells on every side, copied from the other edge when it wraps and dead otherwise, and
+// summed so the count of any rectangle takes four lookups. Squares take one rectangle and
+// diamonds one row per line
+pub fn counts(board: &board::Board, range: &Range) -> Vec<u16> {
+    let r = range.radius;
+    let (width, height) = (board.width + 2 * r, board.height + 2 * r);
+
+    // sums[y][x] holds the alive cells of the padded board above and to the left of (x, y)
+    let mut sums = vec![0u32; (width + 1) * (height + 1)];
+    for y in 0..height {
+        let mut row = 0;
+        for x in 0..width {
+            let alive = match board.neighbour(0, 0, x as isize - r as isize, y as isize - r as isize) {
+                Some((i, j)) => board.get(i, j).ok().unwrap(),
+                None => false,
+            };
+            row += alive as u32;
+            sums[(y + 1) * (width + 1) + x + 1] = sums[y * (width + 1) + x + 1] + row;
+        }
+    }
+    // alive cells of the padded rectangle from (x0, y0) to (x1, y1), both excluded
+    let rectangle = |x0: usize, y0: usize, x1: usize, y1: usize| {
+        sums[y1 * (width + 1) + x1] + sums[y0 * (width + 1) + x0] - sums[y0 * (width + 1) + x1] - sums[y1 * (width + 1) + x0]
+    };
+
+    let mut counts = Vec::with_capacity(board.width * board.height);
+    for y in 0..board.height {
+        for x in 0..board.width {
+            // (x, y) of the board is (x + r, y + r) of the padded one
+            let mut count = if range.neighbourhood == rule::Neighbourhood::VonNeumann {
+                (0..2 * r + 1).map(|dy| {
+                    let half = r - (dy as isize - r as isize).unsigned_abs();
+                    rectangle(x + r - half, y + dy, x + r + half + 1, y + dy + 1)
+                }).sum()
+            } else {
+                rectangle(x, y, x + 2 * r + 1, y + 2 * r + 1)
+            };
+            if !range.middle && board.get(x, y).ok().unwrap() {
+                count -= 1;
+            }
+            counts.push(count as u16);
+        }
+    }
+    counts
+}
+
+#[cfg(test)]
+fn direct_counts(board: &board::Board, range: &Range) -> Vec<u16> {
+    let r = range.radius as isize;
+    let mut counts = Vec::new();
+    for y in 0..board.height {
+        for x in 0..board.width {
+            let mut count = 0;
+            for dy in -r..r + 1 {
+                for dx in -r..r + 1 {
+                    let inside = range.neighbourhood != rule::Neighbourhood::VonNeumann || dx.abs() + dy.abs() <= r;
+                    if !inside || (dx == 0 && dy == 0 && !range.middle) {
+                        continue;
+                    }
+                    if let Some((i, j)) = board.neighbour(x, y, dx, dy) {
+                        count += board.get(i, j).ok().unwrap() as u16;
+                    }
+                }
+            }
+            counts.push(count);
+        }
+    }
+    counts
+}
+
+#[test]
+fn ltl_parse() {
+    let (range, states) = parse_range("R5,C0,M1,S34..58,B34..45,NM").ok().unwrap();
+    assert_eq!(range, Range { radius: 5, middle: true, survival: (34, 58), birth: (34, 45), neighbourhood: rule::Neighbourhood::Moore });
+    assert_eq!(states, 2);
+    assert!(range.survives(34) && range.survives(58) && !range.survives(59));
+    assert!(range.born(40) && !range.born(33));
+
+    let (range, states) = parse_range("r2,c3,s2..4,b3..3,nn").ok().unwrap();
+    assert_eq!(range, Range { radius: 2, middle: false, survival: (2, 4), birth: (3, 3), neighbourhood: rule::Neighbourhood::VonNeumann });
+    assert_eq!(states, 3);
+
+    assert_eq!(parse_range("R11,C0,M1,S1..2,B1..2,NM").err(), Some("Invalid rule: the radius must be between 1 and 10"));
+    assert_eq!(parse_range("R1,C0,M1,S1..10,B1..2,NM").err(), Some("Invalid rule: neighbour counts can not exceed the cells in the radius"));
+    assert_eq!(parse_range("R1,C0,M1,S3..2,B1..2,NM").err(), Some("Invalid rule: neighbour count ranges must go from the lowest to the highest count"));
+    assert_eq!(parse_range("R1,C0,M2,S1..2,B1..2,NM").err(), Some("Invalid rule: the middle cell must be M0 or M1"));
+    assert_eq!(parse_range("R1,C0,M1,S1..2,B1..2,NC").err(), Some("Invalid rule: the neighbourhood must be NM or NN"));
+    assert!(parse_range("R1,C0,M1,S1..2").is_err());
+    assert!(parse_range("R1,C0,M1,S1-2,B3..3").is_err());
+}
+
+#[test]
+fn ltl_counts() {
+    let mut b = board::Board::new(25, 25);
+    for &(x, y) in [(12, 12), (10, 12), (12, 7), (17, 17), (20, 12), (0, 0), (24, 24)].iter() {
+        b.set(x, y, true).ok();
+    }
+
+    let (range, _) = parse_range("R5,C0,M1,S34..58,B34..45,NM").ok().unwrap();
+    let result = counts(&b, &range);
+    assert_eq!(result[12 * 25 + 12], 4);
+    assert_eq!(result[12 * 25 + 7], 3);
+    assert_eq!(result, direct_counts(&b, &range));
+
+    for &topology in &[board::Topology::Bounded, board::Topology::Torus, board::Topology::Cylinder] {
+        let b = board::random_board(30, 20, topology, board::Storage::Packed, 7);
+        for rule in ["R1,C0,M0,S2..3,B3..3,NM", "R3,C0,M1,S9..20,B9..14,NN", "R10,C0,M0,S1..1,B1..1,NM"].iter() {
+            let (range, _) = parse_range(rule).ok().unwrap();
+            assert_eq!(counts(&b, &range), direct_counts(&b, &range));
+        }
+    }
+}
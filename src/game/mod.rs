@@ -1,3 +1,18 @@
+pub mod ant;
 pub mod board;
 pub mod command;
 pub mod engine;
+pub mod hashlife;
+pub mod history;
+pub mod isotropic;
+pub mod life106;
+pub mod ltl;
+pub mod pattern;
+pub mod period;
+pub mod plaintext;
+pub mod random;
+pub mod rle;
+pub mod rule;
+pub mod schedule;
+pub mod universe;
+pub mod wolfram;
@@ -0,0 +1,136 @@
+use std::fmt;
+use ::game::board;
+use ::game::life106;
+use ::game::plaintext;
+use ::game::rle;
+use ::game::rule;
+
+// Largest width or height of a pattern board, bigger patterns are rejected instead of allocated
+pub const MAX_SIDE: usize = 8192;
+
+// Error found while reading a pattern file, lines are numbered from 1
+#[derive(PartialEq,Debug)]
+pub struct ParseError {
+    pub line: usize,
+    pub message: &'static str
+}
+
+impl ParseError {
+    pub fn new(line: usize, message: &'static str) -> ParseError {
+        ParseError {
+            line,
+            message
+        }
+    }
+
+    // Error found after reading the whole input, reported on its last line
+    pub fn at_end(input: &str, message: &'static str) -> ParseError {
+        ParseError::new(input.lines().count().max(1), message)
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Line {}: {}", self.line, self.message)
+    }
+}
+
+#[derive(Copy,Clone,PartialEq,Debug)]
+pub enum Format {
+    Rle,
+    Plaintext,
+    Life106
+}
+
+// Guesses the format from the first lines of the pattern
+pub fn detect_format(input: &str) -> Format {
+    for line in input.lines().map(|line| line.trim()).filter(|line| !line.is_empty()) {
+        if line.starts_with("#Life 1.06") {
+            return Format::Life106;
+        } else if line.starts_with('!') {
+            return Format::Plaintext;
+        } else if !line.starts_with('#') {
+            return if line.starts_with('x') && line.contains('=') { Format::Rle } else { Format::Plaintext };
+        }
+    }
+    Format::Rle
+}
+
+// Chooses the format from the file extension, RLE by default
+pub fn format_from_path(path: &str) -> Format {
+    let path = path.to_lowercase();
+    if path.ends_with(".cells") || path.ends_with(".txt") {
+        Format::Plaintext
+    } else if path.ends_with(".lif") || path.ends_with(".life") {
+        Format::Life106
+    } else {
+        Format::Rle
+    }
+}
+
+pub fn parse_pattern(input: &str) -> Result<(board::Board, Option<rule::Rule>), ParseError> {
+    match detect_format(input) {
+        Format::Rle => rle::parse_rle(input),
+        Format::Plaintext => plaintext::parse_plaintext(input).map(|board| (board, None)),
+        Format::Life106 => life106::parse_life106(input).map(|board| (board, None)),
+    }
+}
+
+pub fn write_pattern(board: &board::Board, rule: &rule::Rule, format: Format) -> String {
+    match format {
+        Format::Rle => rle::to_rle(board, rule),
+        Format::Plaintext => plaintext::to_plaintext(board),
+        Format::Life106 => life106::to_life106(board),
+    }
+}
+
+#[test]
+fn pattern_parse_error() {
+    let e = ParseError::new(3, "Invalid RLE: unexpected character");
+    assert_eq!(e.to_string(), "Line 3: Invalid RLE: unexpected character");
+
+    assert_eq!(ParseError::at_end("", "Empty").line, 1);
+    assert_eq!(ParseError::at_end("a\nb\nc\n", "Empty").line, 3);
+}
+
+#[test]
+fn pattern_detect_format() {
+    assert_eq!(detect_format("#N Glider\nx = 3, y = 3\nbo$2bo$3o!"), Format::Rle);
+    assert_eq!(detect_format("x = 3, y = 3\nbo$2bo$3o!"), Format::Rle);
+    assert_eq!(detect_format("!Name: Glider\n.O.\n..O\nOOO\n"), Format::Plaintext);
+    assert_eq!(detect_format(".O.\n..O\nOOO\n"), Format::Plaintext);
+    assert_eq!(detect_format("#Life 1.06\n1 0\n2 1\n0 2\n1 2\n2 2\n"), Format::Life106);
+}
+
+#[test]
+fn pattern_format_from_path() {
+    assert_eq!(format_from_path("glider.rle"), Format::Rle);
+    assert_eq!(format_from_path("glider"), Format::Rle);
+    assert_eq!(format_from_path("glider.cells"), Format::Plaintext);
+    assert_eq!(format_from_path("GLIDER.CELLS"), Format::Plaintext);
+    assert_eq!(format_from_path("glider.lif"), Format::Life106);
+    assert_eq!(format_from_path("glider.life"), Format::Life106);
+}
+
+#[test]
+fn pattern_parse_any_format() {
+    let expected = "_X_\n\
+                    __X\n\
+                    XXX\n";
+
+    for input in &["x = 3, y = 3, rule = B36/S23\nbo$2bo$3o!",
+                   "!Name: Glider\n.O.\n..O\nOOO\n",
+                   "#Life 1.06\n1 0\n2 1\n0 2\n1 2\n2 2\n"] {
+        let (b, _) = parse_pattern(input).ok().unwrap();
+        assert_eq!(b.to_string(), expected);
+
+        for &format in &[Format::Rle, Format::Plaintext, Format::Life106] {
+            let written = write_pattern(&b, &rule::Rule::conway(), format);
+            assert_eq!(detect_format(&written), format);
+            assert_eq!(parse_pattern(&written).ok().unwrap().0.to_string(), expected);
+        }
+    }
+
+    assert_eq!(parse_pattern("x = 3, y = 3, rule = B36/S23\nbo$2bo$3o!").ok().unwrap().1, rule::parse_rule("B36/S23").ok());
+    assert_eq!(parse_pattern(".O.\n..O\nOOO\n").ok().unwrap().1, None);
+}
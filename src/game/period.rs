@@ -0,0 +1,307 @@
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::fmt;
+use std::mem;
+use ::game::random;
+
+// Number of recent generations remembered, which is also the longest period that can be detected
+pub const PERIOD_HISTORY: usize = 1000;
+
+#[derive(Copy,Clone,PartialEq,Debug)]
+pub enum Period {
+    StillLife,
+    Oscillator(usize),
+    Spaceship { dx: i64, dy: i64, period: usize }
+}
+
+impl Period {
+    fn components(&self) -> (i64, i64, usize) {
+        match *self {
+            Period::StillLife => (0, 0, 1),
+            Period::Oscillator(period) => (0, 0, period),
+            Period::Spaceship { dx, dy, period } => (dx, dy, period),
+        }
+    }
+
+    // Checks if the other period is this one repeated a whole number of times
+    fn divides(&self, other: Period) -> bool {
+        let (dx, dy, period) = self.components();
+        let (other_dx, other_dy, other_period) = other.components();
+        let times = other_period / period;
+        other_period % period == 0 && dx * times as i64 == other_dx && dy * times as i64 == other_dy
+    }
+}
+
+impl fmt::Display for Period {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Period::StillLife => write!(f, "still life"),
+            Period::Oscillator(period) => write!(f, "oscillator of period {}", period),
+            Period::Spaceship { dx, dy, period } => write!(f, "spaceship with displacement ({}, {}) over period {}", dx, dy, period),
+        }
+    }
+}
+
+// Alive cells relative to the top left corner of their bounding box, row by row
+type Shape = Vec<(i64, i64)>;
+
+// Most cells kept in the recent shapes. Repetitions are only reported once the shapes are
+// compared, so a pattern too large to keep its shape for a whole period is not detected
+pub const SHAPE_CELLS: usize = 1 << 16;
+
+// Hash of the shape of a generation, with the top left corner of its bounding box and its number
+// of cells, computed without listing the cells
+#[derive(Copy,Clone,PartialEq,Debug)]
+pub struct Signature {
+    pub hash: u64,
+    pub x: i64,
+    pub y: i64,
+    pub population: usize
+}
+
+// Hash of 64 cells of a row of a shape: bit i of bits is the cell 64 * chunk + i of row y, both
+// relative to the corner. The hash of a shape is the sum of the hashes of its chunks, so it can be
+// added up in any order
+pub fn chunk_hash(y: usize, chunk: usize, bits: u64) -> u64 {
+    if bits == 0 { 0 } else { random::mix(bits ^ random::mix(((y as u64) << 32) ^ chunk as u64)) }
+}
+
+// Signature of a list of cells sorted row by row
+fn signature(cells: &[(i64, i64)]) -> Option<Signature> {
+    let x_min = cells.iter().map(|&(x, _)| x).min()?;
+    let y_min = cells[0].1;
+
+    let mut hash: u64 = 0;
+    let mut chunk = (0, 0, 0);
+    for &(x, y) in cells {
+        let (row, column) = ((y - y_min) as usize, (x - x_min) as usize);
+        if (row, column / 64) != (chunk.0, chunk.1) {
+            hash = hash.wrapping_add(chunk_hash(chunk.0, chunk.1, chunk.2));
+            chunk = (row, column / 64, 0);
+        }
+        chunk.2 |= 1 << (column % 64);
+    }
+    hash = hash.wrapping_add(chunk_hash(chunk.0, chunk.1, chunk.2));
+
+    Some(Signature { hash, x: x_min, y: y_min, population: cells.len() })
+}
+
+// Remembers the signature of the recent generations, indexed by their hash. A shape seen before in
+// the same place is an oscillator, in another place a spaceship. Equal hashes are confirmed with the
+// shapes of the last generations, as many as fit in SHAPE_CELLS
+pub struct PeriodDetector {
+    seen: HashMap<u64, (usize, i64, i64, usize)>,
+    order: VecDeque<(u64, usize)>,
+    shapes: VecDeque<(usize, Shape)>,
+    shape_cells: usize,
+    // shape dropped from the recent ones, its memory is used again for the next one
+    spare: Shape,
+    period: Option<Period>
+}
+
+impl PeriodDetector {
+    pub fn new() -> PeriodDetector {
+        PeriodDetector {
+            seen: HashMap::new(),
+            order: VecDeque::new(),
+            shapes: VecDeque::new(),
+            shape_cells: 0,
+            spare: Vec::new(),
+            period: None
+        }
+    }
+
+    // Last period detected, None while the pattern has not repeated yet
+    pub fn period(&self) -> Option<Period> {
+        self.period
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.order.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.seen.clear();
+        self.order.clear();
+        self.shapes.clear();
+        self.shape_cells = 0;
+        self.period = None;
+    }
+
+    // Records the alive cells of a generation, given in any order
+    pub fn record_cells<I: Iterator<Item = (i64, i64)>>(&mut self, generation: usize, cells: I) -> Option<Period> {
+        let mut cells: Vec<(i64, i64)> = cells.collect();
+        cells.sort_by_key(|&(x, y)| (y, x));
+        self.record(generation, signature(&cells), |shape| shape.extend_from_slice(&cells))
+    }
+
+    // Records the signature of a generation, None when it has no cells. The shape is only asked
+    // for when it fits in the recent ones: cells must add its alive cells row by row. Generations
+    // do not need to be consecutive, the period is the difference between the generation numbers
+    pub fn record<F: FnOnce(&mut Shape)>(&mut self, generation: usize, signature: Option<Signature>, cells: F) -> Option<Period> {
+        let signature = match signature {
+            Some(signature) => signature,
+            None => {
+                self.period = None;
+                return None;
+            },
+        };
+
+        let shape = if signature.population <= SHAPE_CELLS {
+            let mut shape = mem::take(&mut self.spare);
+            shape.clear();
+            cells(&mut shape);
+            for cell in shape.iter_mut() {
+                *cell = (cell.0 - signature.x, cell.1 - signature.y);
+            }
+            Some(shape)
+        } else {
+            None
+        };
+
+        // equal hashes are only a repetition when the shapes are equal too
+        let period = match self.seen.get(&signature.hash) {
+            Some(&(seen, x, y, population)) if seen < generation && population == signature.population && self.same_shape(seen, &shape) => {
+                let (dx, dy) = (signature.x - x, signature.y - y);
+                Some(match (dx, dy, generation - seen) {
+                    (0, 0, 1) => Period::StillLife,
+                    (0, 0, period) => Period::Oscillator(period),
+                    (dx, dy, period) => Period::Spaceship { dx, dy, period },
+                })
+            },
+            _ => None,
+        };
+        // after a jump the repetition found can be a multiple of the known period
+        self.period = match (self.period, period) {
+            (Some(known), Some(found)) if known.divides(found) => Some(known),
+            _ => period,
+        };
+
+        self.seen.insert(signature.hash, (generation, signature.x, signature.y, signature.population));
+        self.order.push_back((signature.hash, generation));
+        while self.order.len() > PERIOD_HISTORY {
+            let (hash, generation) = self.order.pop_front().unwrap();
+            if self.seen.get(&hash).is_some_and(|&(seen, _, _, _)| seen == generation) {
+                self.seen.remove(&hash);
+            }
+        }
+
+        if let Some(shape) = shape {
+            self.shape_cells += shape.len();
+            self.shapes.push_back((generation, shape));
+        }
+        while self.shape_cells > SHAPE_CELLS || self.shapes.len() > PERIOD_HISTORY {
+            let (_, shape) = self.shapes.pop_front().unwrap();
+            self.shape_cells -= shape.len();
+            self.spare = shape;
+        }
+
+        self.period
+    }
+
+    // Checks if the shape of the generation is still kept and equal to the given one
+    fn same_shape(&self, generation: usize, shape: &Option<Shape>) -> bool {
+        match *shape {
+            Some(ref shape) => self.shapes.iter().any(|&(seen, ref seen_shape)| seen == generation && seen_shape == shape),
+            None => false,
+        }
+    }
+}
+
+impl Default for PeriodDetector {
+    fn default() -> PeriodDetector {
+        PeriodDetector::new()
+    }
+}
+
+#[test]
+fn period_print() {
+    assert_eq!(Period::StillLife.to_string(), "still life");
+    assert_eq!(Period::Oscillator(3).to_string(), "oscillator of period 3");
+    assert_eq!(Period::Spaceship { dx: 1, dy: -1, period: 4 }.to_string(), "spaceship with displacement (1, -1) over period 4");
+}
+
+#[test]
+fn period_detector() {
+    let mut d = PeriodDetector::new();
+    assert!(d.is_empty());
+
+    assert_eq!(d.record_cells(0, vec![(0, 0), (1, 0)].into_iter()), None);
+    assert_eq!(d.record_cells(1, vec![(5, 5)].into_iter()), None);
+    assert_eq!(d.record_cells(2, vec![(0, 0), (1, 0)].into_iter()), Some(Period::Oscillator(2)));
+    assert_eq!(d.record_cells(3, vec![(0, 0), (1, 0)].into_iter()), Some(Period::StillLife));
+    assert_eq!(d.record_cells(5, vec![(3, -2), (4, -2)].into_iter()), Some(Period::Spaceship { dx: 3, dy: -2, period: 2 }));
+    assert_eq!(d.period(), Some(Period::Spaceship { dx: 3, dy: -2, period: 2 }));
+
+    // a multiple of the known period keeps it
+    assert_eq!(d.record_cells(9, vec![(9, -6), (10, -6)].into_iter()), Some(Period::Spaceship { dx: 3, dy: -2, period: 2 }));
+    assert_eq!(d.record_cells(10, vec![(9, -6), (10, -6)].into_iter()), Some(Period::StillLife));
+
+    // empty generations have no period
+    assert_eq!(d.record_cells(11, vec![].into_iter()), None);
+
+    d.clear();
+    assert!(d.is_empty());
+    assert_eq!(d.period(), None);
+    assert_eq!(d.record_cells(12, vec![(0, 0), (1, 0)].into_iter()), None);
+}
+
+#[test]
+fn period_detector_history() {
+    let mut d = PeriodDetector::new();
+
+    // a period longer than the history is not detected
+    d.record_cells(0, vec![(0, 0)].into_iter());
+    for generation in 1..PERIOD_HISTORY + 1 {
+        assert_eq!(d.record_cells(generation, vec![(0, 0), (generation as i64, 0)].into_iter()), None);
+    }
+    assert_eq!(d.record_cells(PERIOD_HISTORY + 1, vec![(0, 0)].into_iter()), None);
+    assert_eq!(d.record_cells(PERIOD_HISTORY + 2, vec![(1, 1)].into_iter()), Some(Period::Spaceship { dx: 1, dy: 1, period: 1 }));
+}
+
+#[test]
+fn period_detector_hash_collision() {
+    let mut d = PeriodDetector::new();
+    let blinker = vec![(0, 0), (1, 0), (2, 0)];
+
+    // another shape recorded with the hash of the blinker is not a repetition
+    let hash = signature(&blinker).unwrap().hash;
+    d.record(0, Some(Signature { hash, x: 0, y: 0, population: 3 }), |shape| shape.extend_from_slice(&[(0, 0), (0, 1), (0, 2)]));
+    assert_eq!(d.record_cells(1, blinker.clone().into_iter()), None);
+
+    assert_eq!(d.record_cells(2, blinker.into_iter()), Some(Period::StillLife));
+}
+
+#[test]
+fn period_detector_signature() {
+    // the hash does not depend on the position or on the order of the cells
+    let glider = [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)];
+    let a = signature(&glider).unwrap();
+    let b = signature(&glider.iter().map(|&(x, y)| (x - 70, y + 3)).collect::<Vec<_>>()).unwrap();
+    assert_eq!((a.hash, a.x, a.y, a.population), (b.hash, 0, 0, 5));
+    assert_eq!((b.x, b.y), (-70, 3));
+    assert_ne!(signature(&[(0, 0), (1, 0)]).unwrap().hash, signature(&[(0, 0), (0, 1)]).unwrap().hash);
+    assert_ne!(signature(&[(0, 0), (64, 0)]).unwrap().hash, signature(&[(0, 0), (0, 1)]).unwrap().hash);
+    assert_eq!(signature(&[]), None);
+}
+
+#[test]
+fn period_detector_large_shapes() {
+    let mut d = PeriodDetector::new();
+
+    // only the shapes that fit in SHAPE_CELLS are kept, not one per generation
+    let row = |generation: usize, length: usize| (0..length as i64).map(move |x| (x * 2, generation as i64));
+    for generation in 0..PERIOD_HISTORY + 10 {
+        d.record_cells(generation, row(generation, 5000 + generation % 2));
+    }
+    assert!(d.shape_cells <= SHAPE_CELLS);
+    assert!(d.shapes.len() <= SHAPE_CELLS / 5000);
+    assert_eq!(d.order.len(), PERIOD_HISTORY);
+    assert_eq!(d.period(), Some(Period::Spaceship { dx: 0, dy: 2, period: 2 }));
+
+    // larger ones are never kept, so their repetitions can not be confirmed
+    for generation in 0..4 {
+        assert_eq!(d.record_cells(PERIOD_HISTORY + 10 + generation, row(0, SHAPE_CELLS + 1)), None);
+    }
+    assert!(d.shapes.len() <= SHAPE_CELLS / 5000);
+}
@@ -0,0 +1,92 @@
+use ::game::board;
+use ::game::pattern::{ParseError, MAX_SIDE};
+
+// Parses a pattern in plaintext (.cells) format: '!' starts a comment line, every other line
+// is a row where 'O' is an alive cell and '.' a dead one. Short rows are padded with dead cells
+pub fn parse_plaintext(input: &str) -> Result<board::Board, ParseError> {
+    let mut rows: Vec<(usize, &str)> = input.lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim_end()))
+        .filter(|&(_, line)| !line.starts_with('!'))
+        .collect();
+
+    while rows.last().is_some_and(|&(_, line)| line.is_empty()) {
+        rows.pop();
+    }
+
+    // the first row that is too long, or the first one below the maximum height
+    let too_large = rows.iter().find(|&&(_, line)| line.chars().count() > MAX_SIDE).or(rows.get(MAX_SIDE));
+    if let Some(&(line_number, _)) = too_large {
+        return Err(ParseError::new(line_number, "Invalid plaintext: pattern too large"));
+    }
+
+    let width = rows.iter().map(|&(_, line)| line.chars().count()).max().unwrap_or(0);
+    if width == 0 {
+        return Err(ParseError::at_end(input, "Invalid plaintext: empty pattern"));
+    }
+
+    let mut board = board::Board::new(width, rows.len());
+    for (y, &(line_number, line)) in rows.iter().enumerate() {
+        for (x, c) in line.chars().enumerate() {
+            match c {
+                'O' | 'o' | '*' => { board.set(x, y, true).ok(); },
+                '.' => {},
+                _ => return Err(ParseError::new(line_number, "Invalid plaintext: expected '.' or 'O'")),
+            }
+        }
+    }
+
+    Ok(board)
+}
+
+// Dying cells are saved as dead
+pub fn to_plaintext(board: &board::Board) -> String {
+    board.to_string().chars().map(|c| match c { 'X' => 'O', '\n' => '\n', _ => '.' }).collect()
+}
+
+#[test]
+fn plaintext_parse_glider() {
+    let b = parse_plaintext("!Name: Glider\n!\n.O.\n..O\nOOO\n").ok().unwrap();
+
+    assert_eq!(b.width, 3);
+    assert_eq!(b.height, 3);
+
+    let expected = "_X_\n\
+                    __X\n\
+                    XXX\n";
+
+    assert_eq!(b.to_string(), expected);
+}
+
+#[test]
+fn plaintext_parse_short_and_blank_rows() {
+    let b = parse_plaintext("!Name: Beacon-ish\nOO\nO\n\n...O\n\n\n").ok().unwrap();
+
+    let expected = "XX__\n\
+                    X___\n\
+                    ____\n\
+                    ___X\n";
+
+    assert_eq!(b.to_string(), expected);
+}
+
+#[test]
+fn plaintext_parse_errors() {
+    assert_eq!(parse_plaintext("!Name: Nothing\n").err(), Some(ParseError::new(1, "Invalid plaintext: empty pattern")));
+    assert_eq!(parse_plaintext("").err(), Some(ParseError::new(1, "Invalid plaintext: empty pattern")));
+    assert_eq!(parse_plaintext("!Name: Glider\n.O.\n..X\nOOO\n").err(), Some(ParseError::new(3, "Invalid plaintext: expected '.' or 'O'")));
+
+    let long = format!("!Name: Long\n.O.\n{}\n", ".".repeat(MAX_SIDE + 1));
+    assert_eq!(parse_plaintext(&long).err(), Some(ParseError::new(3, "Invalid plaintext: pattern too large")));
+    let tall = format!("!Name: Tall\nO{}O\n", "\n".repeat(MAX_SIDE));
+    assert_eq!(parse_plaintext(&tall).err(), Some(ParseError::new(MAX_SIDE + 2, "Invalid plaintext: pattern too large")));
+    let largest = parse_plaintext(&format!("O{}{}O\n", ".".repeat(MAX_SIDE - 1), "\n".repeat(MAX_SIDE - 1))).ok().unwrap();
+    assert_eq!((largest.width, largest.height), (MAX_SIDE, MAX_SIDE));
+}
+
+#[test]
+fn plaintext_round_trip() {
+    let b = parse_plaintext(".O.\n..O\nOOO\n").ok().unwrap();
+    assert_eq!(to_plaintext(&b), ".O.\n..O\nOOO\n");
+    assert_eq!(parse_plaintext(&to_plaintext(&b)).ok().unwrap().to_string(), b.to_string());
+}
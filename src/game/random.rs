@@ -0,0 +1,56 @@
+// Small deterministic generator (SplitMix64), runs started with the same seed are reproducible
+#[derive(Clone,PartialEq,Debug)]
+pub struct Random {
+    state: u64
+}
+
+impl Random {
+    pub fn new(seed: u64) -> Random {
+        Random { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        mix(self.state)
+    }
+}
+
+// Scrambles the bits of a value, close values give unrelated results
+pub fn mix(value: u64) -> u64 {
+    let mut z = value;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+// Draws true with the given probability for the value index of a key. The same key and index
+// always give the same answer, whatever the order in which they are asked
+pub fn chance(key: u64, index: u64, probability: f64) -> bool {
+    let value = mix(key ^ mix(index));
+    // the top 53 bits make a uniform number in [0, 1)
+    ((value >> 11) as f64 / (1u64 << 53) as f64) < probability
+}
+
+#[test]
+fn random_sequence() {
+    let mut a = Random::new(42);
+    let mut b = Random::new(42);
+    let first: Vec<u64> = (0..5).map(|_| a.next_u64()).collect();
+    let second: Vec<u64> = (0..5).map(|_| b.next_u64()).collect();
+    assert_eq!(first, second);
+    assert_ne!(first[0], first[1]);
+
+    let mut c = Random::new(43);
+    assert_ne!(c.next_u64(), first[0]);
+}
+
+#[test]
+fn random_chance() {
+    assert!((0..1000).all(|i| chance(7, i, 1.0)));
+    assert!((0..1000).all(|i| !chance(7, i, 0.0)));
+    assert_eq!(chance(7, 3, 0.5), chance(7, 3, 0.5));
+
+    // the frequency is close to the probability
+    let hits = (0..10000).filter(|&i| chance(11, i, 0.3)).count();
+    assert!(hits > 2800 && hits < 3200);
+}
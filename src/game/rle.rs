@@ -0,0 +1,260 @@
+use ::game::board;
+use ::game::pattern::{ParseError, MAX_SIDE};
+use ::game::rule;
+
+// Maximum length of the lines written by to_rle, as recommended by the format
+const LINE_LENGTH: usize = 70;
+
+// Parses a pattern in Run Length Encoded format. The board has the size given in the header,
+// the rule is only returned if the header has one
+pub fn parse_rle(input: &str) -> Result<(board::Board, Option<rule::Rule>), ParseError> {
+    let mut lines = input.lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|&(_, line)| !line.is_empty() && !line.starts_with('#'));
+
+    let (header_line, header) = match lines.next() {
+        Some(line) => line,
+        None => return Err(ParseError::at_end(input, "Invalid RLE: missing header")),
+    };
+
+    let mut width = None;
+    let mut height = None;
+    let mut rule = None;
+    // the rule is the last field and Larger than Life rules have commas, so it takes the rest of the line
+    let (fields, rule_field) = match header.find("rule") {
+        Some(start) => (header[..start].trim().trim_end_matches(','), Some(&header[start..])),
+        None => (header, None),
+    };
+    for field in fields.split(',').chain(rule_field) {
+        let parts: Vec<&str> = field.split('=').map(|part| part.trim()).collect();
+        if parts.len() != 2 {
+            return Err(ParseError::new(header_line, "Invalid RLE: malformed header"));
+        }
+        match parts[0] {
+            "x" => width = parts[1].parse::<usize>().ok(),
+            "y" => height = parts[1].parse::<usize>().ok(),
+            "rule" => rule = Some(rule::parse_rule(parts[1]).map_err(|e| ParseError::new(header_line, e))?),
+            _ => return Err(ParseError::new(header_line, "Invalid RLE: unknown header field")),
+        }
+    }
+
+    let mut board = match (width, height) {
+        (Some(width), Some(height)) if width > MAX_SIDE || height > MAX_SIDE => {
+            return Err(ParseError::new(header_line, "Invalid RLE: pattern too large"));
+        },
+        (Some(width), Some(height)) if width > 0 && height > 0 => board::Board::new(width, height),
+        _ => return Err(ParseError::new(header_line, "Invalid RLE: header must have positive x and y values")),
+    };
+
+    let (mut x, mut y) = (0usize, 0usize);
+    let mut count = String::new();
+    'lines: for (line_number, line) in lines {
+        for c in line.chars() {
+            match c {
+                '0'..='9' => count.push(c),
+                'b' | 'o' | '$' => {
+                    let too_large = || ParseError::new(line_number, "Invalid RLE: run count too large");
+                    let run = if count.is_empty() { 1 } else { count.parse::<usize>().map_err(|_| too_large())? };
+                    count.clear();
+                    if c == '$' {
+                        x = 0;
+                        y = y.checked_add(run).ok_or_else(too_large)?;
+                    } else {
+                        let end = x.checked_add(run).ok_or_else(too_large)?;
+                        for i in x..end {
+                            if board.set(i, y, c == 'o').is_err() {
+                                return Err(ParseError::new(line_number, "Invalid RLE: pattern larger than the size in the header"));
+                            }
+                        }
+                        x = end;
+                    }
+                },
+                '!' => break 'lines,
+                ' ' | '\t' => {},
+                _ => return Err(ParseError::new(line_number, "Invalid RLE: unexpected character")),
+            }
+        }
+    }
+
+    Ok((board, rule))
+}
+
+fn push_run(output: &mut String, line_length: &mut usize, run: usize, tag: char) {
+    let token = if run > 1 { format!("{}{}", run, tag) } else { tag.to_string() };
+    if *line_length + token.len() > LINE_LENGTH {
+        output.push('\n');
+        *line_length = 0;
+    }
+    *line_length += token.len();
+    output.push_str(&token);
+}
+
+// Encodes the board in Run Length Encoded format, dead cells at the end of a row
+// and empty rows at the end of the board are omitted
+pub fn to_rle(board: &board::Board, rule: &rule::Rule) -> String {
+    let mut output = format!("x = {}, y = {}, rule = {}\n", board.width, board.height, rule);
+    let mut line_length = 0;
+    let mut pending_rows = 0;
+
+    for row in board.to_string().lines() {
+        // dying cells are saved as dead
+        let row: String = row.chars().map(|c| if c == 'X' { 'X' } else { '_' }).collect();
+        let row = row.trim_end_matches('_');
+        if row.is_empty() {
+            pending_rows += 1;
+            continue;
+        }
+        if pending_rows > 0 {
+            push_run(&mut output, &mut line_length, pending_rows, '$');
+        }
+        pending_rows = 1;
+
+        let mut chars = row.chars().peekable();
+        while let Some(c) = chars.next() {
+            let mut run = 1;
+            while chars.peek() == Some(&c) {
+                chars.next();
+                run += 1;
+            }
+            push_run(&mut output, &mut line_length, run, if c == 'X' { 'o' } else { 'b' });
+        }
+    }
+
+    push_run(&mut output, &mut line_length, 1, '!');
+    output.push('\n');
+    output
+}
+
+#[cfg(test)]
+const GOSPER_GLIDER_GUN: &str = "#N Gosper glider gun\n\
+                                 #O Bill Gosper\n\
+                                 #C A true period 30 glider gun.\n\
+                                 x = 36, y = 9, rule = B3/S23\n\
+                                 24bo$22bobo$12b2o6b2o12b2o$11bo3bo4b2o12b2o$2o8bo5bo3b2o$2o8bo3bob2o4b\n\
+                                 obo$10bo5bo7bo$11bo3bo$12b2o!\n";
+
+#[test]
+fn rle_parse_glider() {
+    let (b, rule) = parse_rle("#C glider\nx = 3, y = 3\nbo$2bo$3o!").ok().unwrap();
+
+    assert!(rule.is_none());
+    assert_eq!(b.width, 3);
+    assert_eq!(b.height, 3);
+
+    let expected = "_X_\n\
+                    __X\n\
+                    XXX\n";
+
+    assert_eq!(b.to_string(), expected);
+}
+
+#[test]
+fn rle_parse_gosper_glider_gun() {
+    let (b, rule) = parse_rle(GOSPER_GLIDER_GUN).ok().unwrap();
+
+    assert_eq!(rule, Some(rule::Rule::conway()));
+    assert_eq!(b.width, 36);
+    assert_eq!(b.height, 9);
+
+    let expected = "________________________X___________\n\
+                    ______________________X_X___________\n\
+                    ____________XX______XX____________XX\n\
+                    ___________X___X____XX____________XX\n\
+                    XX________X_____X___XX______________\n\
+                    XX________X___X_XX____X_X___________\n\
+                    __________X_____X_______X___________\n\
+                    ___________X___X____________________\n\
+                    ____________XX______________________\n";
+
+    assert_eq!(b.to_string(), expected);
+}
+
+#[test]
+fn rle_parse_rule_and_blank_rows() {
+    let (b, rule) = parse_rle("x = 4, y = 5, rule = B36/S23\n2o2$\n  b3o!").ok().unwrap();
+
+    assert_eq!(rule, rule::parse_rule("B36/S23").ok());
+
+    let expected = "XX__\n\
+                    ____\n\
+                    _XXX\n\
+                    ____\n\
+                    ____\n";
+
+    assert_eq!(b.to_string(), expected);
+}
+
+#[test]
+fn rle_parse_errors() {
+    let error = |input| parse_rle(input).err().unwrap().to_string();
+
+    assert_eq!(error("#C only a comment"), "Line 1: Invalid RLE: missing header");
+    assert_eq!(error("#C a comment\n#C another one\n"), "Line 2: Invalid RLE: missing header");
+    assert_eq!(error("x = 3 y = 3\no!"), "Line 1: Invalid RLE: malformed header");
+    assert_eq!(error("#N Name\nx = 3, y = 3, z = 1\no!"), "Line 2: Invalid RLE: unknown header field");
+    assert_eq!(error("x = 0, y = 3\no!"), "Line 1: Invalid RLE: header must have positive x and y values");
+    assert_eq!(error("x = 3\no!"), "Line 1: Invalid RLE: header must have positive x and y values");
+    assert_eq!(error("x = 1000000000, y = 1000000000\no!"), "Line 1: Invalid RLE: pattern too large");
+    assert_eq!(error("x = 3, y = 3, rule = B9/S23\no!"), "Line 1: Invalid rule: neighbour counts must be digits between 0 and 8");
+    assert_eq!(error("x = 3, y = 3\n4o!"), "Line 2: Invalid RLE: pattern larger than the size in the header");
+    assert_eq!(error("x = 3, y = 3\no$\n\no3$o!"), "Line 4: Invalid RLE: pattern larger than the size in the header");
+    assert_eq!(error("x = 3, y = 3\noxo!"), "Line 2: Invalid RLE: unexpected character");
+}
+
+#[test]
+fn rle_parse_run_overflow() {
+    let error = |input| parse_rle(input).err().unwrap().to_string();
+
+    assert_eq!(error("x = 3, y = 3\n99999999999999999999o!"), "Line 2: Invalid RLE: run count too large");
+    assert_eq!(error("x = 3, y = 3\no\n18446744073709551615b!"), "Line 3: Invalid RLE: run count too large");
+    assert_eq!(error("x = 3, y = 3\n$18446744073709551615$o!"), "Line 2: Invalid RLE: run count too large");
+}
+
+#[test]
+fn rle_write_glider() {
+    let (b, _) = parse_rle("x = 3, y = 3\nbo$2bo$3o!").ok().unwrap();
+    assert_eq!(to_rle(&b, &rule::Rule::conway()), "x = 3, y = 3, rule = B3/S23\nbo$2bo$3o!\n");
+
+    let b = board::Board::new(4, 2);
+    assert_eq!(to_rle(&b, &rule::Rule::conway()), "x = 4, y = 2, rule = B3/S23\n!\n");
+}
+
+#[test]
+fn rle_round_trip() {
+    let (b, rule) = parse_rle(GOSPER_GLIDER_GUN).ok().unwrap();
+    let rle = to_rle(&b, &rule.unwrap());
+
+    assert!(rle.lines().all(|line| line.len() <= LINE_LENGTH));
+    assert!(rle.starts_with("x = 36, y = 9, rule = B3/S23\n24bo$22bobo$12b2o6b2o12b2o$"));
+
+    let (parsed, parsed_rule) = parse_rle(&rle).ok().unwrap();
+    assert_eq!(parsed_rule, rule);
+    assert_eq!(parsed.to_string(), b.to_string());
+
+    let (b, _) = parse_rle("x = 5, y = 7\n2$b3o2$o3bo!").ok().unwrap();
+    let rle = to_rle(&b, &rule::Rule::conway());
+    assert_eq!(rle, "x = 5, y = 7, rule = B3/S23\n2$b3o2$o3bo!\n");
+    assert_eq!(parse_rle(&rle).ok().unwrap().0.to_string(), b.to_string());
+}
+
+#[test]
+fn rle_write_generations() {
+    let mut b = board::Board::new(4, 2);
+    assert!(b.set(0, 0, true).is_ok());
+    assert!(b.set_dying(1, 0, 1).is_ok());
+    assert!(b.set_dying(3, 1, 1).is_ok());
+
+    // dying cells are saved as dead
+    let rule = rule::parse_rule("B2/S/C3").ok().unwrap();
+    assert_eq!(to_rle(&b, &rule), "x = 4, y = 2, rule = B2/S/C3\no!\n");
+    assert_eq!(parse_rle(&to_rle(&b, &rule)).ok().unwrap().1, Some(rule));
+}
+
+#[test]
+fn rle_larger_than_life_rule() {
+    let (b, rule) = parse_rle("x = 3, y = 1, rule = R5,C0,M1,S34..58,B34..45,NM\n3o!").ok().unwrap();
+    assert_eq!(b.population(), 3);
+    assert_eq!(rule, rule::parse_rule("R5,C0,M1,S34..58,B34..45,NM").ok());
+    assert_eq!(to_rle(&b, &rule.unwrap()), "x = 3, y = 1, rule = R5,C0,M1,S34..58,B34..45,NM\n3o!\n");
+}
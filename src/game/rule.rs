@@ -0,0 +1,646 @@
+use std::fmt;
+use ::game::isotropic;
+use ::game::ltl;
+
+// Outer-totalistic rule: a cell's next state only depends on its current state and on the
+// number of alive neighbours. Each mask has the bit n set if n neighbours cause a birth
+// (for dead cells) or a survival (for alive cells).
+// Generations rules have more than 2 states: alive cells that do not survive go through
+// states - 2 dying states before they are dead, and dying cells can not be born again.
+// Wireworld reuses the states: electron heads are alive cells, tails are dying state 1 and
+// conductors dying state 2, which never dies and is the only one that can be born.
+// Larger than Life rules count the neighbours within a range and isotropic non-totalistic
+// rules look at the arrangement of the neighbours instead of using the masks.
+// Stochastic rules only let the births and survivals happen with some probability
+#[derive(Copy,Clone,PartialEq,Debug)]
+pub struct Rule {
+    birth: u16,
+    survival: u16,
+    states: u8,
+    wireworld: bool,
+    neighbourhood: Neighbourhood,
+    range: Option<ltl::Range>,
+    isotropic: Option<isotropic::Isotropic>,
+    chances: Option<Chances>
+}
+
+// Probabilities of a cell being born or surviving when the rule says it does
+#[derive(Copy,Clone,PartialEq,Debug)]
+pub struct Chances {
+    pub birth: f64,
+    pub survival: f64
+}
+
+// Cells counted as neighbours, selected with a suffix of the rulestring as in Golly and LifeViewer
+#[derive(Copy,Clone,PartialEq,Debug)]
+pub enum Neighbourhood {
+    Moore,          // the 8 surrounding cells
+    VonNeumann,     // the 4 orthogonal cells, suffix V
+    Hexagonal,      // the Moore cells but the top right and bottom left ones, a hexagonal grid
+                    // sheared to fit the square one, suffix H
+    Triangular      // the 12 cells touching a triangle, which points up when x + y is even
+                    // and down otherwise, suffix L
+}
+
+const MOORE: [(isize, isize); 8] = [(-1, -1), (0, -1), (1, -1), (-1, 0), (1, 0), (-1, 1), (0, 1), (1, 1)];
+const VON_NEUMANN: [(isize, isize); 4] = [(0, -1), (-1, 0), (1, 0), (0, 1)];
+const HEXAGONAL: [(isize, isize); 6] = [(-1, -1), (0, -1), (-1, 0), (1, 0), (0, 1), (1, 1)];
+const TRIANGLE_UP: [(isize, isize); 12] = [(-1, -1), (0, -1), (1, -1), (-2, 0), (-1, 0), (1, 0), (2, 0),
+    (-2, 1), (-1, 1), (0, 1), (1, 1), (2, 1)];
+const TRIANGLE_DOWN: [(isize, isize); 12] = [(-2, -1), (-1, -1), (0, -1), (1, -1), (2, -1), (-2, 0), (-1, 0),
+    (1, 0), (2, 0), (-1, 1), (0, 1), (1, 1)];
+
+impl Neighbourhood {
+    // Offsets of the neighbours of cell (x, y), only the triangular ones depend on the cell
+    pub fn offsets(&self, x: usize, y: usize) -> &'static [(isize, isize)] {
+        match *self {
+            Neighbourhood::Moore => &MOORE,
+            Neighbourhood::VonNeumann => &VON_NEUMANN,
+            Neighbourhood::Hexagonal => &HEXAGONAL,
+            Neighbourhood::Triangular if (x + y).is_multiple_of(2) => &TRIANGLE_UP,
+            Neighbourhood::Triangular => &TRIANGLE_DOWN,
+        }
+    }
+
+    pub fn size(&self) -> u8 {
+        self.offsets(0, 0).len() as u8
+    }
+
+    fn suffix(&self) -> &'static str {
+        match *self {
+            Neighbourhood::Moore => "",
+            Neighbourhood::VonNeumann => "V",
+            Neighbourhood::Hexagonal => "H",
+            Neighbourhood::Triangular => "L",
+        }
+    }
+}
+
+// Dying states used by Wireworld
+pub const TAIL: u8 = 1;
+pub const CONDUCTOR: u8 = 2;
+
+impl Rule {
+    pub fn new(birth: &[u8], survival: &[u8]) -> Rule {
+        Rule::generations(birth, survival, 2)
+    }
+
+    pub fn generations(birth: &[u8], survival: &[u8], states: u8) -> Rule {
+        Rule {
+            birth: birth.iter().fold(0, |acc, n| acc | 1 << n),
+            survival: survival.iter().fold(0, |acc, n| acc | 1 << n),
+            states,
+            wireworld: false,
+            neighbourhood: Neighbourhood::Moore,
+            range: None,
+            isotropic: None,
+            chances: None
+        }
+    }
+
+    pub fn larger_than_life(range: ltl::Range, states: u8) -> Rule {
+        Rule {
+            range: Some(range),
+            ..Rule::generations(&[], &[], states)
+        }
+    }
+
+    pub fn range(&self) -> Option<ltl::Range> {
+        self.range
+    }
+
+    pub fn isotropic(&self) -> Option<isotropic::Isotropic> {
+        self.isotropic
+    }
+
+    pub fn chances(&self) -> Option<Chances> {
+        self.chances
+    }
+
+    pub fn with_chances(self, chances: Chances) -> Rule {
+        Rule { chances: Some(chances), ..self }
+    }
+
+    pub fn with_neighbourhood(self, neighbourhood: Neighbourhood) -> Rule {
+        Rule { neighbourhood, ..self }
+    }
+
+    pub fn neighbourhood(&self) -> Neighbourhood {
+        self.neighbourhood
+    }
+
+    // Wireworld: heads become tails, tails become conductors and conductors become heads
+    // with 1 or 2 neighbour heads
+    pub fn wireworld() -> Rule {
+        Rule {
+            wireworld: true,
+            ..Rule::generations(&[1, 2], &[], 4)
+        }
+    }
+
+    pub fn is_wireworld(&self) -> bool {
+        self.wireworld
+    }
+
+    // Conway's Game of Life: B3/S23
+    pub fn conway() -> Rule {
+        Rule::new(&[3], &[2, 3])
+    }
+
+    pub fn born(&self, neighbours: u8) -> bool {
+        self.birth & (1 << neighbours) != 0
+    }
+
+    pub fn survives(&self, neighbours: u8) -> bool {
+        self.survival & (1 << neighbours) != 0
+    }
+
+    pub fn next(&self, is_alive: bool, neighbours: u8) -> bool {
+        if is_alive { self.survives(neighbours) } else { self.born(neighbours) }
+    }
+
+    pub fn states(&self) -> u8 {
+        self.states
+    }
+
+    // Next state of a cell whose neighbours are all in its own state
+    fn background(&self, is_alive: bool) -> bool {
+        match self.isotropic {
+            Some(isotropic) => isotropic.next(is_alive, if is_alive { 0xff } else { 0 }),
+            None => self.next(is_alive, if is_alive { self.neighbourhood.size() } else { 0 }),
+        }
+    }
+
+    // Rules with B0 bring the dead background to life, which a board can not hold. They are run
+    // through complements that keep the background dead: without S8 the background flashes, so
+    // even generations show the cells and odd ones their complement, with S8 it stays alive and
+    // the complement is always shown
+    pub fn strobing(&self) -> bool {
+        self.range.is_none() && !self.wireworld && self.background(false)
+    }
+
+    // Rule that computes the generation after the given one
+    pub fn at_generation(&self, generation: usize) -> Rule {
+        if !self.strobing() {
+            *self
+        } else if self.background(true) {
+            self.complement_input().complement_output()
+        } else if generation.is_multiple_of(2) {
+            self.complement_output()
+        } else {
+            self.complement_input()
+        }
+    }
+
+    // Rule giving the complement of the next state of every cell
+    fn complement_output(&self) -> Rule {
+        let all = (1 << (self.neighbourhood.size() + 1)) - 1;
+        Rule {
+            birth: !self.birth & all,
+            survival: !self.survival & all,
+            isotropic: self.isotropic.map(|isotropic| isotropic.complement_output()),
+            ..*self
+        }
+    }
+
+    // Rule applied to the complement of the cells, where n neighbours become size - n
+    fn complement_input(&self) -> Rule {
+        let size = self.neighbourhood.size();
+        let reversed = |mask: u16| (0..size + 1).filter(|n| mask & (1 << n) != 0).fold(0, |acc, n| acc | 1 << (size - n));
+        Rule {
+            birth: reversed(self.survival),
+            survival: reversed(self.birth),
+            isotropic: self.isotropic.map(|isotropic| isotropic.complement_input()),
+            ..*self
+        }
+    }
+
+    // Checks if a cell that is not alive can be born in the given dying state
+    pub fn can_be_born(&self, dying: u8) -> bool {
+        if self.wireworld { dying == CONDUCTOR } else { dying == 0 }
+    }
+
+    // Next dying state of a cell, 0 when it is not dying. Dying states go from 1 to states - 2
+    pub fn next_dying(&self, dying: u8, is_alive: bool, survives: bool) -> u8 {
+        if self.wireworld {
+            return match (is_alive, survives, dying) {
+                (_, true, _) => 0,
+                (true, false, _) => TAIL,
+                (false, false, TAIL) => CONDUCTOR,
+                (false, false, dying) => dying,
+            };
+        }
+
+        if dying > 0 {
+            if (dying as usize) + 2 < self.states as usize { dying + 1 } else { 0 }
+        } else if is_alive && !survives && self.states > 2 {
+            1
+        } else {
+            0
+        }
+    }
+}
+
+// Character shown for a cell: 'X' alive, '_' dead and 1-9, a-z for the dying states.
+// Wireworld shows 'H' heads, 't' tails and '#' conductors
+pub fn cell_char(rule: &Rule, is_alive: bool, dying: u8) -> char {
+    match (rule.wireworld, is_alive, dying) {
+        (true, true, _) => 'H',
+        (true, false, TAIL) => 't',
+        (true, false, CONDUCTOR) => '#',
+        (false, true, _) => 'X',
+        (_, false, 0) => '_',
+        (_, false, dying) => ::std::char::from_digit(dying as u32, 36).unwrap_or('#'),
+    }
+}
+
+impl Default for Rule {
+    fn default() -> Rule {
+        Rule::conway()
+    }
+}
+
+// Counts above 9, only possible with triangular neighbourhoods, are written a, b and c
+fn parse_counts(input: &str, neighbourhood: Neighbourhood) -> Result<u16, &'static str> {
+    let mut mask = 0;
+    for c in input.chars() {
+        match c.to_digit(16) {
+            Some(n) if n <= neighbourhood.size() as u32 => mask |= 1 << n,
+            _ => return Err(match neighbourhood {
+                Neighbourhood::Moore => "Invalid rule: neighbour counts must be digits between 0 and 8",
+                Neighbourhood::VonNeumann => "Invalid rule: von Neumann neighbour counts must be digits between 0 and 4",
+                Neighbourhood::Hexagonal => "Invalid rule: hexagonal neighbour counts must be digits between 0 and 6",
+                Neighbourhood::Triangular => "Invalid rule: triangular neighbour counts must be between 0 and 9 or a to c",
+            }),
+        }
+    }
+    Ok(mask)
+}
+
+fn parse_chances(input: &str) -> Result<Chances, &'static str> {
+    let probabilities: Vec<&str> = input.split(',').collect();
+    if probabilities.len() != 2 {
+        return Err("Invalid rule: expected P<birth>,<survival> probabilities");
+    }
+    let parse = |probability: &str| match probability.parse::<f64>() {
+        Ok(p) if (0.0..=1.0).contains(&p) => Ok(p),
+        Ok(_) => Err("Invalid rule: probabilities must be between 0 and 1"),
+        Err(_) => Err("Invalid rule: expected P<birth>,<survival> probabilities"),
+    };
+    Ok(Chances { birth: parse(probabilities[0])?, survival: parse(probabilities[1])? })
+}
+
+fn parse_states(input: &str) -> Result<u8, &'static str> {
+    let states = if input.starts_with('C') || input.starts_with('c') { &input[1..] } else { input };
+    match states.parse() {
+        Ok(states) if states >= 2 => Ok(states),
+        _ => Err("Invalid rule: the number of states must be between 2 and 255"),
+    }
+}
+
+// Parses a rulestring in B/S notation ("B36/S23") or in S/B notation ("23/36"). Generations
+// rules add the number of states: "B2/S/C3" or "/2/3". A final V, H or L selects the von Neumann,
+// hexagonal or triangular neighbourhood: "B2/S34H". "Wireworld" selects Wireworld and rules
+// starting with R are Larger than Life ones: "R5,C0,M1,S34..58,B34..45,NM". A final P part
+// with the probabilities of the births and survivals makes any of them stochastic: "B3/S23/P0.5,1"
+pub fn parse_rule(input: &str) -> Result<Rule, &'static str> {
+    if let Some((rule, chances)) = input.trim().rsplit_once('/') {
+        if chances.starts_with('P') || chances.starts_with('p') {
+            let rule = parse_rule(rule)?;
+            if rule.strobing() {
+                return Err("Invalid rule: B0 rules can not be stochastic");
+            }
+            return Ok(rule.with_chances(parse_chances(&chances[1..])?));
+        }
+    }
+    if input.trim().eq_ignore_ascii_case("wireworld") {
+        return Ok(Rule::wireworld());
+    }
+    if input.trim().starts_with('R') || input.trim().starts_with('r') {
+        return ltl::parse_range(input).map(|(range, states)| Rule::larger_than_life(range, states));
+    }
+
+    let mut input = input.trim();
+    let neighbourhood = match input.chars().last().map(|c| c.to_ascii_uppercase()) {
+        Some('V') => Neighbourhood::VonNeumann,
+        Some('H') => Neighbourhood::Hexagonal,
+        Some('L') => Neighbourhood::Triangular,
+        _ => Neighbourhood::Moore,
+    };
+    if neighbourhood != Neighbourhood::Moore {
+        input = &input[..input.len() - 1];
+    }
+
+    let parts: Vec<&str> = input.split('/').collect();
+
+    if parts.len() != 2 && parts.len() != 3 {
+        return Err("Invalid rule: expected B<digits>/S<digits> or <digits>/<digits>");
+    }
+
+    let (birth, survival) = match (parts[0].chars().next(), parts[1].chars().next()) {
+        (Some('B'), Some('S')) | (Some('b'), Some('s')) => (&parts[0][1..], &parts[1][1..]),
+        (Some('S'), Some('B')) | (Some('s'), Some('b')) => (&parts[1][1..], &parts[0][1..]),
+        _ => (parts[1], parts[0]),
+    };
+
+    // letters after the counts give the arrangements of the neighbours in Hensel notation
+    let letters = |part: &str| part.chars().any(|c| c.is_ascii_alphabetic() || c == '-');
+    if neighbourhood != Neighbourhood::Triangular && (letters(birth) || letters(survival)) {
+        if neighbourhood != Neighbourhood::Moore {
+            return Err("Invalid rule: neighbourhood letters are only supported with the Moore neighbourhood");
+        }
+        let rule = Rule {
+            isotropic: Some(isotropic::parse_isotropic(birth, survival)?),
+            ..Rule::generations(&[], &[], match parts.get(2) {
+                Some(states) => parse_states(states)?,
+                None => 2,
+            })
+        };
+        if rule.strobing() && rule.states > 2 {
+            return Err("Invalid rule: B0 is only supported with two states");
+        }
+        return Ok(rule);
+    }
+
+    let rule = Rule {
+        birth: parse_counts(birth, neighbourhood)?,
+        survival: parse_counts(survival, neighbourhood)?,
+        states: match parts.get(2) {
+            Some(states) => parse_states(states)?,
+            None => 2,
+        },
+        wireworld: false,
+        neighbourhood,
+        range: None,
+        isotropic: None,
+        chances: None
+    };
+    if rule.strobing() && rule.states > 2 {
+        return Err("Invalid rule: B0 is only supported with two states");
+    }
+    Ok(rule)
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(chances) = self.chances {
+            return write!(f, "{}/P{},{}", Rule { chances: None, ..*self }, chances.birth, chances.survival);
+        }
+        if self.wireworld {
+            return write!(f, "Wireworld");
+        }
+        if let Some(range) = self.range {
+            return ltl::write_range(f, &range, self.states);
+        }
+        if let Some(isotropic) = self.isotropic {
+            write!(f, "{}", isotropic)?;
+            if self.states > 2 {
+                write!(f, "/C{}", self.states)?;
+            }
+            return Ok(());
+        }
+        let counts = |mask: u16| (0..13).filter(|n| mask & (1 << n) != 0)
+            .map(|n| ::std::char::from_digit(n, 16).unwrap()).collect::<String>();
+        write!(f, "B{}/S{}", counts(self.birth), counts(self.survival))?;
+        if self.states > 2 {
+            write!(f, "/C{}", self.states)?;
+        }
+        write!(f, "{}", self.neighbourhood.suffix())
+    }
+}
+
+#[test]
+fn rule_conway() {
+    let r = Rule::conway();
+    assert_eq!(r.to_string(), "B3/S23");
+    assert_eq!(Rule::default(), r);
+
+    for n in 0..9 {
+        assert_eq!(r.born(n), n == 3);
+        assert_eq!(r.survives(n), n == 2 || n == 3);
+    }
+
+    assert!(r.next(false, 3));
+    assert!(!r.next(false, 2));
+    assert!(r.next(true, 2));
+    assert!(!r.next(true, 4));
+}
+
+#[test]
+fn rule_parse_bs_notation() {
+    assert_eq!(parse_rule("B3/S23").ok(), Some(Rule::conway()));
+    assert_eq!(parse_rule("b3/s23").ok(), Some(Rule::conway()));
+    assert_eq!(parse_rule("S23/B3").ok(), Some(Rule::conway()));
+
+    // HighLife
+    assert_eq!(parse_rule("B36/S23").ok(), Some(Rule::new(&[3, 6], &[2, 3])));
+    // Seeds
+    assert_eq!(parse_rule("B2/S").ok(), Some(Rule::new(&[2], &[])));
+    // Day & Night
+    assert_eq!(parse_rule("B3678/S34678").ok(), Some(Rule::new(&[3, 6, 7, 8], &[3, 4, 6, 7, 8])));
+    // Life without death
+    assert_eq!(parse_rule("B3/S012345678").ok(), Some(Rule::new(&[3], &[0, 1, 2, 3, 4, 5, 6, 7, 8])));
+}
+
+#[test]
+fn rule_parse_sb_notation() {
+    assert_eq!(parse_rule("23/3").ok(), Some(Rule::conway()));
+    assert_eq!(parse_rule("23/36").ok(), Some(Rule::new(&[3, 6], &[2, 3])));
+    assert_eq!(parse_rule("/2").ok(), Some(Rule::new(&[2], &[])));
+    assert_eq!(parse_rule("34678/3678").ok(), Some(Rule::new(&[3, 6, 7, 8], &[3, 4, 6, 7, 8])));
+}
+
+#[test]
+fn rule_parse_errors() {
+    assert_eq!(parse_rule("B3S23").err(), Some("Invalid rule: expected B<digits>/S<digits> or <digits>/<digits>"));
+    assert_eq!(parse_rule("B3/S23/C3/D1").err(), Some("Invalid rule: expected B<digits>/S<digits> or <digits>/<digits>"));
+    assert_eq!(parse_rule("B9/S23").err(), Some("Invalid rule: neighbour counts must be digits between 0 and 8"));
+    assert_eq!(parse_rule("Bx/S23").err(), Some("Invalid rule: neighbour counts must be digits between 0 and 8"));
+    assert!(parse_rule("B3/B23").is_err());
+}
+
+#[test]
+fn rule_print() {
+    assert_eq!(parse_rule("S23/B36").ok().unwrap().to_string(), "B36/S23");
+    assert_eq!(parse_rule("/2").ok().unwrap().to_string(), "B2/S");
+    assert_eq!(parse_rule("B6323/S32").ok().unwrap().to_string(), "B236/S23");
+}
+
+#[test]
+fn rule_generations() {
+    // Brian's Brain
+    let r = parse_rule("B2/S/C3").ok().unwrap();
+    assert_eq!(r, Rule::generations(&[2], &[], 3));
+    assert_eq!(r.states(), 3);
+    assert_eq!(r.to_string(), "B2/S/C3");
+    assert_eq!(parse_rule("/2/3").ok(), Some(r));
+    assert_eq!(parse_rule("b2/s/c3").ok(), Some(r));
+
+    // Star Wars
+    let r = parse_rule("345/2/4").ok().unwrap();
+    assert_eq!(r, Rule::generations(&[2], &[3, 4, 5], 4));
+    assert_eq!(r.to_string(), "B2/S345/C4");
+
+    // two states is plain Life
+    assert_eq!(parse_rule("B3/S23/C2").ok(), Some(Rule::conway()));
+    assert_eq!(Rule::conway().states(), 2);
+
+    assert_eq!(parse_rule("B2/S/C1").err(), Some("Invalid rule: the number of states must be between 2 and 255"));
+    assert_eq!(parse_rule("B2/S/C256").err(), Some("Invalid rule: the number of states must be between 2 and 255"));
+    assert_eq!(parse_rule("B2/S/Cx").err(), Some("Invalid rule: the number of states must be between 2 and 255"));
+
+    // alive cells that do not survive go through the dying states
+    assert_eq!(r.next_dying(0, true, true), 0);
+    assert_eq!(r.next_dying(0, true, false), 1);
+    assert_eq!(r.next_dying(1, false, false), 2);
+    assert_eq!(r.next_dying(2, false, false), 0);
+    assert_eq!(r.next_dying(0, false, false), 0);
+    assert_eq!(Rule::conway().next_dying(0, true, false), 0);
+    assert_eq!(Rule::conway().next_dying(1, false, false), 0);
+    assert_eq!(Rule::generations(&[2], &[], 255).next_dying(253, false, false), 0);
+}
+
+#[test]
+fn rule_wireworld() {
+    let r = Rule::wireworld();
+    assert!(r.is_wireworld());
+    assert!(!Rule::conway().is_wireworld());
+    assert_eq!(r.to_string(), "Wireworld");
+    assert_eq!(parse_rule("wireworld").ok(), Some(r));
+    assert_eq!(parse_rule("WireWorld").ok(), Some(r));
+    assert!(r != Rule::generations(&[1, 2], &[], 4));
+
+    // only conductors can be born, with 1 or 2 heads around
+    assert!(r.can_be_born(CONDUCTOR));
+    assert!(!r.can_be_born(0));
+    assert!(!r.can_be_born(TAIL));
+    assert!(Rule::conway().can_be_born(0));
+    assert!(!Rule::conway().can_be_born(1));
+    assert!(r.born(1) && r.born(2) && !r.born(3));
+
+    assert_eq!(r.next_dying(0, true, false), TAIL);
+    assert_eq!(r.next_dying(TAIL, false, false), CONDUCTOR);
+    assert_eq!(r.next_dying(CONDUCTOR, false, false), CONDUCTOR);
+    assert_eq!(r.next_dying(CONDUCTOR, false, true), 0);
+    assert_eq!(r.next_dying(0, false, false), 0);
+
+    assert_eq!(cell_char(&r, true, 0), 'H');
+    assert_eq!(cell_char(&r, false, TAIL), 't');
+    assert_eq!(cell_char(&r, false, CONDUCTOR), '#');
+    assert_eq!(cell_char(&r, false, 0), '_');
+    assert_eq!(cell_char(&Rule::conway(), true, 0), 'X');
+    assert_eq!(cell_char(&Rule::conway(), false, 2), '2');
+}
+
+#[test]
+fn rule_parse_neighbourhoods() {
+    let r = parse_rule("B2/S34H").ok().unwrap();
+    assert_eq!(r.neighbourhood(), Neighbourhood::Hexagonal);
+    assert_eq!(r, Rule::new(&[2], &[3, 4]).with_neighbourhood(Neighbourhood::Hexagonal));
+    assert_eq!(r.to_string(), "B2/S34H");
+
+    assert_eq!(parse_rule("b1/s1v").ok().unwrap().to_string(), "B1/S1V");
+    assert_eq!(parse_rule("34/2H").ok().unwrap().to_string(), "B2/S34H");
+    assert_eq!(parse_rule("B2/S/C3V").ok().unwrap().to_string(), "B2/S/C3V");
+    assert_eq!(parse_rule("B3/S23").ok().unwrap().neighbourhood(), Neighbourhood::Moore);
+
+    let r = parse_rule("B4a/S3bcL").ok().unwrap();
+    assert!(r.born(10));
+    assert!(r.survives(12));
+    assert!(!r.survives(10));
+    assert_eq!(r.to_string(), "B4a/S3bcL");
+
+    assert_eq!(parse_rule("B5/S2V").err(), Some("Invalid rule: von Neumann neighbour counts must be digits between 0 and 4"));
+    assert_eq!(parse_rule("B2/S7H").err(), Some("Invalid rule: hexagonal neighbour counts must be digits between 0 and 6"));
+    assert_eq!(parse_rule("B3/S2d").err(), Some("Invalid rule: unknown neighbourhood letter for the neighbour count"));
+    assert_eq!(parse_rule("B3/S2dL").err(), Some("Invalid rule: triangular neighbour counts must be between 0 and 9 or a to c"));
+}
+
+#[test]
+fn rule_neighbourhood_offsets() {
+    assert_eq!(Neighbourhood::Moore.size(), 8);
+    assert_eq!(Neighbourhood::VonNeumann.size(), 4);
+    assert_eq!(Neighbourhood::Hexagonal.size(), 6);
+    assert_eq!(Neighbourhood::Triangular.size(), 12);
+
+    // the hexagonal neighbourhood is symmetric, as the triangular one is between both orientations
+    for &(dx, dy) in Neighbourhood::Hexagonal.offsets(0, 0) {
+        assert!(Neighbourhood::Hexagonal.offsets(0, 0).contains(&(-dx, -dy)));
+    }
+    for &(dx, dy) in Neighbourhood::Triangular.offsets(0, 0) {
+        assert!(Neighbourhood::Triangular.offsets(1, 0).contains(&(-dx, -dy)));
+    }
+}
+
+#[test]
+fn rule_parse_larger_than_life() {
+    let r = parse_rule("R5,C0,M1,S34..58,B34..45,NM").ok().unwrap();
+    assert_eq!(r.to_string(), "R5,C0,M1,S34..58,B34..45,NM");
+    assert_eq!(r.states(), 2);
+    assert_eq!(r.range().unwrap().radius, 5);
+    assert!(parse_rule("B3/S23").ok().unwrap().range().is_none());
+
+    let r = parse_rule("R2,C4,S3..5,B4..4").ok().unwrap();
+    assert_eq!(r.to_string(), "R2,C4,M0,S3..5,B4..4,NM");
+    assert_eq!(r.states(), 4);
+    assert_eq!(parse_rule("R0,C0,M0,S1..1,B1..1,NM").err(), Some("Invalid rule: the radius must be between 1 and 10"));
+}
+
+#[test]
+fn rule_parse_isotropic() {
+    let r = parse_rule("B2c3aei/S23-k").ok().unwrap();
+    assert!(r.isotropic().is_some());
+    assert_eq!(r.to_string(), "B2c3eai/S23-k");
+    assert_eq!(parse_rule("b2-a/s12").ok().unwrap().to_string(), "B2-a/S12");
+    assert_eq!(parse_rule("B2a/S/C4").ok().unwrap().to_string(), "B2a/S/C4");
+    assert_eq!(parse_rule("B2a/S/C4").ok().unwrap().states(), 4);
+    assert!(parse_rule("B3/S23").ok().unwrap().isotropic().is_none());
+
+    assert_eq!(parse_rule("B2x/S23").err(), Some("Invalid rule: unknown neighbourhood letter for the neighbour count"));
+    assert_eq!(parse_rule("B2a/S23H").err(), Some("Invalid rule: neighbourhood letters are only supported with the Moore neighbourhood"));
+}
+
+#[test]
+fn rule_strobing() {
+    assert!(!Rule::conway().strobing());
+    assert_eq!(Rule::conway().at_generation(1), Rule::conway());
+
+    // without S8 the rule alternates with its complement
+    let r = parse_rule("B0/S23").ok().unwrap();
+    assert!(r.strobing());
+    assert_eq!(r.to_string(), "B0/S23");
+    assert_eq!(r.at_generation(0).to_string(), "B12345678/S0145678");
+    assert_eq!(r.at_generation(1).to_string(), "B56/S8");
+    assert_eq!(r.at_generation(2), r.at_generation(0));
+
+    // with S8 the complement is used on every generation, the complement of Life is Life
+    let r = parse_rule("B0123478/S01234678").ok().unwrap();
+    assert_eq!(r.at_generation(0).to_string(), "B3/S23");
+    assert_eq!(r.at_generation(1).to_string(), "B3/S23");
+    assert_eq!(parse_rule("B0/S8").ok().unwrap().at_generation(5).to_string(), "B12345678/S01234567");
+
+    // the counts go up to the size of the neighbourhood
+    assert_eq!(parse_rule("B0/S1V").ok().unwrap().at_generation(1).to_string(), "B3/S4V");
+    assert_eq!(parse_rule("B02a/S1c").ok().unwrap().at_generation(1).to_string(), "B7c/S6a8");
+
+    assert_eq!(parse_rule("B0/S2/C3").err(), Some("Invalid rule: B0 is only supported with two states"));
+    assert_eq!(parse_rule("B0a/S2").err(), Some("Invalid rule: unknown neighbourhood letter for the neighbour count"));
+}
+
+#[test]
+fn rule_parse_chances() {
+    let r = parse_rule("B3/S23/P0.5,0.99").ok().unwrap();
+    assert_eq!(r.chances(), Some(Chances { birth: 0.5, survival: 0.99 }));
+    assert!(r.born(3) && r.survives(2));
+    assert_eq!(r.to_string(), "B3/S23/P0.5,0.99");
+    assert_eq!(parse_rule("b2/s/c3/p1,0").ok().unwrap().to_string(), "B2/S/C3/P1,0");
+    assert_eq!(parse_rule("B2/S34H/P0.25,1").ok().unwrap().neighbourhood(), Neighbourhood::Hexagonal);
+    assert_eq!(parse_rule("R1,C0,M0,S2..3,B3..3,NM/P0.5,1").ok().unwrap().to_string(), "R1,C0,M0,S2..3,B3..3,NM/P0.5,1");
+    assert_eq!(parse_rule("B2-a/S12/P0.1,0.2").ok().unwrap().to_string(), "B2-a/S12/P0.1,0.2");
+    assert!(parse_rule("B3/S23").ok().unwrap().chances().is_none());
+
+    assert_eq!(parse_rule("B3/S23/P0.5").err(), Some("Invalid rule: expected P<birth>,<survival> probabilities"));
+    assert_eq!(parse_rule("B3/S23/Pa,1").err(), Some("Invalid rule: expected P<birth>,<survival> probabilities"));
+    assert_eq!(parse_rule("B3/S23/P0.5,1.5").err(), Some("Invalid rule: probabilities must be between 0 and 1"));
+    assert_eq!(parse_rule("B0/S23/P0.5,1").err(), Some("Invalid rule: B0 rules can not be stochastic"));
+}
@@ -0,0 +1,162 @@
+use std::fmt;
+use ::game::board;
+use ::game::random;
+use ::game::rule;
+
+// Order in which the cells of a board are updated. The synchronous schedule computes every cell
+// from the previous generation, as if there was a global clock. The others update the cells one
+// at a time in place, so the cells see the ones updated before them in the same generation
+#[derive(Copy,Clone,PartialEq,Debug)]
+pub enum Schedule {
+    Synchronous,
+    RandomSequential,   // as many updates as cells, each one of a cell picked at random
+    Sweep,              // every cell once, row by row from the top left corner
+    Asynchronous(f64)   // every cell updated from the previous generation with probability α
+}
+
+// Parses "sync", "random", "sweep" or "alpha <probability>"
+pub fn parse_schedule(tokens: &[&str]) -> Result<Schedule, &'static str> {
+    match tokens {
+        ["sync"] | ["synchronous"] => Ok(Schedule::Synchronous),
+        ["random"] => Ok(Schedule::RandomSequential),
+        ["sweep"] => Ok(Schedule::Sweep),
+        ["alpha", alpha] => match alpha.parse::<f64>() {
+            Ok(alpha) if (0.0..=1.0).contains(&alpha) => Ok(Schedule::Asynchronous(alpha)),
+            _ => Err("Invalid schedule: alpha must be a probability between 0 and 1"),
+        },
+        _ => Err("Invalid schedule: expected sync, random, sweep or alpha <probability>"),
+    }
+}
+
+impl fmt::Display for Schedule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Schedule::Synchronous => write!(f, "sync"),
+            Schedule::RandomSequential => write!(f, "random"),
+            Schedule::Sweep => write!(f, "sweep"),
+            Schedule::Asynchronous(alpha) => write!(f, "alpha {}", alpha),
+        }
+    }
+}
+
+// Advances the board one generation with the schedule. The key seeds the random choices of
+// the schedule and of stochastic rules
+pub fn step(schedule: Schedule, rule: &rule::Rule, board: &mut board::Board, key: u64) {
+    let (width, height) = (board.width, board.height);
+    match schedule {
+        Schedule::Synchronous => {
+            let mut next = board.clone();
+            board.step_into(rule, 1, key, &mut next);
+            *board = next;
+        },
+        Schedule::RandomSequential => {
+            let mut random = random::Random::new(key);
+            for _ in 0..width * height {
+                let value = random.next_u64();
+                let cell = (value % (width * height) as u64) as usize;
+                let (x, y) = (cell % width, cell / width);
+                let (alive, dying) = board.next_state(rule, x, y, random::mix(value));
+                board.set_state(x, y, alive, dying);
+            }
+        },
+        Schedule::Sweep => {
+            for y in 0..height {
+                for x in 0..width {
+                    let (alive, dying) = board.next_state(rule, x, y, key);
+                    board.set_state(x, y, alive, dying);
+                }
+            }
+        },
+        Schedule::Asynchronous(alpha) => {
+            let previous = board.clone();
+            // the cells updated are drawn apart from the choices of the rule
+            let picks = random::mix(key);
+            for y in 0..height {
+                for x in 0..width {
+                    if random::chance(picks, (y * width + x) as u64, alpha) {
+                        let (alive, dying) = previous.next_state(rule, x, y, key);
+                        board.set_state(x, y, alive, dying);
+                    }
+                }
+            }
+        },
+    }
+}
+
+#[cfg(test)]
+fn blinker() -> board::Board {
+    let mut board = board::Board::new(5, 5);
+    for x in 1..4 {
+        board.set(x, 2, true).ok();
+    }
+    board
+}
+
+#[test]
+fn schedule_parse() {
+    assert_eq!(parse_schedule(&["sync"]), Ok(Schedule::Synchronous));
+    assert_eq!(parse_schedule(&["random"]), Ok(Schedule::RandomSequential));
+    assert_eq!(parse_schedule(&["sweep"]), Ok(Schedule::Sweep));
+    assert_eq!(parse_schedule(&["alpha", "0.5"]), Ok(Schedule::Asynchronous(0.5)));
+    assert_eq!(Schedule::Asynchronous(0.5).to_string(), "alpha 0.5");
+    assert_eq!(parse_schedule(&["alpha", "2"]).err(), Some("Invalid schedule: alpha must be a probability between 0 and 1"));
+    assert_eq!(parse_schedule(&["alpha"]).err(), Some("Invalid schedule: expected sync, random, sweep or alpha <probability>"));
+    assert_eq!(parse_schedule(&["clock"]).err(), Some("Invalid schedule: expected sync, random, sweep or alpha <probability>"));
+}
+
+#[test]
+fn schedule_sweep() {
+    // the cells born above the blinker are seen by the ones updated after them
+    let mut board = blinker();
+    step(Schedule::Sweep, &rule::Rule::conway(), &mut board, 0);
+    assert_eq!(board.to_string(), "_____\n__XX_\n_X_X_\n_____\n_____\n");
+}
+
+#[test]
+fn schedule_asynchronous() {
+    let life = rule::Rule::conway();
+    let mut synchronous = blinker();
+    synchronous.step(&life);
+
+    // updating every cell is the synchronous schedule, updating none leaves the board
+    let mut board = blinker();
+    step(Schedule::Asynchronous(1.0), &life, &mut board, 3);
+    assert_eq!(board.to_string(), synchronous.to_string());
+    let mut board = blinker();
+    step(Schedule::Asynchronous(0.0), &life, &mut board, 3);
+    assert_eq!(board.to_string(), blinker().to_string());
+
+    // with the same key the same cells are updated, and every cell is either updated or not
+    let mut a = blinker();
+    let mut b = blinker();
+    step(Schedule::Asynchronous(0.5), &life, &mut a, 3);
+    step(Schedule::Asynchronous(0.5), &life, &mut b, 3);
+    assert_eq!(a.to_string(), b.to_string());
+    for y in 0..5 {
+        for x in 0..5 {
+            let cell = a.get(x, y).ok().unwrap();
+            assert!(cell == blinker().get(x, y).ok().unwrap() || cell == synchronous.get(x, y).ok().unwrap());
+        }
+    }
+}
+
+#[test]
+fn schedule_random_sequential() {
+    let life = rule::Rule::conway();
+    let mut a = blinker();
+    let mut b = blinker();
+    step(Schedule::RandomSequential, &life, &mut a, 9);
+    step(Schedule::RandomSequential, &life, &mut b, 9);
+    assert_eq!(a.to_string(), b.to_string());
+
+    // a block is stable in any order
+    let mut board = board::Board::new(4, 4);
+    for &(x, y) in [(1, 1), (2, 1), (1, 2), (2, 2)].iter() {
+        board.set(x, y, true).ok();
+    }
+    let block = board.to_string();
+    for key in 0..10 {
+        step(Schedule::RandomSequential, &life, &mut board, key);
+        assert_eq!(board.to_string(), block);
+    }
+}
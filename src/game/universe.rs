@@ -0,0 +1,258 @@
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::fmt;
+use ::game::board;
+use ::game::rule;
+
+// Unbounded universe: only the alive cells are stored, so patterns can grow in any direction
+#[derive(Clone,Default)]
+pub struct Universe {
+    cells: HashSet<(i64, i64)>
+}
+
+impl Universe {
+    pub fn new() -> Universe {
+        Universe {
+            cells: HashSet::new()
+        }
+    }
+
+    pub fn set(&mut self, x: i64, y: i64, value: bool) {
+        if value {
+            self.cells.insert((x, y));
+        } else {
+            self.cells.remove(&(x, y));
+        }
+    }
+
+    pub fn get(&self, x: i64, y: i64) -> bool {
+        self.cells.contains(&(x, y))
+    }
+
+    pub fn toggle(&mut self, x: i64, y: i64) {
+        let value = !self.get(x, y);
+        self.set(x, y, value);
+    }
+
+    pub fn population(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn cells(&self) -> &HashSet<(i64, i64)> {
+        &self.cells
+    }
+
+    // Returns (min x, min y, max x, max y) of the alive cells, None if the universe is empty
+    pub fn bounding_box(&self) -> Option<(i64, i64, i64, i64)> {
+        self.cells.iter().fold(None, |acc, &(x, y)| {
+            match acc {
+                None => Some((x, y, x, y)),
+                Some((x_min, y_min, x_max, y_max)) => Some((x_min.min(x), y_min.min(y), x_max.max(x), y_max.max(y))),
+            }
+        })
+    }
+
+    // Cells with a different value in the other universe
+    pub fn diff(&self, other: &Universe) -> Vec<(i64, i64)> {
+        self.cells.symmetric_difference(&other.cells).cloned().collect()
+    }
+
+    // Copies the bounding box of the alive cells into a board
+    pub fn to_board(&self) -> board::Board {
+        match self.bounding_box() {
+            Some((x_min, y_min, x_max, y_max)) => {
+                let mut board = board::Board::new((x_max - x_min + 1) as usize, (y_max - y_min + 1) as usize);
+                for &(x, y) in &self.cells {
+                    board.set((x - x_min) as usize, (y - y_min) as usize, true).ok();
+                }
+                board
+            },
+            None => board::Board::new(1, 1),
+        }
+    }
+
+    // Creates a universe with the alive cells of the board, placing its top left corner at the origin
+    pub fn from_board(board: &board::Board) -> Universe {
+        let mut universe = Universe::new();
+        for x in 0..board.width {
+            for y in 0..board.height {
+                if board.get(x, y).ok().unwrap() {
+                    universe.set(x as i64, y as i64, true);
+                }
+            }
+        }
+        universe
+    }
+
+    pub fn neighbours(&self, x: i64, y: i64) -> u8 {
+        let mut count: u8 = 0;
+
+        for dx in -1..2 {
+            for dy in -1..2 {
+                if !(dx == 0 && dy == 0) && self.get(x.wrapping_add(dx), y.wrapping_add(dy)) {
+                    count += 1;
+                }
+            }
+        }
+
+        count
+    }
+
+    // Only alive cells and their neighbours can change, so the neighbour counts are
+    // accumulated from the alive cells instead of visiting the whole universe
+    pub fn step(&mut self, rule: &rule::Rule) {
+        let mut counts: HashMap<(i64, i64), u8> = HashMap::new();
+
+        for &(x, y) in &self.cells {
+            for dx in -1..2 {
+                for dy in -1..2 {
+                    if !(dx == 0 && dy == 0) {
+                        *counts.entry((x.wrapping_add(dx), y.wrapping_add(dy))).or_insert(0) += 1;
+                    }
+                }
+            }
+        }
+
+        let mut next = HashSet::new();
+        for &cell in &self.cells {
+            if rule.survives(*counts.get(&cell).unwrap_or(&0)) {
+                next.insert(cell);
+            }
+        }
+        for (cell, neighbours) in counts {
+            if !self.cells.contains(&cell) && rule.born(neighbours) {
+                next.insert(cell);
+            }
+        }
+
+        self.cells = next;
+    }
+}
+
+impl fmt::Display for Universe {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some((x_min, y_min, x_max, y_max)) = self.bounding_box() {
+            for y in y_min..y_max + 1 {
+                let line = (x_min..x_max + 1).fold("".to_string(), |acc, x| acc + if self.get(x, y) { "X" } else { "_" });
+                writeln!(f, "{}", line)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn universe_constructor() {
+    let u = Universe::new();
+    assert_eq!(u.population(), 0);
+    assert_eq!(u.bounding_box(), None);
+    assert_eq!(u.to_string(), "");
+}
+
+#[test]
+fn universe_set_get_toggle() {
+    let mut u = Universe::new();
+
+    assert!(!u.get(0, 0));
+    u.set(0, 0, true);
+    assert!(u.get(0, 0));
+    u.set(0, 0, false);
+    assert!(!u.get(0, 0));
+
+    u.toggle(-5, 1_000_000_000_000);
+    assert!(u.get(-5, 1_000_000_000_000));
+    assert_eq!(u.population(), 1);
+    u.toggle(-5, 1_000_000_000_000);
+    assert!(!u.get(-5, 1_000_000_000_000));
+    assert_eq!(u.population(), 0);
+}
+
+#[test]
+fn universe_print() {
+    let mut u = Universe::new();
+
+    u.set(-2, -1, true);
+    u.set(1, 1, true);
+    assert_eq!(u.bounding_box(), Some((-2, -1, 1, 1)));
+
+    let expected = "X___\n\
+                    ____\n\
+                    ___X\n";
+
+    assert_eq!(u.to_string(), expected);
+}
+
+#[test]
+fn universe_diff() {
+    let mut u = Universe::new();
+    let mut other = Universe::new();
+    assert!(u.diff(&other).is_empty());
+
+    u.set(-1, 2, true);
+    u.set(5, 5, true);
+    other.set(5, 5, true);
+    other.set(0, 0, true);
+
+    let mut cells = u.diff(&other);
+    cells.sort();
+    assert_eq!(cells, vec![(-1, 2), (0, 0)]);
+}
+
+#[test]
+fn universe_board_conversion() {
+    let mut u = Universe::new();
+    assert_eq!(u.to_board().to_string(), "_\n");
+
+    u.set(-2, -1, true);
+    u.set(1, 1, true);
+
+    let b = u.to_board();
+    assert_eq!(b.width, 4);
+    assert_eq!(b.height, 3);
+    assert_eq!(b.to_string(), u.to_string());
+
+    let converted = Universe::from_board(&b);
+    assert_eq!(converted.bounding_box(), Some((0, 0, 3, 2)));
+    assert_eq!(converted.to_string(), u.to_string());
+}
+
+#[test]
+fn universe_neighbours() {
+    let mut u = Universe::new();
+
+    u.set(0, 0, true);
+    assert_eq!(u.neighbours(0, 0), 0);
+    assert_eq!(u.neighbours(-1, -1), 1);
+    assert_eq!(u.neighbours(1, 1), 1);
+    assert_eq!(u.neighbours(2, 2), 0);
+
+    for x in -1..2 {
+        for y in -1..2 {
+            u.set(x, y, true);
+        }
+    }
+    assert_eq!(u.neighbours(0, 0), 8);
+    assert_eq!(u.neighbours(-2, 0), 3);
+}
+
+#[test]
+fn universe_step_glider() {
+    let mut u = Universe::new();
+
+    u.set(1, 0, true);
+    u.set(2, 1, true);
+    u.set(0, 2, true);
+    u.set(1, 2, true);
+    u.set(2, 2, true);
+
+    let start = u.to_string();
+
+    // the glider keeps moving far away from the origin without ever hitting an edge
+    for _ in 0..400 {
+        u.step(&rule::Rule::conway());
+    }
+
+    assert_eq!(u.population(), 5);
+    assert_eq!(u.bounding_box(), Some((100, 100, 102, 102)));
+    assert_eq!(u.to_string(), start);
+}
@@ -0,0 +1,122 @@
+use ::game::board;
+
+// Next state of a cell of an elementary automaton: bit n of the Wolfram code is the state of a
+// cell whose left, centre and right neighbours read as the binary number n
+pub fn next_state(rule: u8, left: bool, centre: bool, right: bool) -> bool {
+    let n = (left as u8) << 2 | (centre as u8) << 1 | right as u8;
+    rule & (1 << n) != 0
+}
+
+// Computes the next generation of row y, cells past the edges follow the topology of the board
+pub fn next_row(rule: u8, board: &board::Board, y: usize) -> Vec<bool> {
+    let get = |x: usize, dx: isize| match board.neighbour(x, y, dx, 0) {
+        Some((x, y)) => board.get(x, y).ok().unwrap(),
+        None => false,
+    };
+    (0..board.width).map(|x| next_state(rule, get(x, -1), get(x, 0), get(x, 1))).collect()
+}
+
+// The board is a space-time diagram with one generation per row, filled from the top.
+// Once the last row is reached the diagram scrolls up one row per generation
+pub fn step(rule: u8, board: &mut board::Board, generation: usize) {
+    let row = generation.min(board.height - 1);
+    let next = next_row(rule, board, row);
+
+    let y = if generation + 1 < board.height {
+        row + 1
+    } else {
+        for y in 0..board.height - 1 {
+            for x in 0..board.width {
+                let value = board.get(x, y + 1).ok().unwrap();
+                board.set(x, y, value).ok();
+            }
+        }
+        board.height - 1
+    };
+    for (x, &value) in next.iter().enumerate() {
+        board.set(x, y, value).ok();
+    }
+}
+
+// Board of the given size with a single cell set in the middle of the first row.
+// The rows wrap around, as usual for elementary automata
+pub fn seed(width: usize, height: usize) -> board::Board {
+    let mut board = board::Board::with_topology(width, height, board::Topology::Cylinder);
+    board.set(width / 2, 0, true).ok();
+    board
+}
+
+#[cfg(test)]
+fn diagram(rule: u8, width: usize, height: usize, generations: usize) -> String {
+    let mut board = seed(width, height);
+    for generation in 0..generations {
+        step(rule, &mut board, generation);
+    }
+    board.to_string()
+}
+
+#[test]
+fn wolfram_next_state() {
+    // rule 30 is 00011110
+    let states: Vec<bool> = (0..8).map(|n| next_state(30, n & 4 != 0, n & 2 != 0, n & 1 != 0)).collect();
+    assert_eq!(states, vec![false, true, true, true, true, false, false, false]);
+    assert!(!next_state(0, true, true, true));
+    assert!(next_state(255, false, false, false));
+}
+
+#[test]
+fn wolfram_rule_30() {
+    assert_eq!(diagram(30, 11, 6, 5), concat!(
+        "_____X_____\n",
+        "____XXX____\n",
+        "___XX__X___\n",
+        "__XX_XXXX__\n",
+        "_XX__X___X_\n",
+        "XX_XXXX_XXX\n"));
+
+    // the centre column is the well known pseudo-random sequence
+    let mut board = seed(81, 1);
+    let mut centre = Vec::new();
+    for generation in 0..20 {
+        centre.push(board.get(40, 0).ok().unwrap() as u8);
+        step(30, &mut board, generation);
+    }
+    assert_eq!(centre, vec![1, 1, 0, 1, 1, 1, 0, 0, 1, 1, 0, 0, 0, 1, 0, 1, 1, 0, 0, 1]);
+}
+
+#[test]
+fn wolfram_rule_90() {
+    assert_eq!(diagram(90, 11, 6, 5), concat!(
+        "_____X_____\n",
+        "____X_X____\n",
+        "___X___X___\n",
+        "__X_X_X_X__\n",
+        "_X_______X_\n",
+        "X_X_____X_X\n"));
+}
+
+#[test]
+fn wolfram_rule_110() {
+    assert_eq!(diagram(110, 11, 5, 4), concat!(
+        "_____X_____\n",
+        "____XX_____\n",
+        "___XXX_____\n",
+        "__XX_X_____\n",
+        "_XXXXX_____\n"));
+}
+
+#[test]
+fn wolfram_scroll() {
+    // the last rows of the rule 90 diagram remain once it scrolls
+    assert_eq!(diagram(90, 11, 3, 5), concat!(
+        "__X_X_X_X__\n",
+        "_X_______X_\n",
+        "X_X_____X_X\n"));
+
+    // rows wrap around
+    assert_eq!(diagram(90, 5, 4, 3), concat!(
+        "__X__\n",
+        "_X_X_\n",
+        "X___X\n",
+        "XX_XX\n"));
+}
@@ -1,11 +1,87 @@
 extern crate rust_of_life;
 
+mod terminal;
+mod tui;
+
 use rust_of_life::game::command;
 use rust_of_life::game::engine;
 use std::io;
 use std::io::Write;
 use std::io::stdout;
 use std::thread;
+use std::time::Duration;
+use std::time::Instant;
+
+// Processes the command and prints the game, returns false if the command failed
+fn process(engine: &mut engine::Engine, command: command::Command) -> bool {
+    println!("Command: {}", command);
+    if let Err(error) = engine.process_command(command) {
+        println!("Error processing command: {}", error);
+        return false;
+    }
+
+    if let Some(game) = engine.render() {
+        print!("{}", game);
+    }
+    stdout().flush().ok();
+    true
+}
+
+// Waits for the delay between generations, returns true if a key was pressed meanwhile
+fn wait(delay: u64, raw_mode: &Option<terminal::RawMode>) -> bool {
+    let start = Instant::now();
+    loop {
+        if let Some(raw_mode) = raw_mode.as_ref() {
+            if raw_mode.key().is_some() {
+                return true;
+            }
+        }
+
+        let elapsed = start.elapsed();
+        if elapsed >= Duration::from_millis(delay) {
+            return false;
+        }
+        thread::sleep((Duration::from_millis(delay) - elapsed).min(Duration::from_millis(10)));
+    }
+}
+
+// Plays until the given number of generations is reached, the board becomes empty or static,
+// or any key (including Ctrl-C) is pressed
+fn play(engine: &mut engine::Engine, command: command::Command, generations: Option<usize>) {
+    let raw_mode = terminal::RawMode::enable();
+    if raw_mode.is_some() {
+        println!("Press any key to stop");
+    }
+
+    let mut played = 0;
+    let mut period = engine.period();
+    while process(engine, command.clone()) {
+        played += 1;
+
+        if engine.period() != period {
+            period = engine.period();
+            if let Some(period) = period {
+                println!("Detected {}", period);
+            }
+        }
+
+        if engine.population() == 0 {
+            println!("The board is empty");
+            break;
+        }
+        if engine.is_static() {
+            println!("The board is static");
+            break;
+        }
+        if generations.is_some_and(|generations| played >= generations) {
+            break;
+        }
+        if wait(engine.delay(), &raw_mode) {
+            println!("Stopped");
+            break;
+        }
+    }
+}
 
 fn main() {
     println!("Welcome to Rust of Live!");
@@ -15,12 +91,11 @@ fn main() {
     loop {
         // Prompt
         print!("game> ");
-        stdout().flush().is_ok();
+        stdout().flush().ok();
 
         // Read command
         let mut input = String::new();
         io::stdin().read_line(&mut input)
-            .ok()
             .expect("Failed to read command");
 
         let result = command::parse_command(&input);
@@ -28,37 +103,32 @@ fn main() {
         if result.is_ok() {
             let command = result.ok().unwrap();
 
-            if let command::Command::Quit = command {
-                break;
-            } else {
-                loop {
-                    // This flag and the following {} block are needed as a workaround
-                    // for the strict borrowing rules of Rust:
-                    // the first mutable borrow when calling process_command was preventing the second
-                    // borrow when getting the board to print it
-                    // the {} block is needed to ensure the first borrow is released, the flag is needed
-                    // to save the result of the process_command to decide if the board should be printed
-                    let mut command_ok = false;
-                    {
-                        let result = engine.process_command(command);
-
-                        if result.is_ok() {
-                            command_ok = true;
-                        } else {
-                            println!("Error processing command: {}", result.err().unwrap());
-                        }
+            match command {
+                command::Command::Quit => break,
+                command::Command::Play { generations } => play(&mut engine, command.clone(), generations),
+                command::Command::Tui => {
+                    if tui::run(&mut engine) {
+                        break;
                     }
-                    if command_ok {
-                        print!("{}", engine.board().unwrap());
-                        stdout().flush().is_ok();
+                },
+                command::Command::Stats => {
+                    if process(&mut engine, command) {
+                        print!("Generation: {} Population: {}", engine.generation(), engine.population());
+                        match engine.evaluated() {
+                            Some(evaluated) => println!(" Evaluated cells: {}", evaluated),
+                            None => println!(),
+                        }
                     }
-
-                    if let command::Command::Play = command {
-                        thread::sleep_ms(1000);
-                    } else {
-                        break;
+                },
+                command::Command::Period => {
+                    if process(&mut engine, command) {
+                        match engine.period() {
+                            Some(period) => println!("The pattern is a {}", period),
+                            None => println!("No period detected yet"),
+                        }
                     }
-                }
+                },
+                _ => { process(&mut engine, command); },
             }
         } else {
             println!("{}", result.err().unwrap());
@@ -0,0 +1,63 @@
+use std::io;
+use std::io::Read;
+use std::process;
+use std::process::Stdio;
+
+// Puts the terminal in raw mode while it is alive: keys are read as soon as they are pressed,
+// without echo, and Ctrl-C is read as a key instead of killing the process.
+// It relies on stty, so it is only available on unix terminals
+pub struct RawMode {
+    saved: String
+}
+
+fn stty(args: &[&str]) -> Option<String> {
+    let output = process::Command::new("stty")
+        .args(args)
+        .stdin(Stdio::inherit())
+        .output()
+        .ok()?;
+
+    if output.status.success() {
+        Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    } else {
+        None
+    }
+}
+
+impl RawMode {
+    // Returns None if stdin is not a terminal
+    pub fn enable() -> Option<RawMode> {
+        let saved = stty(&["-g"])?;
+        stty(&["-icanon", "-echo", "-isig", "min", "0", "time", "0"])?;
+        Some(RawMode { saved })
+    }
+
+    // Reads the pending keys without blocking, returns the first one
+    pub fn key(&self) -> Option<u8> {
+        self.keys().first().cloned()
+    }
+
+    // Reads the pending keys without blocking, escape sequences such as arrows are kept whole
+    pub fn keys(&self) -> Vec<u8> {
+        let mut buffer = [0; 16];
+        match io::stdin().read(&mut buffer) {
+            Ok(n) => buffer[..n].to_vec(),
+            Err(_) => Vec::new(),
+        }
+    }
+}
+
+// Returns the number of columns and rows of the terminal
+pub fn size() -> Option<(usize, usize)> {
+    let size = stty(&["size"])?;
+    let mut tokens = size.split_whitespace();
+    let rows = tokens.next()?.parse().ok()?;
+    let columns = tokens.next()?.parse().ok()?;
+    Some((columns, rows))
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        stty(&[&self.saved]);
+    }
+}
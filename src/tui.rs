@@ -0,0 +1,365 @@
+use rust_of_life::game::ant;
+use rust_of_life::game::board;
+use rust_of_life::game::command;
+use rust_of_life::game::engine;
+use std::io::Write;
+use std::io::stdout;
+use std::thread;
+use std::time::Duration;
+use std::time::Instant;
+use terminal;
+
+// Lines of the screen below the board: status, help and last message
+const STATUS_LINES: usize = 3;
+
+// Raw mode reads Ctrl-C as this byte instead of sending SIGINT
+const CTRL_C: u8 = 0x03;
+
+#[derive(Copy,Clone,PartialEq,Debug)]
+enum Key {
+    Up,
+    Down,
+    Left,
+    Right,
+    Escape,
+    Char(u8)
+}
+
+// Splits the bytes read from the terminal into keys, arrows arrive as "ESC [ A" to "ESC [ D"
+fn parse_keys(bytes: &[u8]) -> Vec<Key> {
+    let mut keys = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == 0x1b && i + 2 < bytes.len() && bytes[i + 1] == b'[' {
+            let key = match bytes[i + 2] {
+                b'A' => Some(Key::Up),
+                b'B' => Some(Key::Down),
+                b'C' => Some(Key::Right),
+                b'D' => Some(Key::Left),
+                _ => None,
+            };
+            if let Some(key) = key {
+                keys.push(key);
+            }
+            i += 3;
+        } else {
+            keys.push(if bytes[i] == 0x1b { Key::Escape } else { Key::Char(bytes[i]) });
+            i += 1;
+        }
+    }
+    keys
+}
+
+struct Tui {
+    // zero based position of the cursor and of the top left cell shown
+    cursor: (usize, usize),
+    origin: (usize, usize),
+    // columns and rows available for the board
+    view: (usize, usize),
+    playing: bool,
+    message: String
+}
+
+impl Tui {
+    // Dimensions the cursor is limited to, None for the unbounded universe
+    fn limits(engine: &engine::Engine) -> Option<(usize, usize)> {
+        engine.board().map(|board| (board.width, board.height))
+    }
+
+    fn move_cursor(&mut self, engine: &engine::Engine, key: Key) {
+        let (x, y) = self.cursor;
+        let (width, height) = Tui::limits(engine).unwrap_or((usize::MAX, usize::MAX));
+        self.cursor = match key {
+            Key::Up => (x, y.saturating_sub(1)),
+            Key::Down => (x, (y + 1).min(height - 1)),
+            Key::Left => (x.saturating_sub(1), y),
+            Key::Right => ((x + 1).min(width - 1), y),
+            _ => (x, y),
+        };
+
+        // scrolls the view to keep the cursor visible
+        let (x, y) = self.cursor;
+        if x < self.origin.0 {
+            self.origin.0 = x;
+        } else if x >= self.origin.0 + self.view.0 {
+            self.origin.0 = x + 1 - self.view.0;
+        }
+        if y < self.origin.1 {
+            self.origin.1 = y;
+        } else if y >= self.origin.1 + self.view.1 {
+            self.origin.1 = y + 1 - self.view.1;
+        }
+    }
+
+    // Command mapped to a key, None for the keys handled by the TUI itself
+    fn command(&mut self, engine: &engine::Engine, key: Key) -> Option<command::Command> {
+        match key {
+            Key::Char(b' ') | Key::Char(b'\n') | Key::Char(b'\r') => {
+                Some(command::Command::Toggle { x: self.cursor.0 + 1, y: self.cursor.1 + 1 })
+            },
+            Key::Char(b'w') => Some(command::Command::Wire { x: self.cursor.0 + 1, y: self.cursor.1 + 1 }),
+            Key::Char(b'e') => Some(command::Command::Electron { x: self.cursor.0 + 1, y: self.cursor.1 + 1 }),
+            Key::Char(b's') => {
+                self.playing = false;
+                Some(command::Command::Step)
+            },
+            Key::Char(b'p') => {
+                self.playing = !self.playing;
+                None
+            },
+            Key::Char(b'n') => {
+                self.playing = false;
+                self.cursor = (0, 0);
+                self.origin = (0, 0);
+                match engine.board() {
+                    Some(board) => Some(command::Command::New { width: board.width, height: board.height, topology: board.topology, storage: board.storage }),
+                    None => Some(command::Command::Universe),
+                }
+            },
+            Key::Char(b'q') | Key::Char(CTRL_C) => Some(command::Command::Quit),
+            _ => None,
+        }
+    }
+
+    fn process(&mut self, engine: &mut engine::Engine, command: command::Command) {
+        match engine.process_command(command) {
+            Ok(_) => self.message.clear(),
+            Err(error) => {
+                self.message = error.to_string();
+                self.playing = false;
+            },
+        }
+    }
+
+    // Redraws the whole screen in place, the cursor cell is shown in reverse video
+    fn draw(&self, engine: &engine::Engine) {
+        let board = engine.board();
+        let universe = engine.universe();
+
+        let mut screen = String::from("\x1b[H");
+        for y in self.origin.1..self.origin.1 + self.view.1 {
+            for x in self.origin.0..self.origin.0 + self.view.0 {
+                let c = if let Some(board) = board.as_ref() {
+                    if x >= board.width || y >= board.height {
+                        ' '
+                    } else if !engine.ants().is_empty() {
+                        ant::cell_char(board, engine.ants(), x, y)
+                    } else {
+                        board.cell_char(x, y, &engine.rule()).ok().unwrap()
+                    }
+                } else {
+                    match universe.as_ref().map(|universe| universe.get(x as i64, y as i64)) {
+                        Some(true) => 'X',
+                        Some(false) => '_',
+                        None => ' ',
+                    }
+                };
+                if (x, y) == self.cursor {
+                    screen.push_str(&format!("\x1b[7m{}\x1b[0m", c));
+                } else {
+                    screen.push(c);
+                }
+            }
+            screen.push_str("\x1b[K\n");
+        }
+
+        let evaluated = match engine.evaluated() {
+            Some(evaluated) => format!("  Evaluated: {}", evaluated),
+            None => String::new(),
+        };
+        let status = format!("Generation: {}  Population: {}{}  Cursor: {} {}  {}",
+            engine.generation(), engine.population(), evaluated, self.cursor.0 + 1, self.cursor.1 + 1,
+            if self.playing { "Playing" } else { "Paused" });
+        let help = "Arrows: move  Space: toggle  w: wire  e: electron  s: step  p: play/pause  n: new  Esc: prompt  q, Ctrl-C: quit";
+        // lines longer than the terminal would wrap and scroll the screen
+        for (i, line) in [&status, help, &self.message].iter().enumerate() {
+            if i > 0 {
+                screen.push_str("\x1b[K\n");
+            }
+            screen.extend(line.chars().take(self.view.0));
+        }
+        // leaves the terminal cursor at the top
+        screen.push_str("\x1b[K\x1b[J\x1b[H");
+
+        print!("{}", screen);
+        stdout().flush().ok();
+    }
+}
+
+// Waits for keys, for the given milliseconds at most while playing or forever while paused
+fn read_keys(raw_mode: &terminal::RawMode, delay: Option<u64>) -> Vec<Key> {
+    let start = Instant::now();
+    loop {
+        let keys = parse_keys(&raw_mode.keys());
+        if !keys.is_empty() {
+            return keys;
+        }
+        if delay.is_some_and(|delay| start.elapsed() >= Duration::from_millis(delay)) {
+            return keys;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+}
+
+// Runs the full-screen mode until Esc, q or Ctrl-C is pressed, returns true if the game must quit
+pub fn run(engine: &mut engine::Engine) -> bool {
+    let raw_mode = match terminal::RawMode::enable() {
+        Some(raw_mode) => raw_mode,
+        None => {
+            println!("The TUI needs a terminal");
+            return false;
+        },
+    };
+    let (columns, rows) = terminal::size().unwrap_or((80, 24));
+    let view = (columns.max(1), rows.saturating_sub(STATUS_LINES).max(1));
+
+    if engine.board().is_none() && engine.universe().is_none() {
+        engine.process_command(command::Command::New { width: view.0, height: view.1, topology: board::Topology::Bounded, storage: board::Storage::Cells }).ok();
+    }
+
+    let mut tui = Tui {
+        cursor: (0, 0),
+        origin: (0, 0),
+        view,
+        playing: false,
+        message: String::new()
+    };
+
+    // alternate screen and hidden cursor
+    print!("\x1b[?1049h\x1b[?25l");
+
+    // Some(true) to quit the game, Some(false) to go back to the prompt
+    let mut leave = None;
+    while leave.is_none() {
+        tui.draw(engine);
+
+        let delay = if tui.playing { Some(engine.delay()) } else { None };
+        let keys = read_keys(&raw_mode, delay);
+
+        if keys.is_empty() {
+            tui.process(engine, command::Command::Play { generations: None });
+            if engine.population() == 0 {
+                tui.message = "The board is empty".to_string();
+                tui.playing = false;
+            } else if engine.is_static() {
+                tui.message = "The board is static".to_string();
+                tui.playing = false;
+            }
+        }
+
+        for key in keys {
+            if key == Key::Escape {
+                leave = Some(false);
+                break;
+            }
+            match tui.command(engine, key) {
+                Some(command::Command::Quit) => {
+                    leave = Some(true);
+                    break;
+                },
+                Some(command) => tui.process(engine, command),
+                None => tui.move_cursor(engine, key),
+            }
+        }
+    }
+
+    print!("\x1b[?25h\x1b[?1049l");
+    stdout().flush().ok();
+    leave.unwrap_or(false)
+}
+
+#[test]
+fn tui_parse_keys() {
+    assert_eq!(parse_keys(b""), vec![]);
+    assert_eq!(parse_keys(b"s p"), vec![Key::Char(b's'), Key::Char(b' '), Key::Char(b'p')]);
+    assert_eq!(parse_keys(b"\x1b[A\x1b[B\x1b[C\x1b[D"), vec![Key::Up, Key::Down, Key::Right, Key::Left]);
+    assert_eq!(parse_keys(b"\x1b"), vec![Key::Escape]);
+    assert_eq!(parse_keys(b"\x1b[Hq"), vec![Key::Char(b'q')]);
+    assert_eq!(parse_keys(b"\x03"), vec![Key::Char(CTRL_C)]);
+}
+
+// TUI with a 3x2 view in its initial state
+#[cfg(test)]
+fn new_tui() -> Tui {
+    Tui {
+        cursor: (0, 0),
+        origin: (0, 0),
+        view: (3, 2),
+        playing: false,
+        message: String::new()
+    }
+}
+
+// Command mapped to a key, as text since commands can not be compared
+#[cfg(test)]
+fn key_command(tui: &mut Tui, engine: &engine::Engine, key: Key) -> Option<String> {
+    tui.command(engine, key).map(|command| command.to_string())
+}
+
+#[test]
+fn tui_key_commands() {
+    let mut e = engine::Engine::new();
+    e.process_command(command::Command::New { width: 5, height: 4, topology: board::Topology::Torus, storage: board::Storage::Packed }).ok().unwrap();
+    let mut tui = new_tui();
+    tui.cursor = (2, 1);
+
+    assert_eq!(key_command(&mut tui, &e, Key::Char(b' ')), Some(command::Command::Toggle { x: 3, y: 2 }.to_string()));
+    assert_eq!(key_command(&mut tui, &e, Key::Char(b'\r')), Some(command::Command::Toggle { x: 3, y: 2 }.to_string()));
+    assert_eq!(key_command(&mut tui, &e, Key::Char(b'w')), Some(command::Command::Wire { x: 3, y: 2 }.to_string()));
+    assert_eq!(key_command(&mut tui, &e, Key::Char(b'e')), Some(command::Command::Electron { x: 3, y: 2 }.to_string()));
+    assert_eq!(key_command(&mut tui, &e, Key::Char(b'q')), Some(command::Command::Quit.to_string()));
+    assert_eq!(key_command(&mut tui, &e, Key::Char(CTRL_C)), Some(command::Command::Quit.to_string()));
+    assert_eq!(key_command(&mut tui, &e, Key::Up), None);
+    assert_eq!(key_command(&mut tui, &e, Key::Char(b'x')), None);
+
+    // play toggles, a step pauses
+    assert_eq!(key_command(&mut tui, &e, Key::Char(b'p')), None);
+    assert!(tui.playing);
+    assert_eq!(key_command(&mut tui, &e, Key::Char(b's')), Some(command::Command::Step.to_string()));
+    assert!(!tui.playing);
+
+    // a new game keeps the shape of the board and resets the cursor
+    assert_eq!(key_command(&mut tui, &e, Key::Char(b'n')), Some(command::Command::New { width: 5, height: 4, topology: board::Topology::Torus, storage: board::Storage::Packed }.to_string()));
+    assert_eq!(tui.cursor, (0, 0));
+
+    e.process_command(command::Command::Universe).ok().unwrap();
+    assert_eq!(key_command(&mut tui, &e, Key::Char(b'n')), Some(command::Command::Universe.to_string()));
+}
+
+#[test]
+fn tui_cursor_bounds() {
+    let mut e = engine::Engine::new();
+    e.process_command(command::Command::New { width: 5, height: 4, topology: board::Topology::Bounded, storage: board::Storage::Cells }).ok().unwrap();
+    let mut tui = new_tui();
+
+    tui.move_cursor(&e, Key::Up);
+    tui.move_cursor(&e, Key::Left);
+    assert_eq!(tui.cursor, (0, 0));
+
+    // the view scrolls to follow the cursor and stops at the edges of the board
+    for _ in 0..10 {
+        tui.move_cursor(&e, Key::Right);
+        tui.move_cursor(&e, Key::Down);
+    }
+    assert_eq!(tui.cursor, (4, 3));
+    assert_eq!(tui.origin, (2, 2));
+
+    tui.move_cursor(&e, Key::Up);
+    tui.move_cursor(&e, Key::Up);
+    tui.move_cursor(&e, Key::Up);
+    assert_eq!(tui.cursor, (4, 0));
+    assert_eq!(tui.origin, (2, 0));
+
+    // the toggle lands on the cell under the cursor
+    let command = tui.command(&e, Key::Char(b' ')).unwrap();
+    e.process_command(command).ok().unwrap();
+    assert!(e.board().unwrap().get(4, 0).ok().unwrap());
+
+    // the universe has no bounds to the right or down
+    e.process_command(command::Command::Universe).ok().unwrap();
+    for _ in 0..10 {
+        tui.move_cursor(&e, Key::Right);
+    }
+    assert_eq!(tui.cursor, (14, 0));
+    assert_eq!(tui.origin, (12, 0));
+}